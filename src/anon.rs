@@ -0,0 +1,96 @@
+use crate::{
+  hash::ipld_cid,
+  ipld_error::IpldError,
+  meta::Meta,
+  name::Name,
+  position::Pos,
+  uses::Uses,
+};
+
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
+use sp_std::{
+  borrow::ToOwned,
+  boxed::Box,
+  convert::TryInto,
+  vec::Vec,
+};
+
+use alloc::string::{
+  String,
+  ToString,
+};
+
+/// The anonymous part of a term: its structure with every binder name and
+/// source position removed, so that its CID only changes when the term does
+/// up to renaming
+#[derive(PartialEq, Clone, Debug)]
+pub enum Anon {
+  /// constructor tag and arguments
+  Ctor(String, Vec<Anon>),
+  /// the body of a binder
+  Bind(Box<Anon>),
+  /// de Bruijn index of a bound variable
+  Vari(u64),
+  /// multiplicity annotation
+  Uses(Uses),
+  /// CID of a referenced definition
+  Link(Cid),
+}
+
+impl Anon {
+  pub fn to_ipld(&self) -> Ipld {
+    match self {
+      Self::Ctor(nam, xs) => Ipld::List(vec![
+        Ipld::Integer(0),
+        Ipld::String(nam.clone()),
+        Ipld::List(xs.iter().map(Anon::to_ipld).collect()),
+      ]),
+      Self::Bind(x) => Ipld::List(vec![Ipld::Integer(1), x.to_ipld()]),
+      Self::Vari(idx) => {
+        Ipld::List(vec![Ipld::Integer(2), Ipld::Integer(*idx as i128)])
+      }
+      Self::Uses(uses) => Ipld::List(vec![Ipld::Integer(3), uses.to_ipld()]),
+      Self::Link(cid) => Ipld::List(vec![Ipld::Integer(4), Ipld::Link(*cid)]),
+    }
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [Ipld::Integer(0), Ipld::String(nam), Ipld::List(xs)] => {
+          let xs = xs.iter().map(Anon::from_ipld).collect::<Result<_, _>>()?;
+          Ok(Self::Ctor(nam.to_string(), xs))
+        }
+        [Ipld::Integer(1), x] => Ok(Self::Bind(Box::new(Anon::from_ipld(x)?))),
+        [Ipld::Integer(2), Ipld::Integer(idx)] => {
+          let idx: u64 = (*idx).try_into().map_err(IpldError::U64)?;
+          Ok(Self::Vari(idx))
+        }
+        [Ipld::Integer(3), uses] => Ok(Self::Uses(Uses::from_ipld(uses)?)),
+        [Ipld::Integer(4), Ipld::Link(cid)] => Ok(Self::Link(*cid)),
+        xs => Err(IpldError::Anon(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::Anon(xs.to_owned())),
+    }
+  }
+
+  /// Content address of the anonymous tree, stable under renaming and
+  /// reformatting
+  pub fn cid(&self) -> Cid { ipld_cid(&self.to_ipld()) }
+}
+
+/// Embeds a constructor from the embeddings of its arguments
+pub fn ctor(tag: &str, pos: Pos, xs: Vec<(Anon, Meta)>) -> (Anon, Meta) {
+  let (anon, meta) = xs.into_iter().unzip();
+  (Anon::Ctor(tag.to_string(), anon), Meta::Ctor(pos, meta))
+}
+
+/// Embeds a binder named `nam` around the embedding of its body
+pub fn bind(nam: &Name, (anon, meta): (Anon, Meta)) -> (Anon, Meta) {
+  (Anon::Bind(Box::new(anon)), Meta::Bind(nam.clone(), Box::new(meta)))
+}
+
+/// Embeds a multiplicity annotation
+pub fn uses(uses: Uses) -> (Anon, Meta) { (Anon::Uses(uses), Meta::Leaf) }
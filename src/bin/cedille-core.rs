@@ -0,0 +1,225 @@
+use test::{
+  diagnostic::{
+    color_enabled,
+    Diagnostic,
+  },
+  module::Def,
+  package::{
+    error::PackageError,
+    fs::Dir,
+  },
+  position::Pos,
+  print::{
+    doc::{
+      concat,
+      hang,
+      text,
+      Doc,
+    },
+    kind,
+    pure,
+    term,
+    typ_,
+    Config,
+    WIDTH,
+  },
+  pure::eval::{
+    eval,
+    Strategy,
+  },
+};
+
+use std::{
+  env,
+  fs,
+  process,
+};
+
+const USAGE: &str = "Usage: cedille-core <command> <file.cedc> [args]
+
+Commands:
+  check <file>                     typecheck a file and its imports
+  eval <file> <name> [--steps N]   normalize the erasure of a definition
+  fmt <file>                       format a file in place
+  hash <file>                      print the CID of each definition";
+
+/// Steps of normal-order evaluation before `eval` gives up
+const DEFAULT_STEPS: u64 = 1_000_000;
+
+/// A failed command. Usage errors exit with code 2, the others with 1.
+enum Failure {
+  Usage(String),
+  Package(Dir, Box<PackageError>),
+  Other(String),
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  match run(&args) {
+    Ok(()) => (),
+    Err(Failure::Usage(msg)) => {
+      eprintln!("{}\n\n{}", msg, USAGE);
+      process::exit(2)
+    }
+    Err(Failure::Package(dir, e)) => {
+      match e.pos().and_then(|(nam, _)| dir.source(nam).ok()) {
+        Some(src) => {
+          eprintln!("{}", Diagnostic::from(&*e).render(&src, color_enabled()))
+        }
+        None => eprintln!("Error: {}", e),
+      }
+      process::exit(1)
+    }
+    Err(Failure::Other(msg)) => {
+      eprintln!("Error: {}", msg);
+      process::exit(1)
+    }
+  }
+}
+
+fn run(args: &[String]) -> Result<(), Failure> {
+  let (cmd, file, rest) = match args {
+    [cmd, file, rest @ ..] => (cmd.as_str(), file, rest),
+    [cmd] if cmd == "help" || cmd == "--help" => {
+      println!("{}", USAGE);
+      return Ok(());
+    }
+    _ => return Err(Failure::Usage("Missing command or file".to_owned())),
+  };
+  let (dir, name) = Dir::open(file)
+    .ok_or_else(|| Failure::Usage(format!("Not a .cedc file: {}", file)))?;
+  let fail = |e| Failure::Package(dir.clone(), Box::new(e));
+  match (cmd, rest) {
+    ("check", []) => {
+      let (package, defs) = dir.build(&name).map_err(fail)?;
+      println!("{}: {} definitions, {}", name, defs.names.len(), package.cid());
+      Ok(())
+    }
+    ("eval", [def, opts @ ..]) => {
+      let limit = match opts {
+        [] => DEFAULT_STEPS,
+        [flag, n] if flag == "--steps" => n.parse().map_err(|_| {
+          Failure::Usage(format!("Invalid number of steps {}", n))
+        })?,
+        _ => return Err(Failure::Usage("Unexpected arguments".to_owned())),
+      };
+      let (_, defs) = dir.build(&name).map_err(fail)?;
+      let term =
+        defs.term_ref(def).and_then(|cid| defs.erased(&cid)).ok_or_else(
+          || Failure::Other(format!("No term definition {} in {}", def, name)),
+        )?;
+      let res = eval(&defs, &term, Strategy::NormalOrder, limit)
+        .map_err(|e| Failure::Other(e.to_string()))?;
+      println!(
+        "{}",
+        pure::print(&Config { width: WIDTH, ..Config::default() }, &res.term)
+      );
+      Ok(())
+    }
+    ("fmt", []) => {
+      let src = dir.source(&name).map_err(fail)?;
+      let (imports, _, module) = dir.parse(&name).map_err(fail)?;
+      let cfg = Config { width: WIDTH, ..Config::default() };
+      let stmts: Vec<(Pos, bool, String)> = imports
+        .iter()
+        .map(|(pos, _)| (*pos, true, verbatim(&src, *pos).to_owned()))
+        .chain(module.defs.iter().map(|def| {
+          let doc = def_doc(&cfg, def);
+          (def.pos(), false, doc.render(cfg.width, cfg.indent))
+        }))
+        .collect();
+      let out = format(&src, &stmts);
+      if out != src {
+        fs::write(dir.path(&name), out)
+          .map_err(|e| Failure::Other(e.to_string()))?;
+      }
+      Ok(())
+    }
+    ("hash", []) => {
+      let (_, _, module) = dir.parse(&name).map_err(fail)?;
+      for def in &module.defs {
+        let kind = match def {
+          Def::Term(..) => "def",
+          Def::Type(..) => "type",
+        };
+        println!("{} {} {}", kind, def.name(), def.cid());
+      }
+      Ok(())
+    }
+    ("check", _) | ("eval", _) | ("fmt", _) | ("hash", _) => {
+      Err(Failure::Usage(format!("Wrong arguments to {}", cmd)))
+    }
+    _ => Err(Failure::Usage(format!("Unknown command {}", cmd))),
+  }
+}
+
+/// A definition laid out as `def name : T = t;` or `type Name : K = T;`
+fn def_doc(cfg: &Config, def: &Def) -> Doc {
+  let (head, bod) = match def {
+    Def::Term(_, nam, typ, trm) => (
+      concat(vec![text(format!("def {} : ", nam)), typ_::doc(cfg, typ)]),
+      term::doc(cfg, trm),
+    ),
+    Def::Type(_, nam, knd, typ) => (
+      concat(vec![text(format!("type {} : ", nam)), kind::doc(cfg, knd)]),
+      typ_::doc(cfg, typ),
+    ),
+  };
+  hang(concat(vec![head, text(" =")]), concat(vec![bod, text(";")]))
+}
+
+/// The source text of a statement
+fn verbatim(src: &str, pos: Pos) -> &str {
+  match pos {
+    Pos::Some(pos) => {
+      src[pos.from_offset as usize..pos.upto_offset as usize].trim_end()
+    }
+    Pos::None => "",
+  }
+}
+
+/// Lays out the statements of a file, given with their positions, whether
+/// they are imports and their text: one import per line, then the definitions
+/// separated by blank lines. Comments stay above the statement that follows
+/// them, and the comments inside a reprinted definition move above it.
+fn format(src: &str, stmts: &[(Pos, bool, String)]) -> String {
+  let mut out = String::new();
+  let mut last = 0;
+  let mut prev_import = None;
+  for (pos, import, stmt) in stmts {
+    let (from, upto) = match pos {
+      Pos::Some(pos) => (pos.from_offset as usize, pos.upto_offset as usize),
+      Pos::None => continue,
+    };
+    match prev_import {
+      Some(true) if *import => out.push('\n'),
+      Some(_) => out.push_str("\n\n"),
+      None => (),
+    }
+    let mut above = comments(&src[last..from]);
+    if !import {
+      above.extend(comments(&src[from..upto]));
+    }
+    for comment in above {
+      out.push_str(comment);
+      out.push('\n');
+    }
+    out.push_str(stmt);
+    prev_import = Some(*import);
+    last = upto;
+  }
+  let trailing = comments(&src[last..]);
+  if prev_import.is_some() && !trailing.is_empty() {
+    out.push_str("\n\n");
+  }
+  out.push_str(&trailing.join("\n"));
+  if !out.is_empty() {
+    out.push('\n');
+  }
+  out
+}
+
+/// The line comments in a stretch of source
+fn comments(src: &str) -> Vec<&str> {
+  src.lines().filter_map(|l| Some(l[l.find("//")?..].trim_end())).collect()
+}
@@ -0,0 +1,19 @@
+use test::lsp::run;
+
+use std::{
+  io,
+  process,
+};
+
+fn main() {
+  let stdin = io::stdin();
+  let stdout = io::stdout();
+  match run(&mut stdin.lock(), &mut stdout.lock()) {
+    Ok(true) => (),
+    Ok(false) => process::exit(1),
+    Err(e) => {
+      eprintln!("Error: {}", e);
+      process::exit(1)
+    }
+  }
+}
@@ -0,0 +1,54 @@
+use test::{
+  diagnostic::{
+    color_enabled,
+    Diagnostic,
+  },
+  repl::Repl,
+};
+
+use std::io::{
+  self,
+  BufRead,
+  Write,
+};
+
+fn main() {
+  let mut repl = Repl::new();
+  let stdin = io::stdin();
+  let mut lines = stdin.lock().lines();
+  println!("Cedille Core. Type :help for the commands, :quit to leave.");
+  loop {
+    // definitions can span several lines, up to their closing `;`
+    let mut input = String::new();
+    let mut prompt = "> ";
+    loop {
+      print!("{}", prompt);
+      io::stdout().flush().ok();
+      let line = match lines.next() {
+        Some(Ok(line)) => line,
+        _ => return,
+      };
+      input.push_str(&line);
+      input.push('\n');
+      let trimmed = input.trim();
+      if trimmed.is_empty()
+        || trimmed.starts_with(':')
+        || trimmed.ends_with(';')
+      {
+        break;
+      }
+      prompt = "| ";
+    }
+    match input.trim() {
+      "" => continue,
+      ":quit" | ":q" => return,
+      _ => (),
+    }
+    match repl.run(&input) {
+      Ok(out) => println!("{}", out),
+      Err(e) => {
+        eprintln!("{}", Diagnostic::from(&e).render(&input, color_enabled()))
+      }
+    }
+  }
+}
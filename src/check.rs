@@ -0,0 +1,807 @@
+// Every type error carries the source position it is about, which alone is
+// as large as the lint allows, so the errors of the checker are large anyway
+#![allow(clippy::result_large_err)]
+
+pub mod ctx;
+pub mod error;
+mod util;
+
+use crate::{
+  check::{
+    ctx::Ctx,
+    error::{
+      Mismatch,
+      TypeError,
+    },
+    util::{
+      count_pure,
+      find_pure,
+      occurs_kind,
+    },
+  },
+  erase::erase,
+  hash::ipld_cid,
+  kind::Kind,
+  module::{
+    Def,
+    Defs,
+    Module,
+  },
+  name::Name,
+  position::Pos,
+  pure::{
+    conv_in,
+    eval::{
+      eval,
+      Strategy,
+    },
+    ConvResult,
+    Pure,
+  },
+  store::Store,
+  term::Term,
+  typ_::Type,
+  uses::Uses,
+};
+
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
+use sp_std::{
+  boxed::Box,
+  rc::Rc,
+};
+
+use alloc::string::ToString;
+
+/// β-steps allowed for each conversion check between pure terms
+pub const CONV_FUEL: u64 = 10_000;
+
+/// Checks that the free variables of a pure term are bound in `ctx` and that
+/// its references point to term definitions
+fn check_pure_scope(ctx: &Ctx, p: &Pure) -> Result<(), TypeError> {
+  fn go(p: &Pure, defs: &Defs, len: u64, depth: u64) -> Result<(), TypeError> {
+    match p {
+      Pure::Var(pos, n, i) => {
+        if *i >= len + depth {
+          Err(TypeError::UnboundVariable(*pos, n.clone(), *i))
+        }
+        else {
+          Ok(())
+        }
+      }
+      Pure::Lam(_, _, b) => go(b, defs, len, depth + 1),
+      Pure::App(_, f, a) => {
+        go(f, defs, len, depth)?;
+        go(a, defs, len, depth)
+      }
+      Pure::Ref(pos, n, cid) => match defs.get(cid) {
+        Some(Def::Term(..)) => Ok(()),
+        _ => Err(TypeError::UndefinedReference(*pos, n.clone())),
+      },
+    }
+  }
+  go(p, &ctx.defs, ctx.term_len(), 0)
+}
+
+/// Checks the number of relevant occurrences of a bound variable in the erased
+/// body `bod` of its binder at `pos` against its multiplicity
+fn check_uses(
+  pos: Pos,
+  nam: &Name,
+  uses: Uses,
+  bod: &Pure,
+) -> Result<(), TypeError> {
+  let count = count_pure(bod, 0);
+  let ok = match uses {
+    Uses::None => count == 0,
+    Uses::Once => count == 1,
+    Uses::Affi => count <= 1,
+    Uses::Many => true,
+  };
+  if ok {
+    Ok(())
+  }
+  else {
+    match find_pure(bod, 0) {
+      Some(var) if uses == Uses::None => {
+        Err(TypeError::ErasedUse(var, nam.clone(), pos))
+      }
+      _ => Err(TypeError::UsageViolation(pos, nam.clone(), uses, count)),
+    }
+  }
+}
+
+fn conv_pure(defs: &Defs, a: &Pure, b: &Pure) -> Option<bool> {
+  match conv_in(defs, a, b, CONV_FUEL) {
+    ConvResult::Equal => Some(true),
+    ConvResult::NotEqual => Some(false),
+    ConvResult::Unknown => None,
+  }
+}
+
+/// The normal form of `p` with its references unfolded, or `p` itself if it
+/// has none within `CONV_FUEL` steps
+fn normal_form(defs: &Defs, p: &Pure) -> Pure {
+  match eval(defs, p, Strategy::NormalOrder, CONV_FUEL) {
+    Ok(res) => res.term,
+    Err(_) => p.clone(),
+  }
+}
+
+fn assert_conv_pure(
+  defs: &Defs,
+  pos: Pos,
+  a: &Pure,
+  b: &Pure,
+) -> Result<(), TypeError> {
+  match conv_in(defs, a, b, CONV_FUEL) {
+    ConvResult::Equal => Ok(()),
+    ConvResult::NotEqual => Err(TypeError::NotConvertible(
+      pos,
+      a.clone(),
+      b.clone(),
+      Box::new((normal_form(defs, a), normal_form(defs, b))),
+    )),
+    ConvResult::Unknown => Err(TypeError::ConversionUndecided(pos)),
+  }
+}
+
+/// Reduces type-level β-redexes at the head of a type
+fn whnf_beta(t: &Type) -> Type {
+  match t {
+    Type::AppTy(pos, f, a) => match whnf_beta(f) {
+      Type::LamTy(_, _, _, b) => whnf_beta(&b.subst_ty(0, a)),
+      f => Type::AppTy(*pos, Box::new(f), a.clone()),
+    },
+    _ => t.clone(),
+  }
+}
+
+/// Replaces the reference at the head of a type by the body of its definition
+fn unfold_head(defs: &Defs, t: &Type) -> Option<Type> {
+  match t {
+    Type::Ref(_, _, cid) => match defs.get(cid)? {
+      Def::Type(_, _, _, typ) => Some(typ.clone()),
+      Def::Term(..) => None,
+    },
+    Type::AppTy(pos, f, a) => {
+      Some(Type::AppTy(*pos, Box::new(unfold_head(defs, f)?), a.clone()))
+    }
+    _ => None,
+  }
+}
+
+/// Reduces type-level β-redexes and unfolds references at the head of a type
+pub fn whnf_type(defs: &Defs, t: &Type) -> Type {
+  let t = whnf_beta(t);
+  match unfold_head(defs, &t) {
+    Some(t) => whnf_type(defs, &t),
+    None => t,
+  }
+}
+
+/// Compares two types up to type-level β, unfolding of references and
+/// conversion of the pure terms in equalities. Binder names are ignored.
+/// References are only unfolded when their heads fail to match.
+fn conv_type(defs: &Defs, a: &Type, b: &Type) -> Option<bool> {
+  let (a, b) = (whnf_beta(a), whnf_beta(b));
+  if conv_head(defs, &a, &b)? {
+    return Some(true);
+  }
+  match (unfold_head(defs, &a), unfold_head(defs, &b)) {
+    (Some(a), Some(b)) => conv_type(defs, &a, &b),
+    (Some(a), None) => conv_type(defs, &a, &b),
+    (None, Some(b)) => conv_type(defs, &a, &b),
+    (None, None) => Some(false),
+  }
+}
+
+fn conv_head(defs: &Defs, a: &Type, b: &Type) -> Option<bool> {
+  match (a, b) {
+    (Type::Var(_, _, i), Type::Var(_, _, j)) => Some(i == j),
+    (Type::Ref(_, _, a), Type::Ref(_, _, b)) => Some(a == b),
+    (Type::Lam(_, _, ta, ba), Type::Lam(_, _, tb, bb))
+    | (Type::Iota(_, _, ta, ba), Type::Iota(_, _, tb, bb)) => {
+      Some(conv_type(defs, ta, tb)? && conv_type(defs, ba, bb)?)
+    }
+    (Type::Pi(_, ua, _, ta, ba), Type::Pi(_, ub, _, tb, bb)) => {
+      Some(ua == ub && conv_type(defs, ta, tb)? && conv_type(defs, ba, bb)?)
+    }
+    (Type::LamTy(_, _, ka, ba), Type::LamTy(_, _, kb, bb))
+    | (Type::PiTy(_, _, ka, ba), Type::PiTy(_, _, kb, bb)) => {
+      Some(conv_kind(defs, ka, kb)? && conv_type(defs, ba, bb)?)
+    }
+    (Type::Eql(_, xa, ya), Type::Eql(_, xb, yb)) => {
+      Some(conv_pure(defs, xa, xb)? && conv_pure(defs, ya, yb)?)
+    }
+    (Type::AppTy(_, fa, aa), Type::AppTy(_, fb, ab)) => {
+      Some(conv_type(defs, fa, fb)? && conv_type(defs, aa, ab)?)
+    }
+    _ => Some(false),
+  }
+}
+
+fn conv_kind(defs: &Defs, a: &Kind, b: &Kind) -> Option<bool> {
+  match (a, b) {
+    (Kind::Type(_), Kind::Type(_)) => Some(true),
+    (Kind::Pi(_, ta, ka), Kind::Pi(_, tb, kb)) => {
+      Some(conv_type(defs, ta, tb)? && conv_kind(defs, ka, kb)?)
+    }
+    (Kind::PiTy(_, ja, ka), Kind::PiTy(_, jb, kb)) => {
+      Some(conv_kind(defs, ja, jb)? && conv_kind(defs, ka, kb)?)
+    }
+    _ => Some(false),
+  }
+}
+
+/// The failed conversion between the pure terms of two types that reduce to
+/// equalities
+fn eql_mismatch(
+  defs: &Defs,
+  pos: Pos,
+  exp: &Type,
+  fnd: &Type,
+) -> Option<Box<TypeError>> {
+  match (whnf_type(defs, exp), whnf_type(defs, fnd)) {
+    (Type::Eql(_, xa, ya), Type::Eql(_, xb, yb)) => {
+      assert_conv_pure(defs, pos, &xa, &xb)
+        .and_then(|_| assert_conv_pure(defs, pos, &ya, &yb))
+        .err()
+        .map(Box::new)
+    }
+    _ => None,
+  }
+}
+
+fn assert_conv_type(
+  ctx: &Ctx,
+  pos: Pos,
+  exp: &Type,
+  fnd: &Type,
+) -> Result<(), TypeError> {
+  match conv_type(&ctx.defs, exp, fnd) {
+    Some(true) => Ok(()),
+    Some(false) => Err(TypeError::TypeMismatch(
+      pos,
+      Box::new(Mismatch {
+        entries: ctx.entries.clone(),
+        expected: exp.clone(),
+        found: fnd.clone(),
+      }),
+      eql_mismatch(&ctx.defs, pos, exp, fnd),
+    )),
+    None => Err(TypeError::ConversionUndecided(pos)),
+  }
+}
+
+fn assert_conv_kind(
+  ctx: &Ctx,
+  pos: Pos,
+  exp: &Kind,
+  fnd: &Kind,
+) -> Result<(), TypeError> {
+  match conv_kind(&ctx.defs, exp, fnd) {
+    Some(true) => Ok(()),
+    Some(false) => Err(TypeError::KindMismatch(
+      pos,
+      Box::new(Mismatch {
+        entries: ctx.entries.clone(),
+        expected: exp.clone(),
+        found: fnd.clone(),
+      }),
+    )),
+    None => Err(TypeError::ConversionUndecided(pos)),
+  }
+}
+
+/// Checks that `t` is a type of terms, i.e. has kind `Type`
+fn check_star(ctx: &Ctx, t: &Type) -> Result<(), TypeError> {
+  let kind = check_kind(ctx, t)?;
+  assert_conv_kind(ctx, t.pos(), &Kind::Type(Pos::None), &kind)
+}
+
+fn expect_eql(
+  defs: &Defs,
+  pos: Pos,
+  t: Type,
+) -> Result<(Pure, Pure), TypeError> {
+  match whnf_type(defs, &t) {
+    Type::Eql(_, a, b) => Ok((*a, *b)),
+    _ => Err(TypeError::ExpectedEql(pos, t)),
+  }
+}
+
+/// Checks that a kind is well-formed in `ctx`
+pub fn check_kind_wf(ctx: &Ctx, kind: &Kind) -> Result<(), TypeError> {
+  match kind {
+    Kind::Type(_) => Ok(()),
+    Kind::Pi(_, a, k) => {
+      check_star(ctx, a)?;
+      check_kind_wf(ctx, k)
+    }
+    Kind::PiTy(_, j, k) => {
+      check_kind_wf(ctx, j)?;
+      check_kind_wf(ctx, k)
+    }
+  }
+}
+
+/// Infers the kind of a type
+pub fn check_kind(ctx: &Ctx, typ: &Type) -> Result<Kind, TypeError> {
+  match typ {
+    Type::Var(pos, n, i) => match ctx.lookup_type(*i) {
+      Some((_, kind)) => Ok(kind),
+      None => Err(TypeError::UnboundTypeVariable(*pos, n.clone(), *i)),
+    },
+    Type::Lam(pos, n, a, b) => {
+      check_star(ctx, a)?;
+      let kind =
+        check_kind(&ctx.bind_term(n.clone(), Uses::Many, *a.clone()), b)?;
+      if occurs_kind(&kind, Some(0), None) {
+        return Err(TypeError::DependentKind(*pos, n.clone()));
+      }
+      Ok(Kind::Pi(*pos, a.clone(), Box::new(kind.shift(-1, 0, 0, 0))))
+    }
+    Type::LamTy(pos, n, k, b) => {
+      check_kind_wf(ctx, k)?;
+      let kind = check_kind(&ctx.bind_type(n.clone(), *k.clone()), b)?;
+      if occurs_kind(&kind, None, Some(0)) {
+        return Err(TypeError::DependentKind(*pos, n.clone()));
+      }
+      Ok(Kind::PiTy(*pos, k.clone(), Box::new(kind.shift(0, 0, -1, 0))))
+    }
+    Type::Pi(pos, _, n, a, b) | Type::Iota(pos, n, a, b) => {
+      check_star(ctx, a)?;
+      check_star(&ctx.bind_term(n.clone(), Uses::Many, *a.clone()), b)?;
+      Ok(Kind::Type(*pos))
+    }
+    Type::PiTy(pos, n, k, b) => {
+      check_kind_wf(ctx, k)?;
+      check_star(&ctx.bind_type(n.clone(), *k.clone()), b)?;
+      Ok(Kind::Type(*pos))
+    }
+    Type::Eql(pos, a, b) => {
+      check_pure_scope(ctx, a)?;
+      check_pure_scope(ctx, b)?;
+      Ok(Kind::Type(*pos))
+    }
+    Type::AppTy(pos, f, a) => match check_kind(ctx, f)? {
+      Kind::PiTy(_, dom, cod) => {
+        let kind = check_kind(ctx, a)?;
+        assert_conv_kind(ctx, a.pos(), &dom, &kind)?;
+        Ok(*cod)
+      }
+      kind => Err(TypeError::ExpectedKindPiTy(*pos, kind)),
+    },
+    Type::Ref(pos, n, cid) => match ctx.defs.get(cid) {
+      Some(Def::Type(_, _, kind, _)) => Ok(kind.clone()),
+      _ => Err(TypeError::UndefinedReference(*pos, n.clone())),
+    },
+  }
+}
+
+/// Infers the type of a term
+pub fn infer(ctx: &Ctx, term: &Term) -> Result<Type, TypeError> {
+  match term {
+    Term::Var(pos, n, i) => match ctx.lookup_term(*i) {
+      Some((_, _, typ)) => Ok(typ),
+      None => Err(TypeError::UnboundVariable(*pos, n.clone(), *i)),
+    },
+    Term::Lam(pos, u, n, a, b) => {
+      check_star(ctx, a)?;
+      let typ = infer(&ctx.bind_term(n.clone(), *u, *a.clone()), b)?;
+      check_uses(*pos, n, *u, &erase(b))?;
+      Ok(Type::Pi(*pos, *u, n.clone(), a.clone(), Box::new(typ)))
+    }
+    Term::LamTy(pos, n, k, b) => {
+      check_kind_wf(ctx, k)?;
+      let typ = infer(&ctx.bind_type(n.clone(), *k.clone()), b)?;
+      Ok(Type::PiTy(*pos, n.clone(), k.clone(), Box::new(typ)))
+    }
+    Term::App(pos, u, f, a) => {
+      let fun = infer(ctx, f)?;
+      match whnf_type(&ctx.defs, &fun) {
+        Type::Pi(_, uses, _, dom, cod) => {
+          if uses != *u {
+            return Err(TypeError::UsesMismatch(*pos, uses, *u));
+          }
+          let arg = infer(ctx, a)?;
+          assert_conv_type(ctx, a.pos(), &dom, &arg)?;
+          Ok(cod.subst_tm(0, &erase(a)))
+        }
+        _ => Err(TypeError::ExpectedPi(f.pos(), fun)),
+      }
+    }
+    Term::AppTy(_, f, a) => {
+      let fun = infer(ctx, f)?;
+      match whnf_type(&ctx.defs, &fun) {
+        Type::PiTy(_, _, dom, cod) => {
+          let kind = check_kind(ctx, a)?;
+          assert_conv_kind(ctx, a.pos(), &dom, &kind)?;
+          Ok(cod.subst_ty(0, a))
+        }
+        _ => Err(TypeError::ExpectedPiTy(f.pos(), fun)),
+      }
+    }
+    Term::Intersect(pos, x, y, t) => {
+      check_star(ctx, t)?;
+      match whnf_type(&ctx.defs, t) {
+        Type::Iota(_, _, a, b) => {
+          let typ = infer(ctx, x)?;
+          assert_conv_type(ctx, x.pos(), &a, &typ)?;
+          let erased = erase(x);
+          let typ = infer(ctx, y)?;
+          assert_conv_type(ctx, y.pos(), &b.subst_tm(0, &erased), &typ)?;
+          assert_conv_pure(&ctx.defs, *pos, &erased, &erase(y))?;
+          Ok(*t.clone())
+        }
+        _ => Err(TypeError::ExpectedIota(t.pos(), *t.clone())),
+      }
+    }
+    Term::ProjectL(_, x) => {
+      let typ = infer(ctx, x)?;
+      match whnf_type(&ctx.defs, &typ) {
+        Type::Iota(_, _, a, _) => Ok(*a),
+        _ => Err(TypeError::ExpectedIota(x.pos(), typ)),
+      }
+    }
+    Term::ProjectR(_, x) => {
+      let typ = infer(ctx, x)?;
+      match whnf_type(&ctx.defs, &typ) {
+        Type::Iota(_, _, _, b) => Ok(b.subst_tm(0, &erase(x))),
+        _ => Err(TypeError::ExpectedIota(x.pos(), typ)),
+      }
+    }
+    Term::Let(pos, u, n, x, b) => {
+      let typ = infer(ctx, x)?;
+      let body = infer(&ctx.bind_term(n.clone(), *u, typ), b)?;
+      check_uses(*pos, n, *u, &erase(b))?;
+      Ok(body.subst_tm(0, &erase(x)))
+    }
+    Term::LetTy(_, _, k, a, b) => {
+      check_kind_wf(ctx, k)?;
+      let kind = check_kind(ctx, a)?;
+      assert_conv_kind(ctx, a.pos(), k, &kind)?;
+      infer(ctx, &b.subst_ty(0, a))
+    }
+    Term::Refl(pos, x, y) => {
+      check_pure_scope(ctx, x)?;
+      check_pure_scope(ctx, y)?;
+      Ok(Type::Eql(*pos, x.clone(), x.clone()))
+    }
+    Term::Sym(pos, e) => {
+      let (a, b) = expect_eql(&ctx.defs, e.pos(), infer(ctx, e)?)?;
+      Ok(Type::Eql(*pos, Box::new(b), Box::new(a)))
+    }
+    Term::Absurd(pos, t, e) => {
+      check_star(ctx, t)?;
+      let (a, b) = expect_eql(&ctx.defs, e.pos(), infer(ctx, e)?)?;
+      let var = |i| Pure::Var(Pos::None, Name::from("x"), i);
+      let lam = |b| {
+        let b = Pure::Lam(Pos::None, Name::from("y"), Box::new(b));
+        Pure::Lam(Pos::None, Name::from("x"), Box::new(b))
+      };
+      assert_conv_pure(&ctx.defs, *pos, &lam(var(1)), &a)?;
+      assert_conv_pure(&ctx.defs, *pos, &lam(var(0)), &b)?;
+      Ok(*t.clone())
+    }
+    Term::Rewrite(pos, e, p, y) => {
+      let (a, b) = expect_eql(&ctx.defs, e.pos(), infer(ctx, e)?)?;
+      match p.retag() {
+        Type::Lam(_, n, dom, motive) => {
+          check_star(ctx, &dom)?;
+          check_star(&ctx.bind_term(n, Uses::Many, *dom), &motive)?;
+          let typ = infer(ctx, y)?;
+          assert_conv_type(ctx, y.pos(), &motive.subst_tm(0, &b), &typ)?;
+          Ok(motive.subst_tm(0, &a))
+        }
+        _ => Err(TypeError::ExpectedMotive(*pos)),
+      }
+    }
+    Term::Cast(pos, e, t, z) => {
+      let (a, b) = expect_eql(&ctx.defs, e.pos(), infer(ctx, e)?)?;
+      check_pure_scope(ctx, z)?;
+      let typ = infer(ctx, t)?;
+      assert_conv_pure(&ctx.defs, *pos, &a, &erase(t))?;
+      assert_conv_pure(&ctx.defs, *pos, &b, z)?;
+      Ok(typ)
+    }
+    Term::Ref(pos, n, cid) => match ctx.defs.get(cid) {
+      Some(Def::Term(_, _, typ, _)) => Ok(typ.clone()),
+      _ => Err(TypeError::UndefinedReference(*pos, n.clone())),
+    },
+  }
+}
+
+/// Checks a definition against the global definitions before it
+pub fn check_def(defs: &Rc<Defs>, def: &Def) -> Result<(), TypeError> {
+  let ctx = Ctx::with_defs(defs.clone());
+  match def {
+    Def::Term(_, _, typ, trm) => {
+      check_star(&ctx, typ)?;
+      let fnd = infer(&ctx, trm)?;
+      assert_conv_type(&ctx, trm.pos(), typ, &fnd)
+    }
+    Def::Type(_, _, kind, typ) => {
+      check_kind_wf(&ctx, kind)?;
+      let fnd = check_kind(&ctx, typ)?;
+      assert_conv_kind(&ctx, typ.pos(), kind, &fnd)
+    }
+  }
+}
+
+/// Checks the definitions of a module in order, returning `defs` extended
+/// with them
+pub fn check_module(defs: &Defs, module: &Module) -> Result<Defs, TypeError> {
+  let mut defs = Rc::new(defs.clone());
+  for def in &module.defs {
+    check_def(&defs, def)?;
+    Rc::make_mut(&mut defs).insert(def.clone());
+  }
+  Ok(Rc::try_unwrap(defs).unwrap_or_else(|defs| (*defs).clone()))
+}
+
+/// Block recording that the definition at `cid` has been checked. A
+/// definition refers to others only by CID, so the outcome of checking it
+/// never changes.
+pub(crate) fn checked_block(cid: &Cid) -> Ipld {
+  Ipld::List(vec![Ipld::String("checked".to_string()), Ipld::Link(*cid)])
+}
+
+/// Checks the definitions of a module like `check_module`, but skips the ones
+/// `cache` records as checked and records the ones it checks. Anyone can write
+/// such a record, so `cache` must be a local store kept apart from the blocks
+/// of packages: a store that packages are shared through could vouch for
+/// definitions that were never checked.
+pub fn check_module_cached(
+  cache: &dyn Store,
+  defs: &Defs,
+  module: &Module,
+) -> Result<Defs, TypeError> {
+  let mut defs = Rc::new(defs.clone());
+  for def in &module.defs {
+    let cid = def.cid();
+    let checked = checked_block(&cid);
+    if !cache.contains(&ipld_cid(&checked)) {
+      check_def(&defs, def)?;
+      // a failed write only loses the cache entry
+      let _ = cache.put(&checked);
+    }
+    Rc::make_mut(&mut defs).insert(def.clone());
+  }
+  Ok(Rc::try_unwrap(defs).unwrap_or_else(|defs| (*defs).clone()))
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    parse::{
+      file::parse_file,
+      span::Span,
+      term::parse_term_telescope,
+      typ_::parse_type_telescope,
+      util::{
+        input_cid,
+        Ctx as NameCtx,
+      },
+    },
+    store::mem::MemStore,
+  };
+
+  fn parse(i: &str) -> Term {
+    parse_term_telescope(
+      input_cid(i),
+      Rc::default(),
+      NameCtx::new(),
+      NameCtx::new(),
+    )(Span::new(i))
+    .unwrap()
+    .1
+  }
+
+  fn parse_ty(i: &str) -> Type {
+    parse_type_telescope(
+      input_cid(i),
+      Rc::default(),
+      NameCtx::new(),
+      NameCtx::new(),
+    )(Span::new(i))
+    .unwrap()
+    .1
+  }
+
+  fn test(i: &str) -> Result<Type, TypeError> { infer(&Ctx::new(), &parse(i)) }
+
+  #[test]
+  fn test_infer_lam() {
+    assert_eq!(
+      test("λ (A: Type) (¹x: A) => x").unwrap(),
+      parse_ty("∀ (A: Type) (¹x: A) -> A")
+    );
+    assert_eq!(
+      test("λ (A: Type) (⁰x: A) (y: A) => y").unwrap(),
+      parse_ty("∀ (A: Type) (⁰x: A) (y: A) -> A")
+    );
+    assert_eq!(
+      test("λ (A: Type) (B: Type) (x: A) (y: B) => x").unwrap(),
+      parse_ty("∀ (A: Type) (B: Type) (x: A) (y: B) -> A")
+    );
+  }
+
+  #[test]
+  fn test_infer_uses() {
+    assert!(matches!(
+      test("λ (A: Type) (⁰x: A) => x"),
+      Err(TypeError::ErasedUse(..))
+    ));
+    assert!(matches!(
+      test("λ (A: Type) (B: Type) (f: ∀ (x: A) (y: A) -> B) (¹x: A) => f x x"),
+      Err(TypeError::UsageViolation(_, _, Uses::Once, 2))
+    ));
+    assert!(matches!(
+      test("λ (A: Type) (B: Type) (f: ∀ (¹x: A) -> B) (x: A) => f x"),
+      Err(TypeError::UsesMismatch(_, Uses::Once, Uses::Many))
+    ));
+    // erased arguments do not count as uses
+    assert!(test("λ (A: Type) (f: ∀ (⁰x: A) -> A) (⁰x: A) => f ⁰x").is_ok());
+  }
+
+  #[test]
+  fn test_infer_app() {
+    assert_eq!(
+      test("λ (A: Type) (x: A) => (λ (B: Type) (y: B) => y) A x").unwrap(),
+      parse_ty("∀ (A: Type) (x: A) -> A")
+    );
+    assert!(matches!(
+      test("λ (A: Type) (B: Type) (x: A) => (λ (y: B) => y) x"),
+      Err(TypeError::TypeMismatch(..))
+    ));
+    assert!(matches!(
+      test("λ (A: Type) (x: A) => x x"),
+      Err(TypeError::ExpectedPi(..))
+    ));
+  }
+
+  #[test]
+  fn test_infer_equality() {
+    assert_eq!(
+      test("λ (A: Type) (x: A) => refl x x").unwrap(),
+      parse_ty("∀ (A: Type) (x: A) -> {x ≃ x}")
+    );
+    assert_eq!(
+      test("λ (A: Type) (x: A) (y: A) (e: {x ≃ y}) => sym e").unwrap(),
+      parse_ty("∀ (A: Type) (x: A) (y: A) (e: {x ≃ y}) -> {y ≃ x}")
+    );
+    assert_eq!(
+      test(
+        "λ (A: Type) (x: A) (y: A) (e: {x ≃ y}) => rewrite e (λ (z: A) => {z \
+         ≃ y}) (refl y y)"
+      )
+      .unwrap(),
+      parse_ty("∀ (A: Type) (x: A) (y: A) (e: {x ≃ y}) -> {x ≃ y}")
+    );
+    assert!(matches!(
+      test(
+        "λ (A: Type) (x: A) (y: A) (e: {x ≃ y}) => rewrite e (λ (z: A) => {z \
+         ≃ y}) (refl x x)"
+      ),
+      Err(TypeError::TypeMismatch(..))
+    ));
+    assert_eq!(
+      test("λ (A: Type) (B: Type) (x: A) (y: B) (⁰e: {x ≃ y}) => cast e x y")
+        .unwrap(),
+      parse_ty("∀ (A: Type) (B: Type) (x: A) (y: B) (⁰e: {x ≃ y}) -> A")
+    );
+    assert!(test(
+      "λ (A: Type) (e: {(λ x y => x) ≃ (λ x y => y)}) => absurd (∀ (x: A) -> \
+       A) e"
+    )
+    .is_ok());
+    assert!(matches!(
+      test("λ (A: Type) (e: {(λ x y => x) ≃ (λ x y => x)}) => absurd A e"),
+      Err(TypeError::NotConvertible(..))
+    ));
+    // a fixed point unfolds until the fuel runs out
+    assert!(matches!(
+      test(
+        "λ (A: Type) (f: A) (e: {(λ x => f (x x)) (λ x => f (x x)) ≃ f}) => \
+         (λ (d: {f ((λ x => f (x x)) (λ x => f (x x))) ≃ f}) => d) e"
+      ),
+      Err(TypeError::ConversionUndecided(_))
+    ));
+  }
+
+  #[test]
+  fn test_infer_intersect() {
+    assert_eq!(
+      test(
+        "λ (A: Type) (x: A) => projectR (intersect x (refl x x) (ι (y: A) -> \
+         {y ≃ x}))"
+      )
+      .unwrap(),
+      parse_ty("∀ (A: Type) (x: A) -> {x ≃ x}")
+    );
+    assert!(matches!(
+      test(
+        "λ (A: Type) (x: A) (y: A) => intersect x (refl y y) (ι (z: A) -> {z \
+         ≃ z})"
+      ),
+      Err(TypeError::TypeMismatch(..))
+    ));
+  }
+
+  #[test]
+  fn test_infer_let() {
+    assert_eq!(
+      test("λ (A: Type) (x: A) => let T: Type = A in let y = x in y").unwrap(),
+      parse_ty("∀ (A: Type) (x: A) -> A")
+    );
+  }
+
+  #[test]
+  fn test_check_kind() {
+    fn test(i: &str) -> Result<Kind, TypeError> {
+      check_kind(&Ctx::new(), &parse_ty(i))
+    }
+    assert!(matches!(
+      test("λ (A: Type) (B: Type) => ∀ (x: A) -> B"),
+      Ok(Kind::PiTy(..))
+    ));
+    assert!(matches!(
+      test("∀ (F: Π Type -> Type) (A: Type) -> F A"),
+      Ok(Kind::Type(_))
+    ));
+    assert!(matches!(
+      test("∀ (A: Type) -> A A"),
+      Err(TypeError::ExpectedKindPiTy(..))
+    ));
+    assert!(check_kind_wf(&Ctx::new(), &Kind::Type(Pos::None)).is_ok());
+  }
+
+  #[test]
+  fn test_check_module() {
+    fn test(i: &str) -> Result<Defs, TypeError> {
+      let module =
+        parse_file(input_cid(i), Rc::default())(Span::new(i)).unwrap().1;
+      check_module(&Defs::new(), &module)
+    }
+    let bool = "type Bool : Type = ∀ (A: Type) (t: A) (f: A) -> A;
+       def true : Bool = λ (A: Type) (t: A) (f: A) => t;
+       def false : Bool = λ (A: Type) (t: A) (f: A) => f;
+       def not : ∀ (b: Bool) -> Bool = λ (b: Bool) => b Bool false true;";
+    let defs = test(bool).unwrap();
+    assert_eq!(defs.defs.len(), 4);
+    assert!(defs.term_ref("not").is_some());
+    // references unfold in types and in the pure terms of equalities
+    let ok = |i: &str| test(&format!("{}\n{}", bool, i));
+    assert!(ok("def t : ∀ (A: Type) (t: A) (f: A) -> A = true;").is_ok());
+    assert!(ok("def e : {not true ≃ false} = refl false false;").is_ok());
+    assert!(matches!(
+      ok("def e : {not true ≃ true} = refl true true;"),
+      Err(TypeError::TypeMismatch(..))
+    ));
+    assert!(matches!(
+      ok("def b : Bool = not;"),
+      Err(TypeError::TypeMismatch(..))
+    ));
+  }
+
+  #[test]
+  fn test_check_module_cached() {
+    let module = |i: &str| {
+      parse_file(input_cid(i), Rc::default())(Span::new(i)).unwrap().1
+    };
+    let cache = MemStore::new();
+    let good = module("type T : Type = ∀ (A: Type) (x: A) -> A;");
+    let bad = module(
+      "def f : ∀ (A: Type) (x: A) -> A = λ (A: Type) (x: A) (y: A) => x;",
+    );
+    assert!(check_module_cached(&cache, &Defs::new(), &good).is_ok());
+    assert!(check_module_cached(&cache, &Defs::new(), &bad).is_err());
+    // only the checked definition is recorded
+    assert_eq!(cache.blocks.borrow().len(), 1);
+    assert!(cache.contains(&ipld_cid(&checked_block(&good.defs[0].cid()))));
+  }
+}
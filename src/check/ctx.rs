@@ -0,0 +1,98 @@
+use crate::{
+  kind::Kind,
+  module::Defs,
+  name::Name,
+  typ_::Type,
+  uses::Uses,
+};
+
+use sp_im::vector::Vector;
+use sp_std::rc::Rc;
+
+/// A single typing assumption
+#[derive(Clone, Debug)]
+pub enum Entry {
+  /// term variable with its multiplicity and type
+  Term(Name, Uses, Type),
+  /// type variable with its kind
+  Type(Name, Kind),
+}
+
+/// Typing context. The front of `entries` is the innermost binder, and each
+/// stored type or kind is valid in the context of the entries behind it.
+/// `defs` holds the global definitions that references point to.
+#[derive(Clone, Debug, Default)]
+pub struct Ctx {
+  pub entries: Vector<Entry>,
+  pub defs: Rc<Defs>,
+}
+
+impl Ctx {
+  pub fn new() -> Self { Ctx { entries: Vector::new(), defs: Rc::default() } }
+
+  pub fn with_defs(defs: Rc<Defs>) -> Self {
+    Ctx { entries: Vector::new(), defs }
+  }
+
+  pub fn bind_term(&self, nam: Name, uses: Uses, typ: Type) -> Self {
+    let mut entries = self.entries.clone();
+    entries.push_front(Entry::Term(nam, uses, typ));
+    Ctx { entries, defs: self.defs.clone() }
+  }
+
+  pub fn bind_type(&self, nam: Name, kind: Kind) -> Self {
+    let mut entries = self.entries.clone();
+    entries.push_front(Entry::Type(nam, kind));
+    Ctx { entries, defs: self.defs.clone() }
+  }
+
+  /// Number of term variables in scope
+  pub fn term_len(&self) -> u64 {
+    self.entries.iter().filter(|e| matches!(e, Entry::Term(..))).count() as u64
+  }
+
+  /// Number of type variables in scope
+  pub fn type_len(&self) -> u64 {
+    self.entries.iter().filter(|e| matches!(e, Entry::Type(..))).count() as u64
+  }
+
+  /// Looks up the type of the term variable with de Bruijn index `idx`,
+  /// shifted into the current context
+  pub fn lookup_term(&self, idx: u64) -> Option<(Name, Uses, Type)> {
+    let mut seen_tys = 0;
+    let mut seen_tms = 0;
+    for entry in self.entries.iter() {
+      match entry {
+        Entry::Term(nam, uses, typ) => {
+          if seen_tms == idx {
+            let typ = typ.shift((idx + 1) as i64, 0, seen_tys, 0);
+            return Some((nam.clone(), *uses, typ));
+          }
+          seen_tms += 1;
+        }
+        Entry::Type(..) => seen_tys += 1,
+      }
+    }
+    None
+  }
+
+  /// Looks up the kind of the type variable with de Bruijn index `idx`,
+  /// shifted into the current context
+  pub fn lookup_type(&self, idx: u64) -> Option<(Name, Kind)> {
+    let mut seen_tys = 0;
+    let mut seen_tms = 0;
+    for entry in self.entries.iter() {
+      match entry {
+        Entry::Type(nam, kind) => {
+          if seen_tys == idx {
+            let kind = kind.shift(seen_tms, 0, (idx + 1) as i64, 0);
+            return Some((nam.clone(), kind));
+          }
+          seen_tys += 1;
+        }
+        Entry::Term(..) => seen_tms += 1,
+      }
+    }
+    None
+  }
+}
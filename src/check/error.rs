@@ -0,0 +1,138 @@
+use crate::{
+  check::ctx::Entry,
+  kind::Kind,
+  name::Name,
+  position::Pos,
+  pure::Pure,
+  typ_::Type,
+  uses::Uses,
+};
+
+use sp_im::vector::Vector;
+use sp_std::{
+  boxed::Box,
+  fmt,
+};
+
+/// The expected and the found type or kind of a mismatch, with the typing
+/// context they are in
+#[derive(Clone, Debug)]
+pub struct Mismatch<T> {
+  pub entries: Vector<Entry>,
+  pub expected: T,
+  pub found: T,
+}
+
+#[derive(Clone, Debug)]
+pub enum TypeError {
+  UnboundVariable(Pos, Name, u64),
+  UnboundTypeVariable(Pos, Name, u64),
+  /// the mismatched types, and the failed conversion of the pure terms when
+  /// both are equalities
+  TypeMismatch(Pos, Box<Mismatch<Type>>, Option<Box<TypeError>>),
+  KindMismatch(Pos, Box<Mismatch<Kind>>),
+  ExpectedPi(Pos, Type),
+  ExpectedPiTy(Pos, Type),
+  ExpectedIota(Pos, Type),
+  ExpectedEql(Pos, Type),
+  ExpectedKindPiTy(Pos, Kind),
+  ExpectedMotive(Pos),
+  /// binder multiplicity, argument multiplicity
+  UsesMismatch(Pos, Uses, Uses),
+  /// variable, declared multiplicity, occurrences in the erased body
+  UsageViolation(Pos, Name, Uses, u64),
+  /// erased variable occurring in the erased body, and its binder
+  ErasedUse(Pos, Name, Pos),
+  /// the two terms, then their normal forms
+  NotConvertible(Pos, Pure, Pure, Box<(Pure, Pure)>),
+  ConversionUndecided(Pos),
+  DependentKind(Pos, Name),
+  /// reference whose definition is missing or of the wrong sort
+  UndefinedReference(Pos, Name),
+}
+
+impl TypeError {
+  pub fn pos(&self) -> Pos {
+    match self {
+      Self::UnboundVariable(pos, ..)
+      | Self::UnboundTypeVariable(pos, ..)
+      | Self::TypeMismatch(pos, ..)
+      | Self::KindMismatch(pos, ..)
+      | Self::ExpectedPi(pos, ..)
+      | Self::ExpectedPiTy(pos, ..)
+      | Self::ExpectedIota(pos, ..)
+      | Self::ExpectedEql(pos, ..)
+      | Self::ExpectedKindPiTy(pos, ..)
+      | Self::ExpectedMotive(pos)
+      | Self::UsesMismatch(pos, ..)
+      | Self::UsageViolation(pos, ..)
+      | Self::ErasedUse(pos, ..)
+      | Self::NotConvertible(pos, ..)
+      | Self::ConversionUndecided(pos)
+      | Self::DependentKind(pos, ..)
+      | Self::UndefinedReference(pos, ..) => *pos,
+    }
+  }
+}
+
+impl fmt::Display for TypeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::UnboundVariable(_, nam, idx) => {
+        write!(f, "Unbound term variable {} at index {}", nam, idx)
+      }
+      Self::UnboundTypeVariable(_, nam, idx) => {
+        write!(f, "Unbound type variable {} at index {}", nam, idx)
+      }
+      Self::TypeMismatch(_, m, _) => {
+        write!(f, "Type mismatch: expected {}, found {}", m.expected, m.found)
+      }
+      Self::KindMismatch(_, m) => {
+        write!(f, "Kind mismatch: expected {}, found {}", m.expected, m.found)
+      }
+      Self::ExpectedPi(_, typ) => {
+        write!(f, "Expected a function type, found {}", typ)
+      }
+      Self::ExpectedPiTy(_, typ) => {
+        write!(f, "Expected a type abstraction, found {}", typ)
+      }
+      Self::ExpectedIota(_, typ) => {
+        write!(f, "Expected a dependent intersection, found {}", typ)
+      }
+      Self::ExpectedEql(_, typ) => {
+        write!(f, "Expected an equality, found {}", typ)
+      }
+      Self::ExpectedKindPiTy(_, kind) => {
+        write!(f, "Expected a type constructor kind, found {}", kind)
+      }
+      Self::ExpectedMotive(_) => {
+        write!(f, "The rewrite motive must be a type abstraction")
+      }
+      Self::UsesMismatch(_, exp, fnd) => {
+        write!(f, "Multiplicity mismatch: expected {:?}, found {:?}", exp, fnd)
+      }
+      Self::UsageViolation(_, nam, uses, count) => {
+        write!(
+          f,
+          "The variable {} has multiplicity {:?} but is used {} times",
+          nam, uses, count
+        )
+      }
+      Self::ErasedUse(_, nam, _) => {
+        write!(f, "The erased variable {} occurs in the erased term", nam)
+      }
+      Self::NotConvertible(_, a, b, ..) => {
+        write!(f, "The terms {} and {} are not convertible", a, b)
+      }
+      Self::ConversionUndecided(_) => {
+        write!(f, "Conversion check ran out of fuel")
+      }
+      Self::DependentKind(_, nam) => {
+        write!(f, "The kind of a type abstraction cannot depend on {}", nam)
+      }
+      Self::UndefinedReference(_, nam) => {
+        write!(f, "The reference {} points to no matching definition", nam)
+      }
+    }
+  }
+}
@@ -0,0 +1,69 @@
+use crate::{
+  kind::BaseKind,
+  position::Pos,
+  pure::Pure,
+  term::Tm,
+  typ_::BaseType,
+};
+
+/// Counts the occurrences of the free variable `j` in `p`
+pub fn count_pure(p: &Pure, j: u64) -> u64 {
+  match p {
+    Pure::Var(_, _, i) => (*i == j) as u64,
+    Pure::Lam(_, _, b) => count_pure(b, j + 1),
+    Pure::App(_, f, a) => count_pure(f, j) + count_pure(a, j),
+    Pure::Ref(..) => 0,
+  }
+}
+
+/// The position of the leftmost occurrence of the free variable `j` in `p`
+pub fn find_pure(p: &Pure, j: u64) -> Option<Pos> {
+  match p {
+    Pure::Var(pos, _, i) if *i == j => Some(*pos),
+    Pure::Var(..) | Pure::Ref(..) => None,
+    Pure::Lam(_, _, b) => find_pure(b, j + 1),
+    Pure::App(_, f, a) => find_pure(f, j).or_else(|| find_pure(a, j)),
+  }
+}
+
+/// Whether the free term variable `jt` or the free type variable `jy` occurs
+/// in `t`
+pub fn occurs_type<const T: Tm>(
+  t: &BaseType<T>,
+  jt: Option<u64>,
+  jy: Option<u64>,
+) -> bool {
+  let under_tm = jt.map(|j| j + 1);
+  let under_ty = jy.map(|j| j + 1);
+  match t {
+    BaseType::Var(_, _, i) => jy == Some(*i),
+    BaseType::Ref(..) => false,
+    BaseType::Lam(_, _, a, b)
+    | BaseType::Pi(_, _, _, a, b)
+    | BaseType::Iota(_, _, a, b) => {
+      occurs_type(a, jt, jy) || occurs_type(b, under_tm, jy)
+    }
+    BaseType::LamTy(_, _, k, b) | BaseType::PiTy(_, _, k, b) => {
+      occurs_kind(k, jt, jy) || occurs_type(b, jt, under_ty)
+    }
+    BaseType::Eql(_, a, b) => match jt {
+      Some(j) => count_pure(a, j) + count_pure(b, j) > 0,
+      None => false,
+    },
+    BaseType::AppTy(_, f, a) => {
+      occurs_type(f, jt, jy) || occurs_type(a, jt, jy)
+    }
+  }
+}
+
+pub fn occurs_kind<const T: Tm>(
+  k: &BaseKind<T>,
+  jt: Option<u64>,
+  jy: Option<u64>,
+) -> bool {
+  match k {
+    BaseKind::Type(_) => false,
+    BaseKind::Pi(_, a, b) => occurs_type(a, jt, jy) || occurs_kind(b, jt, jy),
+    BaseKind::PiTy(_, a, b) => occurs_kind(a, jt, jy) || occurs_kind(b, jt, jy),
+  }
+}
@@ -0,0 +1,689 @@
+use crate::{
+  check::{
+    ctx::Entry,
+    error::TypeError,
+  },
+  name::Name,
+  package::error::PackageError,
+  parse::{
+    error::{
+      ParseError,
+      ParseErrorKind,
+    },
+    span::Span,
+  },
+  position::Pos,
+  print::{
+    kind,
+    print_name,
+    pure,
+    typ_,
+    Config,
+  },
+  pure::Pure,
+  uses::Uses,
+};
+
+use sp_im::vector::Vector;
+
+use alloc::string::{
+  String,
+  ToString,
+};
+
+use sp_std::{
+  cmp,
+  fmt,
+  vec::Vec,
+};
+
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether to render diagnostics in color on standard error: only when it is
+/// a terminal, `NO_COLOR` is unset and the terminal supports it
+#[cfg(feature = "std")]
+pub fn color_enabled() -> bool {
+  use std::io::IsTerminal;
+  use_color(
+    std::io::stderr().is_terminal(),
+    std::env::var_os("NO_COLOR").is_some(),
+    std::env::var("TERM").ok().as_deref(),
+  )
+}
+
+/// Whether to render in color on an output that is a `terminal` or not, given
+/// whether `NO_COLOR` is set and the value of `TERM`
+pub fn use_color(terminal: bool, no_color: bool, term: Option<&str>) -> bool {
+  terminal && !no_color && matches!(term, Some(term) if term != "dumb")
+}
+
+/// A span of the source, between two byte offsets, with a message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+  pub from: usize,
+  pub upto: usize,
+  pub message: String,
+  /// whether the span is where the error is, rather than related context
+  pub primary: bool,
+}
+
+/// An error as shown to the user: a title, labelled spans of the source,
+/// and notes and suggestions that explain it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+  pub title: String,
+  pub labels: Vec<Label>,
+  pub notes: Vec<String>,
+  pub helps: Vec<String>,
+}
+
+impl Diagnostic {
+  pub fn error(title: impl ToString) -> Self {
+    Diagnostic {
+      title: title.to_string(),
+      labels: Vec::new(),
+      notes: Vec::new(),
+      helps: Vec::new(),
+    }
+  }
+
+  /// Adds the span where the error is
+  pub fn primary(
+    mut self,
+    from: usize,
+    upto: usize,
+    msg: impl ToString,
+  ) -> Self {
+    let message = msg.to_string();
+    self.labels.push(Label { from, upto, message, primary: true });
+    self
+  }
+
+  /// Adds a span that the error relates to
+  pub fn secondary(
+    mut self,
+    from: usize,
+    upto: usize,
+    msg: impl ToString,
+  ) -> Self {
+    let message = msg.to_string();
+    self.labels.push(Label { from, upto, message, primary: false });
+    self
+  }
+
+  /// Adds the span of `pos`, if it is known
+  pub fn primary_at(self, pos: Pos, msg: impl ToString) -> Self {
+    match pos {
+      Pos::Some(p) => {
+        self.primary(p.from_offset as usize, p.upto_offset as usize, msg)
+      }
+      Pos::None => self,
+    }
+  }
+
+  /// Adds the related span of `pos`, if it is known
+  pub fn secondary_at(self, pos: Pos, msg: impl ToString) -> Self {
+    match pos {
+      Pos::Some(p) => {
+        self.secondary(p.from_offset as usize, p.upto_offset as usize, msg)
+      }
+      Pos::None => self,
+    }
+  }
+
+  pub fn note(mut self, msg: impl ToString) -> Self {
+    self.notes.push(msg.to_string());
+    self
+  }
+
+  /// Adds a suggestion of how to fix the error
+  pub fn help(mut self, msg: impl ToString) -> Self {
+    self.helps.push(msg.to_string());
+    self
+  }
+
+  /// Renders the diagnostic with the lines of `src` that its labels point
+  /// into, in ANSI colors if `color`:
+  ///
+  /// ```text
+  /// error: Undefined reference y
+  ///  --> 1:13
+  ///   |
+  /// 1 | def y : T = y;
+  ///   |             ^ not in scope
+  ///   |
+  ///   = help: did you mean `x`?
+  /// ```
+  pub fn render(&self, src: &str, color: bool) -> String {
+    let paint = |code: &str, s: &str| {
+      if color {
+        format!("{}{}{}", code, s, RESET)
+      }
+      else {
+        s.to_string()
+      }
+    };
+    let mut out = String::new();
+    let title = paint(BOLD, &self.title);
+    out.push_str(&format!("{}{}", paint(RED, "error"), paint(BOLD, ": ")));
+    out.push_str(&title);
+    let lines = Lines::new(src);
+    let mut spans: Vec<_> = self
+      .labels
+      .iter()
+      .map(|l| {
+        (l, lines.locate(l.from), lines.locate(cmp::max(l.from, l.upto)))
+      })
+      .collect();
+    spans.sort_by_key(|(l, from, _)| (*from, !l.primary));
+    let gutter = spans
+      .iter()
+      .map(|(_, _, (line, _))| (line + 1).to_string().len())
+      .max()
+      .unwrap_or(0);
+    let bar = |out: &mut String| {
+      out.push('\n');
+      out.push_str(&paint(BLUE, &format!("{:w$} |", "", w = gutter)));
+    };
+    let main = spans.iter().find(|(l, ..)| l.primary).or_else(|| spans.first());
+    if let Some((_, (line, col), _)) = main {
+      let arrow = paint(BLUE, "-->");
+      out.push_str(&format!(
+        "\n{:w$}{} {}:{}",
+        "",
+        arrow,
+        line + 1,
+        col + 1,
+        w = gutter
+      ));
+      bar(&mut out);
+    }
+    // the lines with a label, each followed by the underlines on it
+    let mut shown: Vec<usize> = spans
+      .iter()
+      .flat_map(|(_, (from, _), (upto, _))| vec![*from, *upto])
+      .collect();
+    shown.sort_unstable();
+    shown.dedup();
+    let mut last = None;
+    for line in shown {
+      if matches!(last, Some(l) if l + 1 < line) {
+        out.push_str(&format!("\n{}", paint(BLUE, "...")));
+      }
+      last = Some(line);
+      let text = lines.get(line);
+      let num = format!("{:>w$} |", line + 1, w = gutter);
+      out.push_str(&format!("\n{} {}", paint(BLUE, &num), text));
+      for (label, (fl, fc), (ul, uc)) in &spans {
+        let (start, end, msg) = match (line == *fl, line == *ul) {
+          (true, true) => (*fc, cmp::max(*uc, fc + 1), label.message.as_str()),
+          (true, false) => (*fc, cmp::max(text.chars().count(), fc + 1), ""),
+          (false, true) => {
+            let indent = text.chars().take_while(|c| c.is_whitespace()).count();
+            (indent, cmp::max(*uc, indent + 1), label.message.as_str())
+          }
+          (false, false) => continue,
+        };
+        let (mark, code) = if label.primary { ('^', RED) } else { ('-', BLUE) };
+        let marks: String = (start..end).map(|_| mark).collect();
+        let under =
+          if msg.is_empty() { marks } else { format!("{} {}", marks, msg) };
+        bar(&mut out);
+        out.push_str(&format!(
+          "{:s$}{}",
+          "",
+          paint(code, &under),
+          s = start + 1
+        ));
+      }
+    }
+    if main.is_some() && !(self.notes.is_empty() && self.helps.is_empty()) {
+      bar(&mut out);
+    }
+    let notes = self.notes.iter().map(|n| ("note", n));
+    for (kind, msg) in notes.chain(self.helps.iter().map(|h| ("help", h))) {
+      let eq = paint(BLUE, "=");
+      out.push_str(&format!(
+        "\n{:w$} {} {}: {}",
+        "",
+        eq,
+        paint(BOLD, kind),
+        msg,
+        w = gutter
+      ));
+    }
+    out
+  }
+}
+
+/// The title, notes and suggestions, without the source
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.title)?;
+    for note in &self.notes {
+      write!(f, "\nnote: {}", note)?;
+    }
+    for help in &self.helps {
+      write!(f, "\nhelp: {}", help)?;
+    }
+    Ok(())
+  }
+}
+
+/// The lines of a source, to find the line and column of a byte offset
+struct Lines<'a> {
+  src: &'a str,
+  /// the byte offset of the start of each line
+  starts: Vec<usize>,
+}
+
+impl<'a> Lines<'a> {
+  fn new(src: &'a str) -> Self {
+    let starts = Some(0)
+      .into_iter()
+      .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+      .collect();
+    Lines { src, starts }
+  }
+
+  /// The line and the column in characters of a byte offset, both from 0
+  fn locate(&self, offset: usize) -> (usize, usize) {
+    let offset = cmp::min(offset, self.src.len());
+    let line = match self.starts.binary_search(&offset) {
+      Ok(line) => line,
+      Err(next) => next - 1,
+    };
+    let start = self.starts[line];
+    let col = self.src.get(start..offset).map_or(0, |s| s.chars().count());
+    (line, col)
+  }
+
+  fn get(&self, line: usize) -> &'a str {
+    let start = self.starts[line];
+    let end = self.starts.get(line + 1).map_or(self.src.len(), |e| e - 1);
+    self.src[start..end].trim_end_matches('\r')
+  }
+}
+
+/// The number of single character insertions, deletions and substitutions
+/// that turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut row: Vec<usize> = (0..=b.len()).collect();
+  for (i, x) in a.chars().enumerate() {
+    let mut diag = row[0];
+    row[0] = i + 1;
+    for (j, y) in b.iter().enumerate() {
+      let next = cmp::min(
+        cmp::min(row[j] + 1, row[j + 1] + 1),
+        diag + if x == *y { 0 } else { 1 },
+      );
+      diag = row[j + 1];
+      row[j + 1] = next;
+    }
+  }
+  row[b.len()]
+}
+
+/// The name among `names` closest to the misspelled `nam`, if one is close
+/// enough to be what was meant
+pub fn did_you_mean<'a>(
+  nam: &str,
+  names: impl IntoIterator<Item = &'a Name>,
+) -> Option<&'a Name> {
+  let limit = cmp::max(1, nam.chars().count() / 3);
+  names
+    .into_iter()
+    .filter(|x| &***x != nam)
+    .map(|x| (edit_distance(nam, x), x))
+    .filter(|(d, _)| *d <= limit)
+    .min_by_key(|(d, _)| *d)
+    .map(|(_, x)| x)
+}
+
+impl<'a> From<&ParseError<Span<'a>>> for Diagnostic {
+  fn from(e: &ParseError<Span<'a>>) -> Self {
+    let mut kinds = e.errors.iter().filter(|k| !k.is_nom_err());
+    let mut diag = match kinds.next() {
+      Some(kind) => Diagnostic::error(kind),
+      None => Diagnostic::error("Syntax error"),
+    };
+    let from = e.input.location_offset();
+    let next = e.input.fragment().chars().next();
+    // the parsers report an undefined reference at the end of its name
+    let (from, upto, msg) = match (e.errors.first(), e.expected, next) {
+      (Some(ParseErrorKind::UndefinedReference(nam, _)), ..) => {
+        (from.saturating_sub(nam.len()), from, "not in scope".to_string())
+      }
+      (_, Some(exp), _) => (from, from, format!("expected a {}", exp)),
+      (_, None, Some(c)) => {
+        (from, from + c.len_utf8(), "unexpected input".to_string())
+      }
+      (_, None, None) => (from, from, "unexpected end of input".to_string()),
+    };
+    diag = diag.primary(from, upto, msg);
+    for kind in kinds {
+      diag = diag.note(kind);
+    }
+    for kind in &e.errors {
+      if let ParseErrorKind::UndefinedReference(nam, ctx) = kind {
+        if let Some(x) = did_you_mean(nam, ctx) {
+          diag = diag.help(format!("did you mean `{}`?", x));
+        }
+      }
+    }
+    diag
+  }
+}
+
+/// The typing context as a list of hypotheses, outermost first
+fn hypotheses(entries: &Vector<Entry>) -> String {
+  let cfg = Config::default();
+  let hyps: Vec<String> = entries
+    .iter()
+    .rev()
+    .map(|entry| match entry {
+      Entry::Term(nam, uses, typ) => format!(
+        "{}{}: {}",
+        cfg.binder_uses(*uses),
+        print_name(nam),
+        typ_::print(&cfg, typ)
+      ),
+      Entry::Type(nam, kind) => {
+        format!("{}: {}", print_name(nam), kind::print(&cfg, kind))
+      }
+    })
+    .collect();
+  hyps.join(", ")
+}
+
+/// How many relevant occurrences a multiplicity allows
+fn allowed(uses: Uses) -> &'static str {
+  match uses {
+    Uses::None => "no relevant occurrence",
+    Uses::Once => "exactly one relevant occurrence",
+    Uses::Affi => "at most one relevant occurrence",
+    Uses::Many => "any number of relevant occurrences",
+  }
+}
+
+/// Notes showing what each side of a failed conversion reduces to
+fn conversion_notes(
+  diag: Diagnostic,
+  a: &Pure,
+  b: &Pure,
+  (na, nb): &(Pure, Pure),
+) -> Diagnostic {
+  let cfg = Config::default();
+  let normal = |x: &Pure, nx: &Pure| {
+    let (x, nx) = (pure::print(&cfg, x), pure::print(&cfg, nx));
+    if x == nx {
+      format!("`{}` is in normal form", x)
+    }
+    else {
+      format!("`{}` has the normal form `{}`", x, nx)
+    }
+  };
+  diag.note(normal(a, na)).note(normal(b, nb))
+}
+
+/// The label says where the error is, the notes explain it in terms of the
+/// typing context and the normal forms involved
+impl From<&TypeError> for Diagnostic {
+  fn from(e: &TypeError) -> Self {
+    let cfg = Config::default();
+    let context = |diag: Diagnostic, entries: &Vector<Entry>| {
+      if entries.is_empty() {
+        diag
+      }
+      else {
+        diag.note(format!("in the context {}", hypotheses(entries)))
+      }
+    };
+    match e {
+      TypeError::TypeMismatch(pos, m, cause) => {
+        let diag = Diagnostic::error("Type mismatch")
+          .primary_at(*pos, "this term has the wrong type")
+          .note(format!("expected `{}`", typ_::print(&cfg, &m.expected)))
+          .note(format!("   found `{}`", typ_::print(&cfg, &m.found)));
+        let diag = context(diag, &m.entries);
+        match cause.as_deref() {
+          Some(TypeError::NotConvertible(_, a, b, nfs)) => {
+            let diag = diag.note(format!(
+              "the equalities differ in `{}` and `{}`, which are not \
+               convertible",
+              pure::print(&cfg, a),
+              pure::print(&cfg, b)
+            ));
+            conversion_notes(diag, a, b, nfs)
+          }
+          Some(cause) => diag.note(cause),
+          None => diag,
+        }
+      }
+      TypeError::KindMismatch(pos, m) => {
+        let diag = Diagnostic::error("Kind mismatch")
+          .primary_at(*pos, "this type has the wrong kind")
+          .note(format!("expected `{}`", kind::print(&cfg, &m.expected)))
+          .note(format!("   found `{}`", kind::print(&cfg, &m.found)));
+        context(diag, &m.entries)
+      }
+      TypeError::NotConvertible(pos, a, b, nfs) => {
+        let diag = Diagnostic::error(e).primary_at(*pos, "not convertible");
+        conversion_notes(diag, a, b, nfs)
+      }
+      TypeError::UsageViolation(pos, nam, uses, count) => {
+        let diag = Diagnostic::error(e)
+          .primary_at(*pos, format!("`{}` is bound here", print_name(nam)))
+          .note(format!(
+            "the multiplicity {} allows {}, and erased arguments do not count",
+            cfg.uses(*uses),
+            allowed(*uses)
+          ));
+        match (uses, count) {
+          (Uses::Once, 0) => diag.help(format!(
+            "bind it as `{}{}` if it is not needed",
+            cfg.uses(Uses::None),
+            print_name(nam)
+          )),
+          _ => diag.help(format!(
+            "bind it without a multiplicity to use `{}` more than once",
+            print_name(nam)
+          )),
+        }
+      }
+      TypeError::ErasedUse(pos, nam, bound) => Diagnostic::error(e)
+        .primary_at(*pos, "used in a relevant position")
+        .secondary_at(*bound, format!("`{}` is bound as erased", nam))
+        .note(
+          "an erased variable may only occur in erased arguments, types and \
+           equalities",
+        )
+        .help(format!(
+          "pass it as an erased argument `{}{}`, or bind it without `{}`",
+          cfg.uses(Uses::None),
+          nam,
+          cfg.uses(Uses::None)
+        )),
+      TypeError::UsesMismatch(pos, exp, _) => {
+        let diag = Diagnostic::error(e).primary_at(*pos, "");
+        match exp {
+          Uses::Many => diag.help("pass the argument without a multiplicity"),
+          _ => {
+            diag.help(format!("mark the argument with `{}`", cfg.uses(*exp)))
+          }
+        }
+      }
+      _ => Diagnostic::error(e).primary_at(e.pos(), ""),
+    }
+  }
+}
+
+/// The labels of a parse or type error point into the source of the package
+/// that `PackageError::pos` names
+impl From<&PackageError> for Diagnostic {
+  fn from(e: &PackageError) -> Self {
+    let (nam, diag) = match e {
+      PackageError::Parse(nam, e) => (nam, e.1.clone()),
+      PackageError::Type(nam, e) => (nam, Diagnostic::from(&**e)),
+      _ => return Diagnostic::error(e),
+    };
+    let title = format!("In package {}: {}", nam, diag.title);
+    Diagnostic { title, ..diag }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    check::check_module,
+    module::Defs,
+    parse::{
+      file::parse_file,
+      term::parse_term_telescope,
+      util::{
+        input_cid,
+        Ctx,
+      },
+    },
+  };
+  use sp_std::rc::Rc;
+
+  #[test]
+  fn test_edit_distance() {
+    assert_eq!(edit_distance("", ""), 0);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("x", ""), 1);
+    assert_eq!(edit_distance("λx", "λy"), 1);
+    let names: Vec<Name> = vec!["true".into(), "false".into(), "t".into()];
+    assert_eq!(did_you_mean("ture", &names), None);
+    assert_eq!(did_you_mean("tru", &names), Some(&names[0]));
+    assert_eq!(did_you_mean("fasle", &names), None);
+    assert_eq!(did_you_mean("falze", &names), Some(&names[1]));
+    assert_eq!(did_you_mean("s", &names), Some(&names[2]));
+  }
+
+  #[test]
+  fn test_use_color() {
+    assert!(use_color(true, false, Some("xterm")));
+    // output piped to a file or another program stays plain
+    assert!(!use_color(false, false, Some("xterm")));
+    assert!(!use_color(true, true, Some("xterm")));
+    assert!(!use_color(true, false, Some("dumb")));
+    assert!(!use_color(true, false, None));
+  }
+
+  #[test]
+  fn test_render() {
+    let src = "def x : T = y;\ndef z : T =\n  λ (a: A) =>\n  b;";
+    let diag = Diagnostic::error("Undefined reference y")
+      .primary(12, 13, "not in scope")
+      .secondary(4, 5, "in this definition")
+      .help("did you mean `x`?");
+    assert_eq!(
+      diag.render(src, false),
+      "error: Undefined reference y
+ --> 1:13
+  |
+1 | def x : T = y;
+  |     - in this definition
+  |             ^ not in scope
+  |
+  = help: did you mean `x`?"
+    );
+    assert_eq!(
+      diag.to_string(),
+      "Undefined reference y\nhelp: did you mean `x`?"
+    );
+    // a span over several lines is underlined on its first and last line
+    let diag = Diagnostic::error("Mismatch").primary(25, 45, "this").note("n");
+    assert_eq!(
+      diag.render(src, false),
+      "error: Mismatch
+ --> 2:11
+  |
+2 | def z : T =
+  |           ^
+...
+4 |   b;
+  |   ^ this
+  |
+  = note: n"
+    );
+    let colored = diag.render(src, true);
+    assert!(colored.contains("\x1b[1;31m^ this\x1b[0m"));
+    // without labels, there is nothing to show of the source
+    assert_eq!(Diagnostic::error("Oops").render(src, false), "error: Oops");
+  }
+
+  #[test]
+  fn test_parse_error_diagnostic() {
+    let i = "λ (A: Type) (x: A) (xs: A) => y";
+    let e = match parse_term_telescope(
+      input_cid(i),
+      Rc::default(),
+      Ctx::new(),
+      Ctx::new(),
+    )(Span::new(i))
+    {
+      Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => e,
+      _ => panic!("expected a parse error"),
+    };
+    let diag = Diagnostic::from(&e);
+    assert_eq!(diag.title, "Undefined reference y");
+    assert_eq!(diag.helps, vec!["did you mean `x`?".to_string()]);
+    assert_eq!(diag.labels[0].message, "not in scope");
+    assert!(diag.render(i, false).contains("\n1 | λ (A: Type)"));
+  }
+
+  #[test]
+  fn test_type_error_diagnostic() {
+    let bool = "type Bool : Type = ∀ (A: Type) (t: A) (f: A) -> A;
+       def true : Bool = λ (A: Type) (t: A) (f: A) => t;
+       def not : ∀ (b: Bool) -> Bool = λ (b: Bool) => b Bool true true;\n";
+    let check = |i: &str| {
+      let i = format!("{}{}", bool, i);
+      let module =
+        parse_file(input_cid(&i), Rc::default())(Span::new(&i)).unwrap().1;
+      let e = check_module(&Defs::new(), &module).unwrap_err();
+      (Diagnostic::from(&e), i)
+    };
+    let (diag, _) = check("def e : {not true ≃ true} = refl not not;");
+    assert_eq!(diag.title, "Type mismatch");
+    assert_eq!(
+      diag.notes,
+      vec![
+        "expected `{not true ≃ true}`",
+        "   found `{not ≃ not}`",
+        "the equalities differ in `not true` and `not`, which are not \
+         convertible",
+        "`not true` has the normal form `λ t f => t`",
+        "`not` has the normal form `λ b => b (λ t f => t) (λ t f => t)`",
+      ]
+    );
+    let (diag, _) =
+      check("def f : ∀ (x: Bool) -> Bool = λ (b: Bool) (y: Bool) => b;");
+    assert_eq!(diag.notes[0], "expected `∀ (x: Bool) -> Bool`");
+    assert_eq!(diag.notes[1], "   found `∀ (b: Bool) (y: Bool) -> Bool`");
+    // the erased variable is labelled where it occurs and where it is bound
+    let (diag, i) = check("def k : ∀ (⁰x: Bool) -> Bool = λ (⁰x: Bool) => x;");
+    assert_eq!(
+      diag.render(&i, false),
+      "error: The erased variable x occurs in the erased term
+ --> 4:48
+  |
+4 | def k : ∀ (⁰x: Bool) -> Bool = λ (⁰x: Bool) => x;
+  |                                  --------------- `x` is bound as erased
+  |                                                ^ used in a relevant \
+       position
+  |
+  = note: an erased variable may only occur in erased arguments, types and \
+       equalities
+  = help: pass it as an erased argument `⁰x`, or bind it without `⁰`"
+    );
+  }
+}
@@ -0,0 +1,42 @@
+use crate::{
+  anon::Anon,
+  meta::Meta,
+};
+
+use sp_std::{
+  boxed::Box,
+  fmt,
+};
+
+/// An `Anon` tree and a `Meta` tree that do not recombine into a term. The
+/// trees are boxed to keep the error small on the recursive path.
+#[derive(PartialEq, Clone, Debug)]
+pub enum EmbedError {
+  Pure(Box<Anon>, Box<Meta>),
+  Type(Box<Anon>, Box<Meta>),
+  Kind(Box<Anon>, Box<Meta>),
+  Term(Box<Anon>, Box<Meta>),
+  Def(Box<Anon>, Box<Meta>),
+}
+
+impl fmt::Display for EmbedError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Pure(anon, meta) => {
+        write!(f, "Malformed pure term: {:?} with metadata {:?}", anon, meta)
+      }
+      Self::Type(anon, meta) => {
+        write!(f, "Malformed type: {:?} with metadata {:?}", anon, meta)
+      }
+      Self::Kind(anon, meta) => {
+        write!(f, "Malformed kind: {:?} with metadata {:?}", anon, meta)
+      }
+      Self::Term(anon, meta) => {
+        write!(f, "Malformed term: {:?} with metadata {:?}", anon, meta)
+      }
+      Self::Def(anon, meta) => {
+        write!(f, "Malformed definition: {:?} with metadata {:?}", anon, meta)
+      }
+    }
+  }
+}
@@ -0,0 +1,181 @@
+use crate::{
+  pure::Pure,
+  term::Term,
+  uses::Uses,
+};
+
+use sp_std::{
+  boxed::Box,
+  vec::Vec,
+};
+
+/// Maps a term variable `i` under the binders in `rel` (innermost last,
+/// `true` if relevant) to its index once the erased binders are dropped
+fn erase_index(rel: &[bool], i: u64) -> u64 {
+  let len = rel.len() as u64;
+  let inner =
+    |k: u64| rel.iter().rev().take(k as usize).filter(|r| **r).count() as u64;
+  if i < len {
+    inner(i)
+  }
+  else {
+    i - len + inner(len)
+  }
+}
+
+/// Re-indexes a pure term embedded in a `Term`, whose variables still count
+/// the erased binders of `rel`
+fn erase_pure(p: &Pure, rel: &[bool], depth: u64) -> Pure {
+  match p {
+    Pure::Var(pos, n, i) => {
+      if *i < depth {
+        p.clone()
+      }
+      else {
+        Pure::Var(*pos, n.clone(), erase_index(rel, i - depth) + depth)
+      }
+    }
+    Pure::Lam(pos, n, b) => {
+      Pure::Lam(*pos, n.clone(), Box::new(erase_pure(b, rel, depth + 1)))
+    }
+    Pure::App(pos, f, a) => Pure::App(
+      *pos,
+      Box::new(erase_pure(f, rel, depth)),
+      Box::new(erase_pure(a, rel, depth)),
+    ),
+    Pure::Ref(..) => p.clone(),
+  }
+}
+
+fn erase_go(t: &Term, rel: &mut Vec<bool>) -> Pure {
+  match t {
+    Term::Var(pos, n, i) => Pure::Var(*pos, n.clone(), erase_index(rel, *i)),
+    Term::Ref(pos, n, cid) => Pure::Ref(*pos, n.clone(), *cid),
+    Term::Lam(pos, u, n, _, b) => {
+      rel.push(*u != Uses::None);
+      let b = erase_go(b, rel);
+      rel.pop();
+      if *u == Uses::None {
+        b
+      }
+      else {
+        Pure::Lam(*pos, n.clone(), Box::new(b))
+      }
+    }
+    Term::App(pos, u, f, a) => {
+      if *u == Uses::None {
+        erase_go(f, rel)
+      }
+      else {
+        Pure::App(*pos, Box::new(erase_go(f, rel)), Box::new(erase_go(a, rel)))
+      }
+    }
+    Term::Let(pos, u, n, x, b) => {
+      rel.push(*u != Uses::None);
+      let b = erase_go(b, rel);
+      rel.pop();
+      if *u == Uses::None {
+        b
+      }
+      else {
+        let f = Pure::Lam(*pos, n.clone(), Box::new(b));
+        Pure::App(*pos, Box::new(f), Box::new(erase_go(x, rel)))
+      }
+    }
+    Term::LamTy(_, _, _, x)
+    | Term::AppTy(_, x, _)
+    | Term::Intersect(_, x, _, _)
+    | Term::LetTy(_, _, _, _, x)
+    | Term::ProjectL(_, x)
+    | Term::ProjectR(_, x)
+    | Term::Sym(_, x)
+    | Term::Absurd(_, _, x)
+    | Term::Rewrite(_, _, _, x) => erase_go(x, rel),
+    Term::Refl(_, _, y) => erase_pure(y, rel, 0),
+    Term::Cast(_, _, _, z) => erase_pure(z, rel, 0),
+  }
+}
+
+/// Erases a term to its untyped computational content `|t|`. Erased
+/// abstractions and applications, type abstractions and applications, and
+/// the proof arguments of the equality eliminators are dropped, and the
+/// remaining variables are re-indexed so that they no longer count erased
+/// binders. Free variables keep their index relative to the enclosing context.
+///
+/// - `intersect x y T` erases to `|x|`
+/// - `projectL x`, `projectR x`, `sym x` and `absurd T x` erase to `|x|`
+/// - `rewrite x T y` erases to `|y|`
+/// - `refl x y` erases to `y` and `cast x y z` erases to `z`
+/// - `let x = y in b` erases to `(λ x => |b|) |y|`
+pub fn erase(term: &Term) -> Pure { erase_go(term, &mut Vec::new()) }
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    name::Name,
+    parse::{
+      pure::parse_pure_telescope,
+      span::Span,
+      term::parse_term_telescope,
+      util::{
+        input_cid,
+        Ctx,
+      },
+    },
+  };
+  use sp_std::rc::Rc;
+
+  fn term(i: &str, ctx: Ctx) -> Term {
+    parse_term_telescope(input_cid(i), Rc::default(), ctx, Ctx::new())(
+      Span::new(i),
+    )
+    .unwrap()
+    .1
+  }
+
+  fn pure(i: &str, ctx: Ctx) -> Pure {
+    parse_pure_telescope(input_cid(i), Rc::default(), ctx)(Span::new(i))
+      .unwrap()
+      .1
+  }
+
+  #[test]
+  fn test_erase() {
+    fn test(t: &str, p: &str) -> bool {
+      erase(&term(t, Ctx::new())) == pure(p, Ctx::new())
+    }
+    assert!(test("λ (A: Type) (x: A) => x", "λ x => x"));
+    assert!(test("λ (A: Type) (⁰x: A) (y: A) => y", "λ y => y"));
+    assert!(test(
+      "λ (A: Type) (⁰x: A) (f: ∀ (⁰x: A) (y: A) -> A) (y: A) => f ⁰x y",
+      "λ f y => f y"
+    ));
+    assert!(test("λ (A: Type) (x: A) (⁰y: A) => refl x x", "λ x => x"));
+    assert!(test("λ (A: Type) (x: A) (⁰y: A) => refl y x", "λ x => x"));
+    assert!(test(
+      "λ (A: Type) (x: A) => intersect x (refl x x) (ι (y: A) -> {y ≃ x})",
+      "λ x => x"
+    ));
+    assert!(test(
+      "λ (A: Type) (x: A) (⁰e: {x ≃ x}) => rewrite e (λ (z: A) => {z ≃ x}) \
+       (sym (refl x x))",
+      "λ x => x"
+    ));
+    assert!(test("λ (A: Type) (x: A) (⁰e: {x ≃ x}) => cast e x x", "λ x => x"));
+    assert!(test(
+      "λ (A: Type) (x: A) => let ⁰y = x in let z = x in z",
+      "λ x => (λ z => z) x"
+    ));
+    // free variables keep their index in the enclosing context
+    let ctx = Ctx::from(vec![Name::from("a"), Name::from("b")]);
+    assert_eq!(
+      erase(&term("λ (⁰x: ∀ (B: Type) -> B) => b", ctx.clone())),
+      pure("b", ctx.clone())
+    );
+    assert_eq!(
+      erase(&term("λ (B: Type) (⁰x: B) (y: B) => refl a b", ctx.clone())),
+      pure("λ y => b", ctx)
+    );
+  }
+}
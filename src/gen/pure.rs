@@ -8,11 +8,14 @@ use crate::{
   position::Pos,
 };
 
+use sp_cid::Cid;
+
 #[derive(Debug)]
 pub enum Pure {
   Var(Pos, Name, u64),
   Lam(Pos, Name, NonNull<MaybeUninit<Pure>>),
   App(Pos, NonNull<MaybeUninit<Pure>>, NonNull<MaybeUninit<Pure>>),
+  Ref(Pos, Name, Cid),
 }
 
 #[cfg(test)]
@@ -0,0 +1,20 @@
+use sp_cid::Cid;
+use sp_ipld::{
+  dag_cbor::DagCborCodec,
+  Codec,
+  Ipld,
+};
+use sp_multihash::{
+  Code,
+  MultihashDigest,
+};
+
+/// Content address of an IPLD value, hashed the same way as
+/// `parse::util::input_cid`: the Blake2b-256 digest of its DAG-CBOR encoding
+pub fn ipld_cid(ipld: &Ipld) -> Cid {
+  Cid::new_v1(
+    0x55,
+    Code::Blake2b256
+      .digest(DagCborCodec.encode(ipld).unwrap().into_inner().as_ref()),
+  )
+}
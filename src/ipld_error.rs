@@ -42,6 +42,11 @@ pub enum IpldError {
   Import(Ipld),
   ImportEntry(Ipld),
   Package(Ipld),
+  Name(Ipld),
+  Pure(Ipld),
+  Type(Ipld),
+  Kind(Ipld),
+  Term(Ipld),
 }
 
 impl From<IpldError> for String {
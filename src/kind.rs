@@ -1,10 +1,27 @@
 use crate::{
+  anon::{
+    ctor,
+    Anon,
+  },
+  embed_error::EmbedError,
+  hash::ipld_cid,
+  ipld_error::IpldError,
+  meta::Meta,
   position::Pos,
+  print::{
+    kind,
+    Config,
+  },
+  pure::Pure,
   term::Tm,
   typ_::BaseType,
 };
 
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
 use sp_std::{
+  borrow::ToOwned,
   boxed::Box,
   fmt,
 };
@@ -25,6 +42,178 @@ pub type PureKind = BaseKind<true>;
 /// The Kind of Types of annotated Terms
 pub type Kind = BaseKind<false>;
 
+impl<const T: Tm> BaseKind<T> {
+  pub fn pos(&self) -> Pos {
+    match self {
+      Self::Type(pos) => *pos,
+      Self::Pi(pos, ..) => *pos,
+      Self::PiTy(pos, ..) => *pos,
+    }
+  }
+
+  pub fn to_ipld(&self) -> Ipld {
+    match self {
+      Self::Type(pos) => Ipld::List(vec![Ipld::Integer(0), pos.to_ipld()]),
+      Self::Pi(pos, a, b) => Ipld::List(vec![
+        Ipld::Integer(1),
+        pos.to_ipld(),
+        a.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::PiTy(pos, a, b) => Ipld::List(vec![
+        Ipld::Integer(2),
+        pos.to_ipld(),
+        a.to_ipld(),
+        b.to_ipld(),
+      ]),
+    }
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [Ipld::Integer(tag), pos, args @ ..] => {
+          let pos = Pos::from_ipld(pos)?;
+          match (*tag, args) {
+            (0, []) => Ok(Self::Type(pos)),
+            (1, [a, b]) => Ok(Self::Pi(
+              pos,
+              Box::new(BaseType::from_ipld(a)?),
+              Box::new(Self::from_ipld(b)?),
+            )),
+            (2, [a, b]) => Ok(Self::PiTy(
+              pos,
+              Box::new(Self::from_ipld(a)?),
+              Box::new(Self::from_ipld(b)?),
+            )),
+            _ => Err(IpldError::Kind(ipld.to_owned())),
+          }
+        }
+        xs => Err(IpldError::Kind(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::Kind(xs.to_owned())),
+    }
+  }
+
+  /// Content address of the DAG-CBOR encoding of this kind
+  pub fn cid(&self) -> Cid { ipld_cid(&self.to_ipld()) }
+
+  /// Splits into an anonymous tree and its metadata
+  pub fn embed(&self) -> (Anon, Meta) {
+    match self {
+      Self::Type(pos) => ctor("type", *pos, vec![]),
+      Self::Pi(pos, a, b) => ctor("pi", *pos, vec![a.embed(), b.embed()]),
+      Self::PiTy(pos, a, b) => ctor("pi_ty", *pos, vec![a.embed(), b.embed()]),
+    }
+  }
+
+  /// Recombines an anonymous tree with its metadata
+  pub fn unembed(anon: &Anon, meta: &Meta) -> Result<Self, EmbedError> {
+    let err =
+      || EmbedError::Kind(Box::new(anon.clone()), Box::new(meta.clone()));
+    let typ = |a: &Anon, m: &Meta| BaseType::unembed(a, m).map(Box::new);
+    let kind = |a: &Anon, m: &Meta| Self::unembed(a, m).map(Box::new);
+    match (anon, meta) {
+      (Anon::Ctor(tag, xs), Meta::Ctor(pos, ms)) => {
+        match (tag.as_str(), xs.as_slice(), ms.as_slice()) {
+          ("type", [], []) => Ok(Self::Type(*pos)),
+          ("pi", [aa, ba], [am, bm]) => {
+            Ok(Self::Pi(*pos, typ(aa, am)?, kind(ba, bm)?))
+          }
+          ("pi_ty", [aa, ba], [am, bm]) => {
+            Ok(Self::PiTy(*pos, kind(aa, am)?, kind(ba, bm)?))
+          }
+          _ => Err(err()),
+        }
+      }
+      _ => Err(err()),
+    }
+  }
+
+  /// Shifts the free term variables at or above `ct` by `dt` and the free
+  /// type variables at or above `cy` by `dy`
+  pub fn shift(&self, dt: i64, ct: u64, dy: i64, cy: u64) -> Self {
+    match self {
+      Self::Type(pos) => Self::Type(*pos),
+      Self::Pi(pos, a, b) => Self::Pi(
+        *pos,
+        Box::new(a.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct, dy, cy)),
+      ),
+      Self::PiTy(pos, a, b) => Self::PiTy(
+        *pos,
+        Box::new(a.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct, dy, cy)),
+      ),
+    }
+  }
+
+  /// Substitutes the type `v` for the free type variable `j`, removing its
+  /// binder
+  pub fn subst_ty(&self, j: u64, v: &BaseType<T>) -> Self {
+    match self {
+      Self::Type(pos) => Self::Type(*pos),
+      Self::Pi(pos, a, b) => {
+        Self::Pi(*pos, Box::new(a.subst_ty(j, v)), Box::new(b.subst_ty(j, v)))
+      }
+      Self::PiTy(pos, a, b) => {
+        Self::PiTy(*pos, Box::new(a.subst_ty(j, v)), Box::new(b.subst_ty(j, v)))
+      }
+    }
+  }
+
+  /// Substitutes the pure term `v` for the free term variable `j`, removing
+  /// its binder
+  pub fn subst_tm(&self, j: u64, v: &Pure) -> Self {
+    match self {
+      Self::Type(pos) => Self::Type(*pos),
+      Self::Pi(pos, a, b) => {
+        Self::Pi(*pos, Box::new(a.subst_tm(j, v)), Box::new(b.subst_tm(j, v)))
+      }
+      Self::PiTy(pos, a, b) => {
+        Self::PiTy(*pos, Box::new(a.subst_tm(j, v)), Box::new(b.subst_tm(j, v)))
+      }
+    }
+  }
+
+  /// Reinterprets this kind at another purity
+  pub fn retag<const U: Tm>(&self) -> BaseKind<U> {
+    match self {
+      Self::Type(pos) => BaseKind::Type(*pos),
+      Self::Pi(pos, a, b) => {
+        BaseKind::Pi(*pos, Box::new(a.retag()), Box::new(b.retag()))
+      }
+      Self::PiTy(pos, a, b) => {
+        BaseKind::PiTy(*pos, Box::new(a.retag()), Box::new(b.retag()))
+      }
+    }
+  }
+
+  /// Structural equality up to the names of binders and variables
+  pub fn alpha_eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Type(_), Self::Type(_)) => true,
+      (Self::Pi(_, ta, ba), Self::Pi(_, tb, bb)) => {
+        ta.alpha_eq(tb) && ba.alpha_eq(bb)
+      }
+      (Self::PiTy(_, ka, ba), Self::PiTy(_, kb, bb)) => {
+        ka.alpha_eq(kb) && ba.alpha_eq(bb)
+      }
+      _ => false,
+    }
+  }
+
+  /// Structural equality including names, ignoring only positions
+  pub fn syntactic_eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Type(_), Self::Type(_)) => true,
+      (Self::Pi(_, ta, ba), Self::Pi(_, tb, bb)) => ta == tb && ba == bb,
+      (Self::PiTy(_, ka, ba), Self::PiTy(_, kb, bb)) => ka == kb && ba == bb,
+      _ => false,
+    }
+  }
+}
+
 impl<const T: Tm> fmt::Debug for BaseKind<T> {
   fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
@@ -37,13 +226,85 @@ impl<const T: Tm> fmt::Debug for BaseKind<T> {
   }
 }
 
+impl<const T: Tm> fmt::Display for BaseKind<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", kind::print(&Config::from_formatter(f), self))
+  }
+}
+
 impl<const T: Tm> PartialEq for BaseKind<T> {
-  fn eq(&self, other: &Self) -> bool {
-    match (self, other) {
-      (Self::Type(_), Self::Type(_)) => true,
-      (Self::Pi(_, ta, ba), Self::Pi(_, tb, bb)) => ta == tb && ba == bb,
-      (Self::PiTy(_, ka, ba), Self::PiTy(_, kb, bb)) => ka == kb && ba == bb,
+  fn eq(&self, other: &Self) -> bool { self.syntactic_eq(other) }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    gen::tests::gen_range,
+    typ_::{
+      tests::arbitrary_type,
+      Type,
+    },
+  };
+  use quickcheck::{
+    Arbitrary,
+    Gen,
+  };
+
+  /// An arbitrary, not necessarily well-scoped, kind of at most `depth`
+  /// nested constructors
+  pub fn arbitrary_kind<const T: Tm>(g: &mut Gen, depth: usize) -> BaseKind<T> {
+    let case = if depth == 0 { 0 } else { gen_range(g, 0..3) };
+    match case {
+      0 => BaseKind::Type(Pos::None),
+      1 => BaseKind::Pi(
+        Pos::None,
+        Box::new(arbitrary_type(g, depth - 1)),
+        Box::new(arbitrary_kind(g, depth - 1)),
+      ),
+      _ => BaseKind::PiTy(
+        Pos::None,
+        Box::new(arbitrary_kind(g, depth - 1)),
+        Box::new(arbitrary_kind(g, depth - 1)),
+      ),
+    }
+  }
+
+  impl<const T: Tm> Arbitrary for BaseKind<T> {
+    fn arbitrary(g: &mut Gen) -> Self { arbitrary_kind(g, 4) }
+  }
+
+  #[quickcheck]
+  fn kind_embed(x: Kind) -> bool {
+    let (anon, meta) = x.embed();
+    let anon = Anon::from_ipld(&anon.to_ipld());
+    let meta = Meta::from_ipld(&meta.to_ipld());
+    match (anon, meta) {
+      (Ok(anon), Ok(meta)) => match Kind::unembed(&anon, &meta) {
+        Ok(y) => x.syntactic_eq(&y),
+        _ => false,
+      },
+      _ => false,
+    }
+  }
+
+  #[quickcheck]
+  fn kind_ipld(x: Kind) -> bool {
+    match Kind::from_ipld(&x.to_ipld()) {
+      Ok(y) => x.syntactic_eq(&y) && x.cid() == y.cid(),
       _ => false,
     }
   }
+
+  #[quickcheck]
+  fn kind_shift_inverse(x: Kind, dt: u8, dy: u8) -> bool {
+    let (dt, dy) = (dt as i64, dy as i64);
+    x.shift(dt, 0, dy, 0).shift(-dt, 0, -dy, 0).alpha_eq(&x)
+  }
+
+  #[quickcheck]
+  fn kind_subst_fresh(x: Kind, v: Type, p: Pure) -> bool {
+    x.shift(0, 0, 1, 0).subst_ty(0, &v).alpha_eq(&x)
+      && x.shift(1, 0, 0, 0).subst_tm(0, &p).alpha_eq(&x)
+  }
 }
@@ -13,14 +13,28 @@ extern crate rand;
 #[macro_use]
 extern crate alloc;
 
+pub mod anon;
+pub mod check;
+pub mod diagnostic;
+pub mod embed_error;
+pub mod erase;
 pub mod gen;
+pub mod hash;
 pub mod ipld_error;
 pub mod kind;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+pub mod meta;
+pub mod module;
 pub mod name;
+pub mod package;
 pub mod parse;
 pub mod position;
 pub mod print;
 pub mod pure;
+#[cfg(feature = "std")]
+pub mod repl;
+pub mod store;
 pub mod term;
 pub mod typ_;
 pub mod uses;
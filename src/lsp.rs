@@ -0,0 +1,473 @@
+pub mod analysis;
+pub mod rpc;
+
+use crate::{
+  lsp::{
+    analysis::{
+      Analysis,
+      Location,
+    },
+    rpc::{
+      read_message,
+      write_message,
+    },
+  },
+  module::Def,
+  package::fs::Dir,
+  position::Pos,
+};
+
+use serde_json::{
+  json,
+  Value,
+};
+
+use std::{
+  collections::BTreeMap,
+  fs,
+  io::{
+    self,
+    BufRead,
+    Write,
+  },
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+/// An open document with the result of its last analysis
+#[derive(Clone, Debug)]
+pub struct Document {
+  pub text: String,
+  pub analysis: Analysis,
+}
+
+/// A language server for the open documents, each of which is analyzed as a
+/// package of the directory it is in
+#[derive(Clone, Debug, Default)]
+pub struct Server {
+  pub docs: BTreeMap<String, Document>,
+  pub shutdown: bool,
+}
+
+/// Serves the messages of `input` until the client sends `exit`. Returns
+/// whether the client asked for a shutdown first.
+pub fn run(
+  input: &mut impl BufRead,
+  output: &mut impl Write,
+) -> io::Result<bool> {
+  let mut server = Server::default();
+  while let Some(msg) = read_message(input)? {
+    let msg = match msg {
+      Ok(msg) => msg,
+      Err(e) => {
+        write_message(output, &parse_error(&e))?;
+        continue;
+      }
+    };
+    if msg["method"] == "exit" {
+      return Ok(server.shutdown);
+    }
+    for out in server.handle(&msg) {
+      write_message(output, &out)?;
+    }
+  }
+  Ok(false)
+}
+
+/// The response to a message whose body is not valid JSON, which has no id
+fn parse_error(e: &serde_json::Error) -> Value {
+  json!({
+    "jsonrpc": "2.0",
+    "id": Value::Null,
+    "error": { "code": -32700, "message": format!("Parse error: {}", e) },
+  })
+}
+
+impl Server {
+  /// Handles a request or notification, returning the response and
+  /// notifications to send back
+  pub fn handle(&mut self, msg: &Value) -> Vec<Value> {
+    let params = &msg["params"];
+    let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+    let result = match msg["method"].as_str().unwrap_or("") {
+      "initialize" => json!({
+        "capabilities": {
+          "textDocumentSync": 1,
+          "hoverProvider": true,
+          "definitionProvider": true,
+          "documentSymbolProvider": true,
+        },
+        "serverInfo": { "name": "cedille-lsp" },
+      }),
+      "shutdown" => {
+        self.shutdown = true;
+        Value::Null
+      }
+      "textDocument/didOpen" => {
+        let text = params["textDocument"]["text"].as_str().unwrap_or("");
+        return vec![self.open(uri, text.to_owned())];
+      }
+      "textDocument/didChange" => {
+        let changes = params["contentChanges"].as_array();
+        match changes.and_then(|cs| cs.last()?["text"].as_str()) {
+          Some(text) => return vec![self.open(uri, text.to_owned())],
+          None => return Vec::new(),
+        }
+      }
+      "textDocument/didClose" => {
+        self.docs.remove(uri);
+        return vec![publish(uri, Vec::new())];
+      }
+      "textDocument/hover" => self.hover(uri, &params["position"]),
+      "textDocument/definition" => self.definition(uri, &params["position"]),
+      "textDocument/documentSymbol" => self.symbols(uri),
+      method => {
+        if msg.get("id").is_none() {
+          return Vec::new();
+        }
+        return vec![json!({
+          "jsonrpc": "2.0",
+          "id": msg["id"],
+          "error": {
+            "code": -32601,
+            "message": format!("Unknown method {}", method),
+          },
+        })];
+      }
+    };
+    if msg.get("id").is_none() {
+      return Vec::new();
+    }
+    vec![json!({ "jsonrpc": "2.0", "id": msg["id"], "result": result })]
+  }
+
+  /// Analyzes a new version of a document and publishes its diagnostics
+  fn open(&mut self, uri: &str, text: String) -> Value {
+    let path = uri_path(uri);
+    let (dir, name) = path
+      .as_deref()
+      .and_then(Dir::open)
+      .unwrap_or_else(|| (Dir::new("."), "Document".to_owned()));
+    let analysis = Analysis::new(&dir, &name, &text);
+    let diagnostics = analysis
+      .diagnostics
+      .iter()
+      .map(|d| {
+        json!({
+          "range": range(&text, d.from, d.upto),
+          "severity": 1,
+          "source": "cedille",
+          "message": d.message,
+        })
+      })
+      .collect();
+    self.docs.insert(uri.to_owned(), Document { text, analysis });
+    publish(uri, diagnostics)
+  }
+
+  fn hover(&self, uri: &str, position: &Value) -> Value {
+    let doc = match self.docs.get(uri) {
+      Some(doc) => doc,
+      None => return Value::Null,
+    };
+    let offset = offset(&doc.text, position);
+    match doc.analysis.hover(offset) {
+      Some((value, pos)) => {
+        let mut res = json!({
+          "contents": { "kind": "plaintext", "value": value },
+        });
+        if let Pos::Some(p) = pos {
+          res["range"] =
+            range(&doc.text, p.from_offset as usize, p.upto_offset as usize);
+        }
+        res
+      }
+      None => Value::Null,
+    }
+  }
+
+  fn definition(&self, uri: &str, position: &Value) -> Value {
+    let doc = match self.docs.get(uri) {
+      Some(doc) => doc,
+      None => return Value::Null,
+    };
+    let offset = offset(&doc.text, position);
+    match doc.analysis.definition(offset, word_at(&doc.text, offset)) {
+      Some(Location::Here(from, upto)) => {
+        json!({ "uri": uri, "range": range(&doc.text, from, upto) })
+      }
+      Some(Location::File(path, from, upto)) => match fs::read_to_string(&path)
+      {
+        Ok(text) => {
+          json!({ "uri": path_uri(&path), "range": range(&text, from, upto) })
+        }
+        Err(_) => Value::Null,
+      },
+      None => Value::Null,
+    }
+  }
+
+  fn symbols(&self, uri: &str) -> Value {
+    let doc = match self.docs.get(uri) {
+      Some(doc) => doc,
+      None => return Value::Null,
+    };
+    let symbols = doc.analysis.module.defs.iter().filter_map(|def| {
+      let (from, upto) = match def.pos() {
+        Pos::Some(p) => (p.from_offset as usize, p.upto_offset as usize),
+        Pos::None => return None,
+      };
+      // LSP symbol kinds: function and class
+      let (keyword, kind) = match def {
+        Def::Term(..) => ("def", 12),
+        Def::Type(..) => ("type", 5),
+      };
+      let name = def.name();
+      let start = from + keyword.len();
+      let name_from =
+        doc.text[start..upto].find(&**name).map_or(from, |i| start + i);
+      Some(json!({
+        "name": name.to_string(),
+        "kind": kind,
+        "range": range(&doc.text, from, upto),
+        "selectionRange": range(&doc.text, name_from, name_from + name.len()),
+      }))
+    });
+    Value::Array(symbols.collect())
+  }
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+  json!({
+    "jsonrpc": "2.0",
+    "method": "textDocument/publishDiagnostics",
+    "params": { "uri": uri, "diagnostics": diagnostics },
+  })
+}
+
+/// The LSP position of a byte offset: a zero-based line and a column in
+/// UTF-16 code units
+fn position(text: &str, offset: usize) -> Value {
+  let offset = offset.min(text.len());
+  let before = &text[..offset];
+  let line = before.matches('\n').count();
+  let start = before.rfind('\n').map_or(0, |i| i + 1);
+  let character: usize = before[start..].chars().map(char::len_utf16).sum();
+  json!({ "line": line, "character": character })
+}
+
+fn range(text: &str, from: usize, upto: usize) -> Value {
+  json!({ "start": position(text, from), "end": position(text, upto) })
+}
+
+/// The byte offset of an LSP position
+fn offset(text: &str, position: &Value) -> usize {
+  let line = position["line"].as_u64().unwrap_or(0) as usize;
+  let character = position["character"].as_u64().unwrap_or(0) as usize;
+  let start = match line {
+    0 => 0,
+    n => match text.match_indices('\n').nth(n - 1) {
+      Some((i, _)) => i + 1,
+      None => return text.len(),
+    },
+  };
+  let mut units = 0;
+  for (i, c) in text[start..].char_indices() {
+    if units >= character || c == '\n' {
+      return start + i;
+    }
+    units += c.len_utf16();
+  }
+  text.len()
+}
+
+/// The name around a byte offset
+fn word_at(text: &str, offset: usize) -> &str {
+  let is_delim = |c: char| c.is_whitespace() || "():;{},".contains(c);
+  let from = text[..offset].rfind(is_delim).map_or(0, |i| i + 1);
+  let upto = text[offset..].find(is_delim).map_or(text.len(), |i| offset + i);
+  &text[from..upto]
+}
+
+/// The path of a `file://` URI, with its percent-encoded bytes decoded
+fn uri_path(uri: &str) -> Option<PathBuf> {
+  let path = uri.strip_prefix("file://")?.as_bytes();
+  let mut bytes = Vec::new();
+  let mut i = 0;
+  while i < path.len() {
+    let hex = path
+      .get(i + 1..i + 3)
+      .and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok());
+    match (path[i], hex) {
+      (b'%', Some(b)) => {
+        bytes.push(b);
+        i += 3;
+      }
+      (b, _) => {
+        bytes.push(b);
+        i += 1;
+      }
+    }
+  }
+  String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn path_uri(path: &Path) -> String {
+  let mut uri = String::from("file://");
+  for b in path.to_string_lossy().bytes() {
+    match b {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' => {
+        uri.push(b as char)
+      }
+      b => uri.push_str(&format!("%{:02X}", b)),
+    }
+  }
+  uri
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+
+  fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+  }
+
+  fn notify(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+  }
+
+  fn at(uri: &str, line: u64, character: u64) -> Value {
+    json!({
+      "textDocument": { "uri": uri },
+      "position": { "line": line, "character": character },
+    })
+  }
+
+  #[test]
+  fn test_lsp_session() {
+    let root = std::env::temp_dir()
+      .join(format!("cedille-core-lsp-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+      root.join("Bool.cedc"),
+      "type Bool : Type = ∀ (A: Type) (t: A) (f: A) -> A;
+def true : Bool = λ (A: Type) (t: A) (f: A) => t;
+def false : Bool = λ (A: Type) (t: A) (f: A) => f;",
+    )
+    .unwrap();
+    let uri = path_uri(&root.join("Not.cedc"));
+    let text = "import Bool;
+def not : ∀ (b: Bool) -> Bool = λ (b: Bool) => b Bool false true;
+def bad : Bool = λ (A: Type) (t: A) => t;
+def c : Bool = let B: Type = Bool in (λ (b: B) => b) true;";
+    let open = json!({
+      "textDocument": { "uri": uri, "languageId": "cedille", "version": 1,
+                        "text": text },
+    });
+    let script = vec![
+      request(1, "initialize", json!({})),
+      notify("initialized", json!({})),
+      notify("textDocument/didOpen", open),
+      // the `b` applied in the body of `not`
+      request(2, "textDocument/hover", at(&uri, 1, 47)),
+      request(3, "textDocument/definition", at(&uri, 1, 47)),
+      // `Bool`, in the type of `bad`
+      request(4, "textDocument/definition", at(&uri, 2, 11)),
+      request(5, "textDocument/documentSymbol", at(&uri, 0, 0)),
+      request(6, "textDocument/formatting", at(&uri, 0, 0)),
+      // the application in the body of the type `let`
+      request(7, "textDocument/hover", at(&uri, 3, 37)),
+      request(8, "shutdown", Value::Null),
+      notify("exit", Value::Null),
+    ];
+    let mut input = Vec::new();
+    for msg in &script {
+      rpc::write_message(&mut input, msg).unwrap();
+    }
+    let mut output = Vec::new();
+    assert!(run(&mut &input[..], &mut output).unwrap());
+    let mut output = &output[..];
+    let mut replies = Vec::new();
+    while let Some(msg) = read_message(&mut output).unwrap() {
+      replies.push(msg.unwrap());
+    }
+    assert_eq!(replies.len(), 9);
+    assert_eq!(replies[0]["result"]["capabilities"]["hoverProvider"], true);
+    // one type error, in the body of `bad`
+    let diags = &replies[1]["params"]["diagnostics"];
+    assert_eq!(diags.as_array().unwrap().len(), 1);
+    assert_eq!(diags[0]["range"]["start"], json!({"line": 2, "character": 19}));
+    assert!(replies[2]["result"]["contents"]["value"].is_string());
+    assert_eq!(
+      replies[2]["result"]["range"],
+      json!({
+        "start": {"line": 1, "character": 47},
+        "end": {"line": 1, "character": 48},
+      })
+    );
+    // the binder of `b`, up to its type
+    assert_eq!(replies[3]["result"]["uri"], json!(uri));
+    assert_eq!(
+      replies[3]["result"]["range"],
+      json!({
+        "start": {"line": 1, "character": 34},
+        "end": {"line": 1, "character": 42},
+      })
+    );
+    assert_eq!(
+      replies[4]["result"]["uri"],
+      json!(path_uri(&root.join("Bool.cedc")))
+    );
+    let names: Vec<&Value> = replies[5]["result"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|s| &s["name"])
+      .collect();
+    assert_eq!(names, vec!["not", "bad", "c"]);
+    assert_eq!(
+      replies[5]["result"][0]["selectionRange"]["start"],
+      json!({"line": 1, "character": 4})
+    );
+    assert_eq!(replies[6]["error"]["code"], -32601);
+    let hover = replies[7]["result"]["contents"]["value"].as_str().unwrap();
+    assert!(hover.contains("Bool"));
+    assert_eq!(replies[8]["result"], Value::Null);
+    // imports are checked without writing their blocks
+    assert!(!root.join(".cedille").exists());
+    assert!(!root.join(".cedille-checked").exists());
+    fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn test_lsp_parse_error() {
+    let mut input = b"Content-Length: 5\r\n\r\n{oops".to_vec();
+    rpc::write_message(&mut input, &request(1, "shutdown", Value::Null))
+      .unwrap();
+    rpc::write_message(&mut input, &notify("exit", Value::Null)).unwrap();
+    let mut output = Vec::new();
+    assert!(run(&mut &input[..], &mut output).unwrap());
+    let mut output = &output[..];
+    let error = read_message(&mut output).unwrap().unwrap().unwrap();
+    assert_eq!(error["id"], Value::Null);
+    assert_eq!(error["error"]["code"], -32700);
+    // the server keeps serving the messages after the bad one
+    let reply = read_message(&mut output).unwrap().unwrap().unwrap();
+    assert_eq!(reply["id"], 1);
+    assert_eq!(reply["result"], Value::Null);
+  }
+
+  #[test]
+  fn test_lsp_positions() {
+    let text = "ab\nλ (x: A)\n";
+    assert_eq!(position(text, 5), json!({"line": 1, "character": 1}));
+    assert_eq!(offset(text, &json!({"line": 1, "character": 1})), 5);
+    assert_eq!(offset(text, &json!({"line": 9, "character": 0})), text.len());
+    assert_eq!(word_at(text, 8), "x");
+    let path = Path::new("/tmp/a b/λ.cedc");
+    assert_eq!(uri_path(&path_uri(path)).unwrap(), path);
+  }
+}
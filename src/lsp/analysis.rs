@@ -0,0 +1,267 @@
+use crate::{
+  check::{
+    check_def,
+    ctx::Ctx,
+    infer,
+  },
+  diagnostic,
+  module::{
+    Def,
+    Defs,
+    Module,
+  },
+  name::Name,
+  package::{
+    error::PackageError,
+    fs::Dir,
+    parse_recovering,
+    Import,
+    Target,
+  },
+  parse::util::input_cid,
+  position::{
+    Pos,
+    Position,
+  },
+  term::Term,
+};
+
+use sp_cid::Cid;
+
+use std::{
+  collections::BTreeMap,
+  path::PathBuf,
+  rc::Rc,
+};
+
+/// A problem in a document, between two byte offsets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+  pub from: usize,
+  pub upto: usize,
+  pub message: String,
+}
+
+/// Where a definition or binder is: in the analyzed document, or in the
+/// source file of an imported package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Location {
+  Here(usize, usize),
+  File(PathBuf, usize, usize),
+}
+
+/// A parsed and checked document
+#[derive(Clone, Debug)]
+pub struct Analysis {
+  pub input: Cid,
+  /// the definitions of the document that parse
+  pub module: Module,
+  /// the imported definitions and the document's own
+  pub defs: Rc<Defs>,
+  pub diagnostics: Vec<Diagnostic>,
+  /// the source files of the packages imported by name, by input CID
+  pub sources: BTreeMap<Cid, PathBuf>,
+}
+
+/// The byte offsets of a position, if it points into `input`
+fn offsets(input: Cid, pos: Pos) -> Option<(usize, usize)> {
+  match pos {
+    Pos::Some(p) if p.input == input => {
+      Some((p.from_offset as usize, p.upto_offset as usize))
+    }
+    _ => None,
+  }
+}
+
+/// The span from the start of `outer` to the end of `inner`
+fn span(outer: Pos, inner: Pos) -> Pos {
+  match (outer, inner) {
+    (Pos::Some(o), Pos::Some(i)) => Pos::Some(Position {
+      upto_offset: i.upto_offset,
+      upto_line: i.upto_line,
+      upto_column: i.upto_column,
+      ..o
+    }),
+    _ => outer,
+  }
+}
+
+fn contains(pos: Pos, offset: usize) -> bool {
+  match pos {
+    Pos::Some(p) => {
+      p.from_offset as usize <= offset && offset <= p.upto_offset as usize
+    }
+    Pos::None => false,
+  }
+}
+
+impl Analysis {
+  /// Parses and checks the source `text` of package `name` in `dir`. Every
+  /// definition is parsed and checked, so that one malformed or ill-typed
+  /// definition does not hide the errors of the others.
+  pub fn new(dir: &Dir, name: &str, text: &str) -> Self {
+    let input = input_cid(text);
+    let mut sources = BTreeMap::new();
+    let mut failed = Pos::None;
+    let parsed = parse_recovering(&name.into(), text, &mut |pos, tgt| {
+      failed = pos;
+      let (package, defs) = dir.check(tgt)?;
+      if let Target::Name(nam) = tgt {
+        sources.insert(package.input, dir.path(nam));
+      }
+      Ok((Import { name: package.name.clone(), cid: package.cid() }, defs))
+    });
+    let mut res = Analysis {
+      input,
+      module: Module { input, defs: Vec::new() },
+      defs: Rc::default(),
+      diagnostics: Vec::new(),
+      sources,
+    };
+    let error = |e: &PackageError| {
+      let pos = match e {
+        PackageError::Parse(_, e) => e.0,
+        _ => failed,
+      };
+      let (from, upto) = offsets(input, pos).unwrap_or((0, 0));
+      Diagnostic { from, upto, message: e.to_string() }
+    };
+    match parsed {
+      Ok(((_, env, module), errs)) => {
+        res.diagnostics.extend(errs.iter().map(error));
+        let mut env = Rc::new(env);
+        for def in &module.defs {
+          if let Err(e) = check_def(&env, def) {
+            let (from, upto) = offsets(input, e.pos())
+              .or_else(|| offsets(input, def.pos()))
+              .unwrap_or((0, 0));
+            let message = diagnostic::Diagnostic::from(&e).to_string();
+            res.diagnostics.push(Diagnostic { from, upto, message });
+          }
+          Rc::make_mut(&mut env).insert(def.clone());
+        }
+        res.module = module;
+        res.defs = env;
+      }
+      Err(e) => res.diagnostics.push(error(&e)),
+    }
+    res
+  }
+
+  /// The definition of the document around `offset`
+  pub fn def_at(&self, offset: usize) -> Option<&Def> {
+    self.module.defs.iter().find(|def| contains(def.pos(), offset))
+  }
+
+  /// The inferred type of the innermost term around `offset`, or the declared
+  /// type or kind of the definition around it
+  pub fn hover(&self, offset: usize) -> Option<(String, Pos)> {
+    match self.def_at(offset)? {
+      Def::Term(pos, nam, typ, trm) => {
+        let ctx = Ctx::with_defs(self.defs.clone());
+        match term_at(ctx, Vec::new(), trm, offset) {
+          Some(found) => {
+            let typ = infer(&found.ctx, &found.term).ok()?;
+            Some((format!("{}", typ), found.term.pos()))
+          }
+          None => Some((format!("{} : {}", nam, typ), *pos)),
+        }
+      }
+      Def::Type(pos, nam, kind, _) => {
+        Some((format!("{} : {}", nam, kind), *pos))
+      }
+    }
+  }
+
+  /// Where the variable or reference around `offset` is bound. `word` is the
+  /// name under the cursor, which is looked up among the global definitions
+  /// when the cursor is not on a term, as in type annotations.
+  pub fn definition(&self, offset: usize, word: &str) -> Option<Location> {
+    let found = match self.def_at(offset)? {
+      Def::Term(_, _, _, trm) => {
+        let ctx = Ctx::with_defs(self.defs.clone());
+        term_at(ctx, Vec::new(), trm, offset)
+      }
+      Def::Type(..) => None,
+    };
+    let cid = match found.map(|f| (f.term, f.binders)) {
+      Some((Term::Var(_, _, idx), binders)) => {
+        let binder = binders.len().checked_sub(idx as usize + 1)?;
+        let (from, upto) = offsets(self.input, binders[binder])?;
+        return Some(Location::Here(from, upto));
+      }
+      Some((Term::Ref(_, _, cid), _)) => cid,
+      _ => *self.defs.names.get(word)?,
+    };
+    match self.defs.get(&cid)?.pos() {
+      Pos::Some(p) if p.input == self.input => {
+        Some(Location::Here(p.from_offset as usize, p.upto_offset as usize))
+      }
+      Pos::Some(p) => Some(Location::File(
+        self.sources.get(&p.input)?.clone(),
+        p.from_offset as usize,
+        p.upto_offset as usize,
+      )),
+      Pos::None => None,
+    }
+  }
+}
+
+/// The innermost subterm around an offset, with its typing context and the
+/// positions of the term binders in scope, innermost last
+struct Found {
+  ctx: Ctx,
+  binders: Vec<Pos>,
+  term: Term,
+}
+
+fn term_at(
+  ctx: Ctx,
+  binders: Vec<Pos>,
+  term: &Term,
+  offset: usize,
+) -> Option<Found> {
+  if !contains(term.pos(), offset) {
+    return None;
+  }
+  // a binder's position spans the binder itself, up to its type or value
+  let bind = |n: &Name, u, typ, upto: Pos| {
+    let mut binders = binders.clone();
+    binders.push(span(term.pos(), upto));
+    (ctx.bind_term(n.clone(), u, typ), binders)
+  };
+  let child = match term {
+    Term::Lam(_, u, n, a, b) => {
+      let (ctx, binders) = bind(n, *u, *a.clone(), a.pos());
+      term_at(ctx, binders, b, offset)
+    }
+    Term::LamTy(_, n, k, b) => {
+      term_at(ctx.bind_type(n.clone(), *k.clone()), binders.clone(), b, offset)
+    }
+    Term::Let(_, u, n, x, b) => {
+      term_at(ctx.clone(), binders.clone(), x, offset).or_else(|| {
+        let (ctx, binders) = bind(n, *u, infer(&ctx, x).ok()?, x.pos());
+        term_at(ctx, binders, b, offset)
+      })
+    }
+    // the type is substituted into the body, as the checker does
+    Term::LetTy(_, _, _, a, b) => {
+      term_at(ctx.clone(), binders.clone(), &b.subst_ty(0, a), offset)
+    }
+    Term::App(_, _, f, a) | Term::Intersect(_, f, a, _) => {
+      term_at(ctx.clone(), binders.clone(), f, offset)
+        .or_else(|| term_at(ctx.clone(), binders.clone(), a, offset))
+    }
+    Term::Rewrite(_, e, _, y) | Term::Cast(_, e, y, _) => {
+      term_at(ctx.clone(), binders.clone(), e, offset)
+        .or_else(|| term_at(ctx.clone(), binders.clone(), y, offset))
+    }
+    Term::AppTy(_, x, _)
+    | Term::ProjectL(_, x)
+    | Term::ProjectR(_, x)
+    | Term::Sym(_, x)
+    | Term::Absurd(_, _, x) => term_at(ctx.clone(), binders.clone(), x, offset),
+    Term::Var(..) | Term::Refl(..) | Term::Ref(..) => None,
+  };
+  child.or_else(|| Some(Found { ctx, binders, term: term.clone() }))
+}
@@ -0,0 +1,68 @@
+use serde_json::Value;
+
+use std::io::{
+  self,
+  BufRead,
+  Write,
+};
+
+/// Reads a JSON-RPC message framed by a `Content-Length` header. Returns
+/// `None` at the end of the input. A body that is not valid JSON is returned as
+/// the inner error, since the messages after it can still be read.
+pub fn read_message(
+  input: &mut impl BufRead,
+) -> io::Result<Option<serde_json::Result<Value>>> {
+  let mut len = None;
+  loop {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+    let line = line.trim_end();
+    if line.is_empty() {
+      break;
+    }
+    if let Some(n) = line.strip_prefix("Content-Length:") {
+      len = n.trim().parse::<usize>().ok();
+    }
+  }
+  let len = len.ok_or_else(|| {
+    io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length")
+  })?;
+  let mut body = vec![0; len];
+  input.read_exact(&mut body)?;
+  Ok(Some(serde_json::from_slice(&body)))
+}
+
+/// Writes a JSON-RPC message with its `Content-Length` header
+pub fn write_message(output: &mut impl Write, msg: &Value) -> io::Result<()> {
+  let body = msg.to_string();
+  write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+  output.flush()
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn test_rpc_framing() {
+    let msgs = vec![json!({"id": 1, "method": "λ"}), json!(null)];
+    let mut buf = Vec::new();
+    for msg in &msgs {
+      write_message(&mut buf, msg).unwrap();
+    }
+    let mut input = &buf[..];
+    let mut read = || read_message(&mut input).unwrap().map(Result::unwrap);
+    assert_eq!(read(), Some(msgs[0].clone()));
+    assert_eq!(read(), Some(msgs[1].clone()));
+    assert_eq!(read(), None);
+    assert!(read_message(&mut &b"Content-Type: x\r\n\r\n{}"[..]).is_err());
+    // a bad body leaves the framing of the next message intact
+    let mut input =
+      &b"Content-Length: 1\r\n\r\n{Content-Length: 2\r\n\r\n{}"[..];
+    assert!(matches!(read_message(&mut input), Ok(Some(Err(_)))));
+    assert_eq!(read_message(&mut input).unwrap().unwrap().unwrap(), json!({}));
+  }
+}
@@ -0,0 +1,78 @@
+use crate::{
+  hash::ipld_cid,
+  ipld_error::IpldError,
+  name::Name,
+  position::Pos,
+};
+
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
+use sp_std::{
+  borrow::ToOwned,
+  boxed::Box,
+  vec::Vec,
+};
+
+/// The metadata of a term: the binder names and source positions removed
+/// from its `Anon` tree, in the same shape
+#[derive(PartialEq, Clone, Debug)]
+pub enum Meta {
+  /// position of a constructor and the metadata of its arguments
+  Ctor(Pos, Vec<Meta>),
+  /// binder name and the metadata of its body
+  Bind(Name, Box<Meta>),
+  /// position and name of a variable
+  Vari(Pos, Name),
+  /// position and name of a reference
+  Link(Pos, Name),
+  /// an argument without metadata
+  Leaf,
+}
+
+impl Meta {
+  pub fn to_ipld(&self) -> Ipld {
+    match self {
+      Self::Ctor(pos, xs) => Ipld::List(vec![
+        Ipld::Integer(0),
+        pos.to_ipld(),
+        Ipld::List(xs.iter().map(Meta::to_ipld).collect()),
+      ]),
+      Self::Bind(nam, x) => {
+        Ipld::List(vec![Ipld::Integer(1), nam.to_ipld(), x.to_ipld()])
+      }
+      Self::Vari(pos, nam) => {
+        Ipld::List(vec![Ipld::Integer(2), pos.to_ipld(), nam.to_ipld()])
+      }
+      Self::Leaf => Ipld::List(vec![Ipld::Integer(3)]),
+      Self::Link(pos, nam) => {
+        Ipld::List(vec![Ipld::Integer(4), pos.to_ipld(), nam.to_ipld()])
+      }
+    }
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [Ipld::Integer(0), pos, Ipld::List(xs)] => {
+          let xs = xs.iter().map(Meta::from_ipld).collect::<Result<_, _>>()?;
+          Ok(Self::Ctor(Pos::from_ipld(pos)?, xs))
+        }
+        [Ipld::Integer(1), nam, x] => {
+          Ok(Self::Bind(Name::from_ipld(nam)?, Box::new(Meta::from_ipld(x)?)))
+        }
+        [Ipld::Integer(2), pos, nam] => {
+          Ok(Self::Vari(Pos::from_ipld(pos)?, Name::from_ipld(nam)?))
+        }
+        [Ipld::Integer(3)] => Ok(Self::Leaf),
+        [Ipld::Integer(4), pos, nam] => {
+          Ok(Self::Link(Pos::from_ipld(pos)?, Name::from_ipld(nam)?))
+        }
+        xs => Err(IpldError::Meta(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::Meta(xs.to_owned())),
+    }
+  }
+
+  pub fn cid(&self) -> Cid { ipld_cid(&self.to_ipld()) }
+}
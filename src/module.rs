@@ -0,0 +1,166 @@
+use crate::{
+  anon::{
+    ctor,
+    Anon,
+  },
+  embed_error::EmbedError,
+  erase::erase,
+  kind::Kind,
+  meta::Meta,
+  name::Name,
+  position::Pos,
+  pure::Pure,
+  term::Term,
+  typ_::Type,
+};
+
+use sp_cid::Cid;
+
+use sp_std::{
+  boxed::Box,
+  collections::btree_map::BTreeMap,
+  vec::Vec,
+};
+
+/// A top-level definition. Later definitions refer to it by name, which the
+/// parser resolves to a reference to its CID.
+#[derive(Clone, Debug)]
+pub enum Def {
+  /// term definition, `def name : T = t;`
+  Term(Pos, Name, Type, Term),
+  /// type definition, `type Name : K = T;`
+  Type(Pos, Name, Kind, Type),
+}
+
+impl Def {
+  pub fn pos(&self) -> Pos {
+    match self {
+      Self::Term(pos, ..) => *pos,
+      Self::Type(pos, ..) => *pos,
+    }
+  }
+
+  pub fn name(&self) -> &Name {
+    match self {
+      Self::Term(_, nam, ..) => nam,
+      Self::Type(_, nam, ..) => nam,
+    }
+  }
+
+  /// Splits into an anonymous tree and its metadata. The name is not part of
+  /// either, so definitions that only differ in their names share a CID.
+  pub fn embed(&self) -> (Anon, Meta) {
+    match self {
+      Self::Term(pos, _, typ, trm) => {
+        ctor("def", *pos, vec![typ.embed(), trm.embed()])
+      }
+      Self::Type(pos, _, kind, typ) => {
+        ctor("type", *pos, vec![kind.embed(), typ.embed()])
+      }
+    }
+  }
+
+  /// Recombines an anonymous tree and its metadata into a definition named
+  /// `nam`
+  pub fn unembed(
+    nam: Name,
+    anon: &Anon,
+    meta: &Meta,
+  ) -> Result<Self, EmbedError> {
+    let err =
+      || EmbedError::Def(Box::new(anon.clone()), Box::new(meta.clone()));
+    match (anon, meta) {
+      (Anon::Ctor(tag, xs), Meta::Ctor(pos, ms)) => {
+        match (tag.as_str(), xs.as_slice(), ms.as_slice()) {
+          ("def", [ta, xa], [tm, xm]) => Ok(Self::Term(
+            *pos,
+            nam,
+            Type::unembed(ta, tm)?,
+            Term::unembed(xa, xm)?,
+          )),
+          ("type", [ka, ta], [km, tm]) => Ok(Self::Type(
+            *pos,
+            nam,
+            Kind::unembed(ka, km)?,
+            Type::unembed(ta, tm)?,
+          )),
+          _ => Err(err()),
+        }
+      }
+      _ => Err(err()),
+    }
+  }
+
+  /// Content address of the anonymous definition, which references use
+  pub fn cid(&self) -> Cid { self.embed().0.cid() }
+}
+
+/// Global definitions by CID, with the CID of each name
+#[derive(Clone, Debug, Default)]
+pub struct Defs {
+  pub defs: BTreeMap<Cid, Def>,
+  pub names: BTreeMap<Name, Cid>,
+}
+
+impl Defs {
+  pub fn new() -> Self { Defs::default() }
+
+  /// Adds a definition, returning its CID
+  pub fn insert(&mut self, def: Def) -> Cid {
+    let cid = def.cid();
+    self.names.insert(def.name().clone(), cid);
+    self.defs.insert(cid, def);
+    cid
+  }
+
+  pub fn get(&self, cid: &Cid) -> Option<&Def> { self.defs.get(cid) }
+
+  pub fn contains(&self, cid: &Cid) -> bool { self.defs.contains_key(cid) }
+
+  /// Adds the definitions and names of `other`, whose names shadow existing
+  /// ones
+  pub fn import(&mut self, other: &Defs) {
+    self.defs.extend(other.defs.iter().map(|(k, v)| (*k, v.clone())));
+    self.names.extend(other.names.iter().map(|(k, v)| (k.clone(), *v)));
+  }
+
+  /// The CID of the term definition named `nam`
+  pub fn term_ref(&self, nam: &str) -> Option<Cid> {
+    let cid = self.names.get(nam)?;
+    match self.defs.get(cid)? {
+      Def::Term(..) => Some(*cid),
+      Def::Type(..) => None,
+    }
+  }
+
+  /// The CID of the type definition named `nam`
+  pub fn type_ref(&self, nam: &str) -> Option<Cid> {
+    let cid = self.names.get(nam)?;
+    match self.defs.get(cid)? {
+      Def::Type(..) => Some(*cid),
+      Def::Term(..) => None,
+    }
+  }
+
+  /// The erased body of a term definition
+  pub fn erased(&self, cid: &Cid) -> Option<Pure> {
+    match self.defs.get(cid)? {
+      Def::Term(_, _, _, trm) => Some(erase(trm)),
+      Def::Type(..) => None,
+    }
+  }
+}
+
+/// The ordered definitions of a source file
+#[derive(Clone, Debug)]
+pub struct Module {
+  pub input: Cid,
+  pub defs: Vec<Def>,
+}
+
+impl Module {
+  /// Looks up the definition named `nam`
+  pub fn get(&self, nam: &str) -> Option<&Def> {
+    self.defs.iter().find(|d| &**d.name() == nam)
+  }
+}
@@ -1,5 +1,12 @@
+use crate::ipld_error::IpldError;
+
+use sp_ipld::Ipld;
+
 use sp_std::{
-  borrow::Borrow,
+  borrow::{
+    Borrow,
+    ToOwned,
+  },
   fmt,
   ops::Deref,
   rc::Rc,
@@ -40,6 +47,17 @@ impl Deref for Name {
   fn deref(&self) -> &str { self.inner.deref() }
 }
 
+impl Name {
+  pub fn to_ipld(&self) -> Ipld { Ipld::String(self.to_string()) }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::String(s) => Ok(Name::from(s.as_str())),
+      xs => Err(IpldError::Name(xs.to_owned())),
+    }
+  }
+}
+
 impl<'a> From<&'a str> for Name {
   fn from(v: &str) -> Name { Self { inner: Rc::from(v) } }
 }
@@ -0,0 +1,478 @@
+pub mod error;
+#[cfg(feature = "std")]
+pub mod fs;
+
+use crate::{
+  anon::Anon,
+  check::check_module_cached,
+  diagnostic::Diagnostic,
+  hash::ipld_cid,
+  ipld_error::IpldError,
+  meta::Meta,
+  module::{
+    Def,
+    Defs,
+    Module,
+  },
+  name::Name,
+  package::error::PackageError,
+  parse::{
+    error::ParseError,
+    file::{
+      parse_file,
+      parse_file_recovering,
+      parse_imports,
+    },
+    span::Span,
+    util::input_cid,
+  },
+  position::Pos,
+  store::Store,
+};
+
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
+use sp_std::{
+  borrow::ToOwned,
+  boxed::Box,
+  collections::btree_map::BTreeMap,
+  rc::Rc,
+  vec::Vec,
+};
+
+/// What an `import` statement names: the source of a package, or a stored
+/// package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+  Name(Name),
+  Cid(Cid),
+}
+
+/// A resolved import, with the name of the imported package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+  pub name: Name,
+  pub cid: Cid,
+}
+
+impl Import {
+  pub fn to_ipld(&self) -> Ipld {
+    Ipld::List(vec![self.name.to_ipld(), Ipld::Link(self.cid)])
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [name, Ipld::Link(cid)] => {
+          Ok(Import { name: Name::from_ipld(name)?, cid: *cid })
+        }
+        xs => Err(IpldError::ImportEntry(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::ImportEntry(xs.to_owned())),
+    }
+  }
+}
+
+/// The exported definitions of a package in source order, each pointing to
+/// its entry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Index(pub Vec<(Name, Cid)>);
+
+impl Index {
+  pub fn to_ipld(&self) -> Ipld {
+    Ipld::List(
+      self
+        .0
+        .iter()
+        .map(|(n, cid)| Ipld::List(vec![n.to_ipld(), Ipld::Link(*cid)]))
+        .collect(),
+    )
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    let entry = |ipld: &Ipld| match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [name, Ipld::Link(cid)] => Ok((Name::from_ipld(name)?, *cid)),
+        xs => Err(IpldError::IndexEntry(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::IndexEntry(xs.to_owned())),
+    };
+    match ipld {
+      Ipld::List(xs) => {
+        Ok(Index(xs.iter().map(entry).collect::<Result<_, _>>()?))
+      }
+      xs => Err(IpldError::Index(xs.to_owned())),
+    }
+  }
+}
+
+/// A stored definition: its name and the CIDs of its anonymous tree, which is
+/// the CID references use, and of its metadata
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+  pub name: Name,
+  pub anon: Cid,
+  pub meta: Cid,
+}
+
+impl Entry {
+  pub fn to_ipld(&self) -> Ipld {
+    Ipld::List(vec![
+      self.name.to_ipld(),
+      Ipld::Link(self.anon),
+      Ipld::Link(self.meta),
+    ])
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [name, Ipld::Link(anon), Ipld::Link(meta)] => {
+          Ok(Entry { name: Name::from_ipld(name)?, anon: *anon, meta: *meta })
+        }
+        xs => Err(IpldError::Entry(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::Entry(xs.to_owned())),
+    }
+  }
+
+  pub fn cid(&self) -> Cid { ipld_cid(&self.to_ipld()) }
+}
+
+/// A checked source file with the packages it imports and the definitions it
+/// exports
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Package {
+  pub name: Name,
+  pub input: Cid,
+  pub imports: Vec<Import>,
+  pub index: Index,
+}
+
+impl Package {
+  pub fn to_ipld(&self) -> Ipld {
+    Ipld::List(vec![
+      self.name.to_ipld(),
+      Ipld::Link(self.input),
+      Ipld::List(self.imports.iter().map(Import::to_ipld).collect()),
+      self.index.to_ipld(),
+    ])
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [name, Ipld::Link(input), imports, index] => {
+          let imports = match imports {
+            Ipld::List(xs) => {
+              xs.iter().map(Import::from_ipld).collect::<Result<_, _>>()?
+            }
+            xs => return Err(IpldError::Import(xs.to_owned())),
+          };
+          Ok(Package {
+            name: Name::from_ipld(name)?,
+            input: *input,
+            imports,
+            index: Index::from_ipld(index)?,
+          })
+        }
+        xs => Err(IpldError::Package(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::Package(xs.to_owned())),
+    }
+  }
+
+  pub fn cid(&self) -> Cid { ipld_cid(&self.to_ipld()) }
+}
+
+/// Resolves an import at a position to the package it names and the
+/// definitions that package exports
+pub type Resolve<'a> =
+  dyn FnMut(Pos, &Target) -> Result<(Import, Defs), PackageError> + 'a;
+
+/// Keeps the definitions of `defs` but only the names in `module`, so that a
+/// package exports its own definitions and not the ones it imports
+fn exports(defs: Defs, module: &Module) -> Defs {
+  let mut names = BTreeMap::new();
+  for def in &module.defs {
+    names.insert(def.name().clone(), def.cid());
+  }
+  Defs { defs: defs.defs, names }
+}
+
+/// The imports of a parsed package with their positions, the definitions
+/// they bring into scope, and the module of the package's own definitions
+pub type Parsed = (Vec<(Pos, Import)>, Defs, Module);
+
+/// Parses the package `name` from its source, resolving its imports with
+/// `resolve`
+pub fn parse(
+  name: &Name,
+  src: &str,
+  resolve: &mut Resolve,
+) -> Result<Parsed, PackageError> {
+  let input = input_cid(src);
+  let parse_err = |e| parse_error(name, input, e);
+  let (i, targets) = parse_imports(input)(Span::new(src)).map_err(parse_err)?;
+  let (imports, env) = resolve_imports(&targets, resolve)?;
+  let (_, module) =
+    parse_file(input, Rc::new(env.clone()))(i).map_err(parse_err)?;
+  Ok((imports, env, module))
+}
+
+/// Parses the package `name` like `parse`, but recovers from errors in its
+/// definitions. Returns the module of the definitions that parse, and an
+/// error for each of the others.
+pub fn parse_recovering(
+  name: &Name,
+  src: &str,
+  resolve: &mut Resolve,
+) -> Result<(Parsed, Vec<PackageError>), PackageError> {
+  let input = input_cid(src);
+  let (i, targets) = parse_imports(input)(Span::new(src))
+    .map_err(|e| parse_error(name, input, e))?;
+  let (imports, env) = resolve_imports(&targets, resolve)?;
+  let (module, errs) = parse_file_recovering(input, Rc::new(env.clone()))(i);
+  let errs = errs
+    .into_iter()
+    .map(|e| parse_error(name, input, nom::Err::Error(e)))
+    .collect();
+  Ok(((imports, env, module), errs))
+}
+
+fn parse_error(
+  name: &Name,
+  input: Cid,
+  e: nom::Err<ParseError<Span>>,
+) -> PackageError {
+  match e {
+    nom::Err::Error(e) | nom::Err::Failure(e) => {
+      let pos = Pos::from_upto(input, e.input, e.input);
+      PackageError::Parse(name.clone(), Box::new((pos, Diagnostic::from(&e))))
+    }
+    nom::Err::Incomplete(_) => PackageError::Parse(
+      name.clone(),
+      Box::new((Pos::None, Diagnostic::error("Incomplete input"))),
+    ),
+  }
+}
+
+/// Resolves the imports `targets` with `resolve`, returning them with the
+/// definitions they bring into scope
+fn resolve_imports(
+  targets: &[(Pos, Target)],
+  resolve: &mut Resolve,
+) -> Result<(Vec<(Pos, Import)>, Defs), PackageError> {
+  let mut imports = Vec::new();
+  let mut env = Defs::new();
+  for (pos, tgt) in targets {
+    let (import, defs) = resolve(*pos, tgt)?;
+    env.import(&defs);
+    imports.push((*pos, import));
+  }
+  Ok((imports, env))
+}
+
+/// Builds the package `name` from its source: resolves its imports with
+/// `resolve`, parses and checks its definitions, and puts its blocks in
+/// `store`. The definitions it checks are recorded in the local `cache`, see
+/// `check_module_cached`.
+pub fn build(
+  name: Name,
+  src: &str,
+  store: &dyn Store,
+  cache: &dyn Store,
+  resolve: &mut Resolve,
+) -> Result<(Package, Defs), PackageError> {
+  let (imports, env, module) = parse(&name, src, resolve)?;
+  let imports = imports.into_iter().map(|(_, import)| import).collect();
+  let defs = check_module_cached(cache, &env, &module)
+    .map_err(|e| PackageError::Type(name.clone(), Box::new(e)))?;
+  let package =
+    package(name, imports, &module, &mut |ipld| Ok(store.put(ipld)?))?;
+  store.put(&package.to_ipld())?;
+  Ok((package, exports(defs, &module)))
+}
+
+/// Parses the package `name` from its source like `build`, but neither checks
+/// its definitions nor stores its blocks. For tools that only need the syntax
+/// of a package and the CIDs of its definitions.
+pub fn build_unchecked(
+  name: Name,
+  src: &str,
+  resolve: &mut Resolve,
+) -> Result<(Package, Defs), PackageError> {
+  let (imports, mut defs, module) = parse(&name, src, resolve)?;
+  let imports = imports.into_iter().map(|(_, import)| import).collect();
+  for def in &module.defs {
+    defs.insert(def.clone());
+  }
+  let package =
+    package(name, imports, &module, &mut |ipld| Ok(ipld_cid(ipld)))?;
+  Ok((package, exports(defs, &module)))
+}
+
+/// The package of `module`, with the entries of its definitions addressed by
+/// `put`
+fn package(
+  name: Name,
+  imports: Vec<Import>,
+  module: &Module,
+  put: &mut dyn FnMut(&Ipld) -> Result<Cid, PackageError>,
+) -> Result<Package, PackageError> {
+  let mut index = Vec::new();
+  for def in &module.defs {
+    let (anon, meta) = def.embed();
+    let entry = Entry {
+      name: def.name().clone(),
+      anon: put(&anon.to_ipld())?,
+      meta: put(&meta.to_ipld())?,
+    };
+    index.push((entry.name.clone(), put(&entry.to_ipld())?));
+  }
+  Ok(Package { name, input: module.input, imports, index: Index(index) })
+}
+
+/// Loads the package stored at `cid`, checking its definitions against the
+/// packages it imports unless the local `cache` records them as checked
+pub fn load(
+  cid: &Cid,
+  store: &dyn Store,
+  cache: &dyn Store,
+) -> Result<(Package, Defs), PackageError> {
+  let package = Package::from_ipld(&store.get(cid)?)?;
+  let mut env = Defs::new();
+  for import in &package.imports {
+    let (_, defs) = load(&import.cid, store, cache)?;
+    env.import(&defs);
+  }
+  let mut module = Module { input: package.input, defs: Vec::new() };
+  for (_, cid) in &package.index.0 {
+    let entry = Entry::from_ipld(&store.get(cid)?)?;
+    let anon = Anon::from_ipld(&store.get(&entry.anon)?)?;
+    let meta = Meta::from_ipld(&store.get(&entry.meta)?)?;
+    module.defs.push(Def::unembed(entry.name, &anon, &meta)?);
+  }
+  let defs = check_module_cached(cache, &env, &module)
+    .map_err(|e| PackageError::Type(package.name.clone(), Box::new(e)))?;
+  Ok((package, exports(defs, &module)))
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    check::checked_block,
+    store::{
+      mem::MemStore,
+      StoreError,
+    },
+  };
+
+  const BOOL: &str = "type Bool : Type = ∀ (A: Type) (t: A) (f: A) -> A;
+    def true : Bool = λ (A: Type) (t: A) (f: A) => t;
+    def false : Bool = λ (A: Type) (t: A) (f: A) => f;";
+
+  fn no_imports(_: Pos, tgt: &Target) -> Result<(Import, Defs), PackageError> {
+    panic!("unexpected import {:?}", tgt)
+  }
+
+  #[test]
+  fn test_package_ipld() {
+    let (store, cache) = (MemStore::new(), MemStore::new());
+    let (package, defs) =
+      build(Name::from("Bool"), BOOL, &store, &cache, &mut no_imports).unwrap();
+    assert_eq!(Package::from_ipld(&package.to_ipld()), Ok(package.clone()));
+    let names: Vec<&str> = package.index.0.iter().map(|(n, _)| &**n).collect();
+    assert_eq!(names, vec!["Bool", "true", "false"]);
+    assert_eq!(defs.defs.len(), 3);
+    // anon, meta and entry per definition, and the package
+    assert_eq!(store.blocks.borrow().len(), 10);
+    // and a checked mark per definition, kept apart
+    assert_eq!(cache.blocks.borrow().len(), 3);
+    assert_eq!(store.get(&package.cid()), Ok(package.to_ipld()));
+  }
+
+  #[test]
+  fn test_package_unchecked() {
+    let store = MemStore::new();
+    let (built, _) =
+      build(Name::from("Bool"), BOOL, &store, &store, &mut no_imports).unwrap();
+    let (package, defs) =
+      build_unchecked(Name::from("Bool"), BOOL, &mut no_imports).unwrap();
+    assert_eq!(package, built);
+    assert_eq!(defs.defs.len(), 3);
+    // ill-typed definitions are not checked, and nothing is stored
+    let src = format!("{}\n{}", BOOL, "def b : Bool = λ (x: Bool) => x;");
+    let (package, defs) =
+      build_unchecked(Name::from("Bool"), &src, &mut no_imports).unwrap();
+    assert!(defs.term_ref("b").is_some());
+    assert!(!store.contains(&package.index.0[3].1));
+  }
+
+  #[test]
+  fn test_package_import() {
+    let (store, cache) = (MemStore::new(), MemStore::new());
+    let (bool, _) =
+      build(Name::from("Bool"), BOOL, &store, &cache, &mut no_imports).unwrap();
+    let (loaded, defs) = load(&bool.cid(), &store, &cache).unwrap();
+    assert_eq!(loaded, bool);
+    assert!(defs.term_ref("true").is_some());
+    let src = format!(
+      "import {};
+       def not : ∀ (b: Bool) -> Bool = λ (b: Bool) => b Bool false true;",
+      bool.cid()
+    );
+    let mut resolve = |_, tgt: &Target| match tgt {
+      Target::Cid(cid) => {
+        let (package, defs) = load(cid, &store, &cache)?;
+        Ok((Import { name: package.name, cid: *cid }, defs))
+      }
+      Target::Name(nam) => Err(PackageError::UnknownPackage(nam.clone())),
+    };
+    let (not, defs) =
+      build(Name::from("Not"), &src, &store, &cache, &mut resolve).unwrap();
+    assert_eq!(
+      not.imports,
+      vec![Import { name: Name::from("Bool"), cid: bool.cid() }]
+    );
+    // imported definitions are available but not exported
+    assert_eq!(defs.defs.len(), 4);
+    assert!(defs.term_ref("not").is_some());
+    assert!(defs.term_ref("true").is_none());
+    assert_eq!(load(&not.cid(), &store, &cache).unwrap().0, not);
+    assert!(matches!(
+      build(Name::from("Not"), "import Bool;", &store, &cache, &mut resolve),
+      Err(PackageError::UnknownPackage(_))
+    ));
+    // blocks are checked against their CID
+    let (_, entry) = not.index.0[0].clone();
+    store.blocks.borrow_mut().insert(entry, Ipld::Null);
+    assert!(matches!(
+      load(&not.cid(), &store, &cache),
+      Err(PackageError::Store(StoreError::Corrupt(cid))) if cid == entry
+    ));
+  }
+
+  #[test]
+  fn test_package_forged_mark() {
+    // a stored package whose definition does not check, with a mark in the
+    // store claiming that it does
+    let store = MemStore::new();
+    let name = Name::from("Bad");
+    let src =
+      "def f : ∀ (A: Type) (x: A) -> A = λ (A: Type) (x: A) (y: A) => x;";
+    let (_, _, module) = parse(&name, src, &mut no_imports).unwrap();
+    let bad =
+      package(name, Vec::new(), &module, &mut |ipld| Ok(store.put(ipld)?))
+        .unwrap();
+    store.put(&bad.to_ipld()).unwrap();
+    store.put(&checked_block(&module.defs[0].cid())).unwrap();
+    // only the local cache is trusted
+    assert!(matches!(
+      load(&bad.cid(), &store, &MemStore::new()),
+      Err(PackageError::Type(..))
+    ));
+  }
+}
@@ -0,0 +1,67 @@
+use crate::{
+  check::error::TypeError,
+  diagnostic::Diagnostic,
+  embed_error::EmbedError,
+  ipld_error::IpldError,
+  name::Name,
+  position::Pos,
+  store::StoreError,
+};
+
+use sp_std::{
+  boxed::Box,
+  fmt,
+};
+
+#[derive(Clone, Debug)]
+pub enum PackageError {
+  Store(StoreError),
+  Ipld(IpldError),
+  Embed(EmbedError),
+  /// package name, position of the failure and parse error
+  Parse(Name, Box<(Pos, Diagnostic)>),
+  Type(Name, Box<TypeError>),
+  /// no source for the package imported by this name
+  UnknownPackage(Name),
+  /// the package imports itself, directly or through other packages
+  ImportCycle(Name),
+}
+
+impl PackageError {
+  /// Where the error occurred, with the package whose source it points into
+  pub fn pos(&self) -> Option<(&Name, Pos)> {
+    match self {
+      Self::Parse(nam, e) => Some((nam, e.0)),
+      Self::Type(nam, e) => Some((nam, e.pos())),
+      _ => None,
+    }
+  }
+}
+
+impl From<StoreError> for PackageError {
+  fn from(e: StoreError) -> Self { Self::Store(e) }
+}
+
+impl From<IpldError> for PackageError {
+  fn from(e: IpldError) -> Self { Self::Ipld(e) }
+}
+
+impl From<EmbedError> for PackageError {
+  fn from(e: EmbedError) -> Self { Self::Embed(e) }
+}
+
+impl fmt::Display for PackageError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Store(e) => write!(f, "{}", e),
+      Self::Ipld(e) => write!(f, "Malformed block: {:?}", e),
+      Self::Embed(e) => write!(f, "{}", e),
+      Self::Parse(nam, e) => write!(f, "In package {}: {}", nam, e.1),
+      Self::Type(nam, e) => write!(f, "In package {}: {}", nam, e),
+      Self::UnknownPackage(nam) => write!(f, "No source for package {}", nam),
+      Self::ImportCycle(nam) => {
+        write!(f, "The package {} imports itself", nam)
+      }
+    }
+  }
+}
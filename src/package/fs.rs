@@ -0,0 +1,219 @@
+use crate::{
+  module::Defs,
+  name::Name,
+  package::{
+    build,
+    build_unchecked,
+    error::PackageError,
+    load,
+    parse,
+    Import,
+    Package,
+    Parsed,
+    Target,
+  },
+  store::{
+    fs::FsStore,
+    ReadOnly,
+    Store,
+  },
+};
+
+use sp_cid::Cid;
+
+use std::{
+  fs,
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+/// A directory of package sources, `<name>.cedc`. The blocks of built
+/// packages go to a store in its `.cedille` subdirectory, and the records of
+/// checked definitions to a cache in `.cedille-checked`, which is never shared.
+#[derive(Clone, Debug)]
+pub struct Dir {
+  pub root: PathBuf,
+  pub store: FsStore,
+  pub cache: FsStore,
+}
+
+impl Dir {
+  pub fn new(root: impl Into<PathBuf>) -> Self {
+    let root = root.into();
+    let store = FsStore::new(root.join(".cedille"));
+    let cache = FsStore::new(root.join(".cedille-checked"));
+    Dir { root, store, cache }
+  }
+
+  /// The directory and package name of a source file `<root>/<name>.cedc`
+  pub fn open(file: impl AsRef<Path>) -> Option<(Self, String)> {
+    let path = file.as_ref();
+    let name = match (path.file_stem(), path.extension()) {
+      (Some(stem), Some(ext)) if ext == "cedc" => stem.to_string_lossy(),
+      _ => return None,
+    };
+    let root = match path.parent() {
+      Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+      _ => PathBuf::from("."),
+    };
+    Some((Dir::new(root), name.into_owned()))
+  }
+
+  /// Loads a stored package by CID
+  pub fn load(&self, cid: &Cid) -> Result<(Package, Defs), PackageError> {
+    load(cid, &self.store, &self.cache)
+  }
+
+  /// The path of the source of package `name`
+  pub fn path(&self, name: &str) -> PathBuf {
+    self.root.join(format!("{}.cedc", name))
+  }
+
+  /// Reads the source of package `name`
+  pub fn source(&self, name: &str) -> Result<String, PackageError> {
+    fs::read_to_string(self.path(name))
+      .map_err(|_| PackageError::UnknownPackage(Name::from(name)))
+  }
+
+  /// Builds the package in `<root>/<name>.cedc` and the packages it imports
+  /// by name
+  pub fn build(&self, name: &str) -> Result<(Package, Defs), PackageError> {
+    let stores = Some((&self.store as &dyn Store, &self.cache as &dyn Store));
+    self.build_go(Name::from(name), &mut Vec::new(), stores)
+  }
+
+  /// Checks the package that an import names like `build` does, but writes
+  /// nothing to the store or the cache
+  pub fn check(&self, tgt: &Target) -> Result<(Package, Defs), PackageError> {
+    let (store, cache) = (ReadOnly(&self.store), ReadOnly(&self.cache));
+    match tgt {
+      Target::Name(nam) => {
+        self.build_go(nam.clone(), &mut Vec::new(), Some((&store, &cache)))
+      }
+      Target::Cid(cid) => load(cid, &store, &cache),
+    }
+  }
+
+  /// Parses the package in `<root>/<name>.cedc` and the packages it imports
+  /// by name, without checking them or storing their blocks
+  pub fn parse(&self, name: &str) -> Result<Parsed, PackageError> {
+    let name = Name::from(name);
+    let src = self.source(&name)?;
+    let mut visiting = vec![name.clone()];
+    parse(&name, &src, &mut |_, tgt| self.resolve(tgt, &mut visiting, None))
+  }
+
+  /// Builds the package `name` into a store, recording the definitions it
+  /// checks in a cache, or without checking or storing anything if there are
+  /// none
+  fn build_go(
+    &self,
+    name: Name,
+    visiting: &mut Vec<Name>,
+    stores: Option<(&dyn Store, &dyn Store)>,
+  ) -> Result<(Package, Defs), PackageError> {
+    if visiting.contains(&name) {
+      return Err(PackageError::ImportCycle(name));
+    }
+    let src = self.source(&name)?;
+    visiting.push(name.clone());
+    let mut resolve = |_, tgt: &Target| self.resolve(tgt, visiting, stores);
+    let res = match stores {
+      Some((store, cache)) => build(name, &src, store, cache, &mut resolve),
+      None => build_unchecked(name, &src, &mut resolve),
+    };
+    visiting.pop();
+    res
+  }
+
+  fn resolve(
+    &self,
+    tgt: &Target,
+    visiting: &mut Vec<Name>,
+    stores: Option<(&dyn Store, &dyn Store)>,
+  ) -> Result<(Import, Defs), PackageError> {
+    match tgt {
+      Target::Name(nam) => {
+        let (package, defs) = self.build_go(nam.clone(), visiting, stores)?;
+        Ok((Import { name: nam.clone(), cid: package.cid() }, defs))
+      }
+      Target::Cid(cid) => {
+        let (package, defs) = match stores {
+          Some((store, cache)) => load(cid, store, cache)?,
+          None => load(cid, &ReadOnly(&self.store), &ReadOnly(&self.cache))?,
+        };
+        Ok((Import { name: package.name, cid: *cid }, defs))
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    check::checked_block,
+    hash::ipld_cid,
+  };
+
+  #[test]
+  fn test_dir_open() {
+    let (dir, name) = Dir::open("lib/Nat.cedc").unwrap();
+    assert_eq!((dir.root, name), (PathBuf::from("lib"), "Nat".to_owned()));
+    assert_eq!(dir.store.dir, PathBuf::from("lib/.cedille"));
+    let (dir, name) = Dir::open("Nat.cedc").unwrap();
+    assert_eq!(dir.path(&name), PathBuf::from("./Nat.cedc"));
+    assert!(Dir::open("Nat.ced").is_none());
+    assert!(Dir::open("Nat").is_none());
+  }
+
+  #[test]
+  fn test_dir_build() {
+    let root = std::env::temp_dir()
+      .join(format!("cedille-core-package-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    let write = |name: &str, src: &str| {
+      fs::write(root.join(format!("{}.cedc", name)), src).unwrap()
+    };
+    write(
+      "Bool",
+      "type Bool : Type = ∀ (A: Type) (t: A) (f: A) -> A;
+       def true : Bool = λ (A: Type) (t: A) (f: A) => t;
+       def false : Bool = λ (A: Type) (t: A) (f: A) => f;",
+    );
+    write(
+      "Not",
+      "import Bool;
+       def not : ∀ (b: Bool) -> Bool = λ (b: Bool) => b Bool false true;",
+    );
+    write("Loop", "import Loop;");
+    let (dir, name) = Dir::open(root.join("Not.cedc")).unwrap();
+    assert_eq!(name, "Not");
+    assert!(Dir::open(root.join("Not.txt")).is_none());
+    // checking and parsing write nothing
+    let (checked, _) = dir.check(&Target::Name(Name::from("Not"))).unwrap();
+    dir.parse(&name).unwrap();
+    assert!(!root.join(".cedille").exists());
+    assert!(!root.join(".cedille-checked").exists());
+    let (not, defs) = dir.build(&name).unwrap();
+    assert_eq!(not, checked);
+    // the checked marks are kept apart from the blocks
+    let not_def = defs.term_ref("not").unwrap();
+    assert!(dir.cache.contains(&ipld_cid(&checked_block(&not_def))));
+    assert!(defs.term_ref("not").is_some());
+    assert_eq!(dir.load(&not.cid()).unwrap().0, not);
+    assert_eq!(dir.load(&not.imports[0].cid).unwrap().0.name, "Bool".into());
+    assert!(matches!(dir.build("Loop"), Err(PackageError::ImportCycle(_))));
+    assert!(matches!(dir.parse("Loop"), Err(PackageError::ImportCycle(_))));
+    let (imports, _, module) = dir.parse("Not").unwrap();
+    assert_eq!(imports[0].1, not.imports[0]);
+    assert_eq!(module.defs[0].name(), &Name::from("not"));
+    assert!(matches!(
+      dir.build("Missing"),
+      Err(PackageError::UnknownPackage(_))
+    ));
+    fs::remove_dir_all(&root).unwrap();
+  }
+}
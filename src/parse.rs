@@ -1,7 +1,9 @@
 pub mod base;
 pub mod error;
+pub mod file;
 pub mod kind;
 pub mod pure;
 pub mod span;
+pub mod term;
+pub mod typ_;
 pub mod util;
-// pub mod term;
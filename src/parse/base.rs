@@ -8,6 +8,8 @@ use crate::parse::span::Span;
 
 use multibase::Base;
 
+use sp_cid::Cid;
+
 use nom::{
   branch::alt,
   bytes::complete::{
@@ -26,6 +28,7 @@ use sp_std::{
   vec::Vec,
   boxed::Box,
   borrow::ToOwned,
+  convert::TryFrom,
 };
    
 use alloc::string::String;
@@ -267,3 +270,16 @@ pub fn parse_multibase()
     }
   }
 }
+
+/// Parses a CID in its multibase string form
+pub fn parse_cid() -> impl Fn(Span) -> IResult<Span, Cid, ParseError<Span>> {
+  move |from: Span| {
+    let (i, o) = parse_multibase_digits()(from)?;
+    match Cid::try_from(*o.fragment()) {
+      Ok(cid) => Ok((i, cid)),
+      Err(_) => {
+        Err(nom::Err::Error(ParseError::new(from, ParseErrorKind::CidError)))
+      }
+    }
+  }
+}
@@ -41,6 +41,8 @@ pub enum ParseErrorKind {
   ReservedSyntax(String),
   TypeDefConstructorMustReturnItsType,
   InvalidSymbol(String),
+  UsesInTypeBinder(Name),
+  DuplicateDefinition(Name),
   Nom(ErrorKind),
 }
 
@@ -65,7 +67,7 @@ impl<'a> fmt::Display for ParseErrorKind {
         write!(f, "Error parsing number: {}", e)
       }
       Self::ReservedKeyword(name) => {
-        write!(f, "{}` is a reserved language keyword", name)
+        write!(f, "`{}` is a reserved language keyword", name)
       }
       Self::ReservedSyntax(_) => {
         write!(f, "Symbols beginning with '#' are reserved")
@@ -81,7 +83,27 @@ impl<'a> fmt::Display for ParseErrorKind {
           name
         )
       }
-      _ => write!(f, "internal parser error"),
+      Self::UsesInTypeBinder(name) => {
+        write!(
+          f,
+          "The type-level binder {} cannot have a multiplicity annotation",
+          name
+        )
+      }
+      Self::CidError => write!(f, "Invalid CID"),
+      Self::DuplicateDefinition(name) => {
+        write!(f, "The name {} is already defined in this file", name)
+      }
+      Self::InvalidBaseEncoding(base) => {
+        write!(f, "Invalid digits for the base {:?}", base)
+      }
+      Self::UnknownBaseCode => write!(f, "Unknown base code"),
+      Self::MultibaseError(e) => write!(f, "Invalid multibase string: {}", e),
+      Self::TypeDefConstructorMustReturnItsType => {
+        write!(f, "A constructor must return the type it constructs")
+      }
+      Self::Nom(ErrorKind::Eof) => write!(f, "Expected the end of the input"),
+      Self::Nom(_) => write!(f, "Unexpected input"),
     }
   }
 }
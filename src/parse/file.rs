@@ -0,0 +1,357 @@
+use crate::{
+  module::{
+    Def,
+    Defs,
+    Module,
+  },
+  package::Target,
+  parse::{
+    base::parse_cid,
+    error::{
+      ParseError,
+      ParseErrorKind,
+    },
+    kind::parse_kind,
+    span::Span,
+    term::parse_term_telescope,
+    typ_::parse_type_telescope,
+    util::{
+      parse_keyword,
+      parse_name,
+      parse_space,
+      parse_space1,
+      Ctx,
+    },
+  },
+  position::Pos,
+};
+use nom::{
+  branch::alt,
+  bytes::complete::tag,
+  combinator::{
+    eof,
+    map,
+  },
+  error::{
+    context,
+    ErrorKind,
+  },
+  multi::many0,
+  sequence::{
+    preceded,
+    terminated,
+  },
+  Err,
+  IResult,
+  Slice,
+};
+use sp_cid::Cid;
+use sp_std::{
+  rc::Rc,
+  vec::Vec,
+};
+
+/// Parses `import Name;`, which imports a package by name, or `import <cid>;`,
+/// which imports a stored package by CID
+pub fn parse_import(
+  input: Cid,
+) -> impl Fn(Span) -> IResult<Span, (Pos, Target), ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = tag("import")(from)?;
+    let (i, _) = parse_space1(i)?;
+    let (i, tgt) =
+      alt((map(parse_cid(), Target::Cid), map(parse_name, Target::Name)))(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, _) = tag(";")(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, (pos, tgt)))
+  }
+}
+
+/// Parses the imports at the start of a file. The definitions after them are
+/// parsed by `parse_file` once the imports are resolved.
+pub fn parse_imports(
+  input: Cid,
+) -> impl Fn(Span) -> IResult<Span, Vec<(Pos, Target)>, ParseError<Span>> {
+  move |i: Span| many0(preceded(parse_space, parse_import(input)))(i)
+}
+
+/// Parses `def name : T = t;`
+pub fn parse_def(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Def, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = tag("def")(from)?;
+    let (i, _) = parse_space1(i)?;
+    let (i, nam) = parse_name(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag(":"), parse_space)(i)?;
+    let (i, typ) =
+      parse_type_telescope(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("="), parse_space)(i)?;
+    let (i, trm) =
+      parse_term_telescope(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, _) = tag(";")(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Def::Term(pos, nam, typ, trm)))
+  }
+}
+
+/// Parses `type Name : K = T;`
+pub fn parse_type_def(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Def, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = tag("type")(from)?;
+    let (i, _) = parse_space1(i)?;
+    let (i, nam) = parse_name(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag(":"), parse_space)(i)?;
+    let (i, kind) =
+      parse_kind(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("="), parse_space)(i)?;
+    let (i, typ) =
+      parse_type_telescope(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, _) = tag(";")(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Def::Type(pos, nam, kind, typ)))
+  }
+}
+
+/// Parses the next definition of a file on top of `env`, given the
+/// definitions `defs` before it in the file
+fn parse_file_def<'a>(
+  input: Cid,
+  env: &Rc<Defs>,
+  defs: &[Def],
+  from: Span<'a>,
+) -> IResult<Span<'a>, Def, ParseError<Span<'a>>> {
+  let (i, def) = alt((
+    context(
+      "term definition",
+      parse_def(input, env.clone(), Ctx::new(), Ctx::new()),
+    ),
+    context(
+      "type definition",
+      parse_type_def(input, env.clone(), Ctx::new(), Ctx::new()),
+    ),
+  ))(from)?;
+  if defs.iter().any(|d| d.name() == def.name()) {
+    return Err(Err::Error(ParseError::new(
+      from,
+      ParseErrorKind::DuplicateDefinition(def.name().clone()),
+    )));
+  }
+  Ok((i, def))
+}
+
+/// Parses a file of definitions on top of the global definitions `defs`.
+/// Every definition can refer to the ones before it, which the parser turns
+/// into references, and definition names must be unique within the file.
+pub fn parse_file(
+  input: Cid,
+  defs: Rc<Defs>,
+) -> impl Fn(Span) -> IResult<Span, Module, ParseError<Span>> {
+  move |i: Span| {
+    let mut env = defs.clone();
+    let mut defs: Vec<Def> = Vec::new();
+    let (mut i, _) = parse_space(i)?;
+    loop {
+      if let Ok((i, _)) = eof::<Span, ParseError<Span>>(i) {
+        return Ok((i, Module { input, defs }));
+      }
+      let (i2, def) = parse_file_def(input, &env, &defs, i)?;
+      Rc::make_mut(&mut env).insert(def.clone());
+      defs.push(def);
+      let (i2, _) = parse_space(i2)?;
+      i = i2;
+    }
+  }
+}
+
+/// The input after the definition at `i` that failed to parse: after its
+/// `;`, or at the next `def` or `type` keyword if that comes first
+fn skip_def(i: Span<'_>) -> Span<'_> {
+  let s = *i.fragment();
+  let mut comment = false;
+  let mut prev = None;
+  for (n, c) in s.char_indices() {
+    if comment {
+      comment = c != '\n';
+    }
+    else if c == ';' {
+      return i.slice(n + 1..);
+    }
+    else if s[n..].starts_with("//") {
+      comment = true;
+    }
+    else if matches!(prev, Some(p) if char::is_whitespace(p))
+      && alt((parse_keyword("def"), parse_keyword("type")))(i.slice(n..))
+        .is_ok()
+    {
+      return i.slice(n..);
+    }
+    prev = Some(c);
+  }
+  i.slice(s.len()..)
+}
+
+/// Parses a file of definitions like `parse_file`, but recovers from an
+/// error in a definition by skipping to the next one. Returns the definitions
+/// that parse together with the errors of the others, in order. A definition
+/// that refers to one that does not parse fails as an undefined reference.
+pub fn parse_file_recovering(
+  input: Cid,
+  defs: Rc<Defs>,
+) -> impl Fn(Span) -> (Module, Vec<ParseError<Span>>) {
+  move |i: Span| {
+    let mut env = defs.clone();
+    let mut defs: Vec<Def> = Vec::new();
+    let mut errs = Vec::new();
+    let mut i = parse_space(i).map_or(i, |(i, _)| i);
+    while eof::<Span, ParseError<Span>>(i).is_err() {
+      match parse_file_def(input, &env, &defs, i) {
+        Ok((i2, def)) => {
+          Rc::make_mut(&mut env).insert(def.clone());
+          defs.push(def);
+          i = i2;
+        }
+        Err(Err::Error(e)) | Err(Err::Failure(e)) => {
+          errs.push(e);
+          i = skip_def(i);
+        }
+        Err(Err::Incomplete(_)) => {
+          errs.push(ParseError::new(i, ParseErrorKind::Nom(ErrorKind::Eof)));
+          break;
+        }
+      }
+      i = parse_space(i).map_or(i, |(i, _)| i);
+    }
+    (Module { input, defs }, errs)
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    parse::util::input_cid,
+    term::Term,
+    typ_::Type,
+  };
+
+  fn test(i: &str) -> IResult<Span<'_>, Module, ParseError<Span<'_>>> {
+    parse_file(input_cid(i), Rc::default())(Span::new(i))
+  }
+
+  #[test]
+  fn test_parse_file() {
+    let res = test(
+      "// Church-encoded booleans
+       type Bool : Type = ∀ (A: Type) (⁰t: A) (⁰f: A) -> A;
+       def true : Bool = λ (A: Type) (⁰t: A) (⁰f: A) => t;
+       def false : Bool = λ (A: Type) (⁰t: A) (⁰f: A) => f;
+       def not : ∀ (b: Bool) -> Bool = λ (b: Bool) => b Bool false true;
+      ",
+    );
+    assert!(res.is_ok());
+    let module = res.unwrap().1;
+    let names: Vec<&str> = module.defs.iter().map(|d| &**d.name()).collect();
+    assert_eq!(names, vec!["Bool", "true", "false", "not"]);
+    let cid = |nam: &str| module.get(nam).unwrap().cid();
+    match module.get("not") {
+      Some(Def::Term(_, _, Type::Pi(_, _, _, a, _), trm)) => {
+        assert_eq!(**a, Type::Ref(Pos::None, "Bool".into(), cid("Bool")));
+        match trm {
+          Term::Lam(_, _, _, _, bod) => match &**bod {
+            Term::App(_, _, f, t) => {
+              assert_eq!(**t, Term::Ref(Pos::None, "true".into(), cid("true")));
+              assert!(matches!(&**f, Term::App(_, _, _, x)
+                if **x == Term::Ref(Pos::None, "false".into(), cid("false"))));
+            }
+            _ => panic!("expected an application"),
+          },
+          _ => panic!("expected a lambda"),
+        }
+      }
+      _ => panic!("expected a term definition"),
+    }
+    assert!(test("").is_ok());
+    // definitions can only refer to earlier ones
+    assert!(test("def x : ∀ (A: Type) -> A = y; def y : Y = x;").is_err());
+    assert!(test("type T : Type = ∀ (A: Type) -> A; def f : T = f;").is_err());
+    // names are unique per file
+    let res = test(
+      "type T : Type = ∀ (A: Type) -> A; type T : Type = ∀ (B: Type) -> B;",
+    );
+    match res {
+      Err(Err::Error(e)) => assert_eq!(
+        e.errors,
+        vec![ParseErrorKind::DuplicateDefinition("T".into())]
+      ),
+      _ => panic!("expected a duplicate definition error"),
+    }
+    // missing terminator
+    assert!(test("type T : Type = ∀ (A: Type) -> A").is_err());
+  }
+
+  #[test]
+  fn test_parse_file_recovering() {
+    let i = "type Bool : Type = ∀ (A: Type) (t: A) (f: A) -> A;
+def true : Bool = λ (A: Type) (t: A) (f: A) => t
+def false : Bool = λ (A: Type) (t: A) (f: A) => f;
+def x : Bool = (;
+// a comment; with a semicolon
+type T : Type = ∀ (A: Type) -> A;
+def y : T = y;
+def z : T = λ (A: Type) => z;";
+    let (module, errs) =
+      parse_file_recovering(input_cid(i), Rc::default())(Span::new(i));
+    let names: Vec<&str> = module.defs.iter().map(|d| &**d.name()).collect();
+    assert_eq!(names, vec!["Bool", "false", "T"]);
+    // the missing `;` after `true`, the `(` and the self-references
+    let lines: Vec<u32> =
+      errs.iter().map(|e| e.input.location_line()).collect();
+    assert_eq!(lines, vec![3, 4, 7, 8]);
+    assert!(errs[2]
+      .errors
+      .contains(&ParseErrorKind::UndefinedReference("y".into(), Ctx::new())));
+    // a file without errors parses as with `parse_file`
+    let i = "type T : Type = ∀ (A: Type) -> A;";
+    let (module, errs) =
+      parse_file_recovering(input_cid(i), Rc::default())(Span::new(i));
+    let cids = |m: &Module| m.defs.iter().map(Def::cid).collect::<Vec<_>>();
+    assert_eq!(cids(&module), cids(&test(i).unwrap().1));
+    assert!(errs.is_empty());
+  }
+
+  #[test]
+  fn test_parse_imports() {
+    let i = "// prelude\nimport Bool;\n  import Nat ;\ndef x : X = x;";
+    let (rest, imports) = parse_imports(input_cid(i))(Span::new(i)).unwrap();
+    let targets: Vec<Target> = imports.into_iter().map(|(_, t)| t).collect();
+    assert_eq!(
+      targets,
+      vec![Target::Name("Bool".into()), Target::Name("Nat".into())]
+    );
+    assert!(rest.fragment().trim_start().starts_with("def"));
+    let cid = input_cid("Bool");
+    let i = format!("import {};", cid);
+    let (_, imports) = parse_imports(input_cid(&i))(Span::new(&i)).unwrap();
+    assert_eq!(imports[0].1, Target::Cid(cid));
+    assert!(parse_imports(input_cid("import;"))(Span::new("import;"))
+      .unwrap()
+      .1
+      .is_empty());
+  }
+}
@@ -1,18 +1,24 @@
 use crate::{
   kind::BaseKind,
+  module::Defs,
   parse::{
     error::ParseError,
     span::Span,
-    util::parse_space,
+    typ_::parse_type,
+    util::{
+      parse_space,
+      parse_token,
+      Ctx,
+    },
   },
   position::Pos,
   term::Tm,
-  uses::Uses,
+  typ_::BaseType,
 };
 use nom::{
   branch::alt,
   bytes::complete::tag,
-  combinator::value,
+  combinator::map,
   error::context,
   multi::many1,
   sequence::{
@@ -22,7 +28,17 @@ use nom::{
   IResult,
 };
 use sp_cid::Cid;
-use sp_std::boxed::Box;
+use sp_std::{
+  boxed::Box,
+  rc::Rc,
+};
+
+/// A domain of a `Π` kind, either a kind or a type
+#[derive(Clone, Debug)]
+pub enum Dom<const T: Tm> {
+  Kind(BaseKind<T>),
+  Type(BaseType<T>),
+}
 
 pub fn parse_kind_type<const T: Tm>(
   input: Cid,
@@ -34,26 +50,47 @@ pub fn parse_kind_type<const T: Tm>(
   }
 }
 
-/// Parses `Π Type -> Type`
-pub fn parse_kind_pi_ty<const T: Tm>(
+/// Parses `Π Type -> Type` and `Π T -> Type`
+pub fn parse_kind_pi<const T: Tm>(
   input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
 ) -> impl Fn(Span) -> IResult<Span, BaseKind<T>, ParseError<Span>> {
   move |from: Span| {
-    let (i, _) = tag("Π")(from)?;
-    let (i, ks) = many1(preceded(parse_space, parse_kind(input)))(i)?;
+    let (i, _) = parse_token("Π", "Pi")(from)?;
+    let (i, ds) = many1(preceded(
+      parse_space,
+      alt((
+        map(
+          parse_kind(input, defs.clone(), ctx.clone(), tctx.clone()),
+          Dom::Kind,
+        ),
+        map(
+          parse_type(input, defs.clone(), ctx.clone(), tctx.clone()),
+          Dom::Type,
+        ),
+      )),
+    ))(i)?;
     let (i, _) = parse_space(i)?;
     let (i, _) = tag("->")(i)?;
     let (i, _) = parse_space(i)?;
-    let (upto, bod) = parse_kind(input)(i)?;
+    let (upto, bod) =
+      parse_kind(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
     let pos = Pos::from_upto(input, from, upto);
-    let kind = ks
-      .into_iter()
-      .fold(bod, |acc, k| BaseKind::<T>::PiTy(pos, Box::new(k), Box::new(acc)));
+    let kind = ds.into_iter().rev().fold(bod, |acc, d| match d {
+      Dom::Kind(k) => BaseKind::<T>::PiTy(pos, Box::new(k), Box::new(acc)),
+      Dom::Type(t) => BaseKind::<T>::Pi(pos, Box::new(t), Box::new(acc)),
+    });
     Ok((upto, kind))
   }
 }
+
 pub fn parse_kind<const T: Tm>(
   input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
 ) -> impl Fn(Span) -> IResult<Span, BaseKind<T>, ParseError<Span>> {
   move |i: Span| {
     alt((
@@ -61,12 +98,15 @@ pub fn parse_kind<const T: Tm>(
         "parenthesized kind",
         delimited(
           preceded(tag("("), parse_space),
-          parse_kind(input),
+          parse_kind(input, defs.clone(), ctx.clone(), tctx.clone()),
           preceded(parse_space, tag(")")),
         ),
       ),
       context("Type kind", parse_kind_type(input)),
-      context("Π kind", parse_kind_pi_ty(input)),
+      context(
+        "Π kind",
+        parse_kind_pi(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
     ))(i)
   }
 }
@@ -76,7 +116,9 @@ pub mod tests {
   use super::*;
   use crate::{
     kind::Kind,
+    name::Name,
     parse::util::input_cid,
+    typ_::Type,
   };
 
   #[test]
@@ -84,7 +126,9 @@ pub mod tests {
     fn test<const T: Tm>(
       i: &str,
     ) -> IResult<Span, BaseKind<T>, ParseError<Span>> {
-      parse_kind(input_cid(i))(Span::new(i))
+      parse_kind(input_cid(i), Rc::default(), Ctx::new(), Ctx::new())(
+        Span::new(i),
+      )
     }
 
     let res = test("Π Type -> Type");
@@ -115,5 +159,27 @@ pub mod tests {
     );
     let res = test::<false>("Π (Π Type -> Type) Type Type -> Type");
     assert!(res.is_ok());
+    let res = test("Π (∀ (X: Type) -> X) Type -> Type");
+    assert!(res.is_ok());
+    let res: Kind = res.unwrap().1;
+    assert_eq!(
+      res,
+      Kind::Pi(
+        Pos::None,
+        Box::new(Type::PiTy(
+          Pos::None,
+          Name::from("X"),
+          Box::new(Kind::Type(Pos::None)),
+          Box::new(Type::Var(Pos::None, Name::from("X"), 0u64))
+        )),
+        Box::new(Kind::PiTy(
+          Pos::None,
+          Box::new(Kind::Type(Pos::None)),
+          Box::new(Kind::Type(Pos::None))
+        ))
+      )
+    );
+    let res = test::<false>("Π A -> Type");
+    assert!(res.is_err());
   }
 }
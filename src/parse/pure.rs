@@ -1,4 +1,5 @@
 use crate::{
+  module::Defs,
   parse::{
     error::{
       ParseError,
@@ -6,6 +7,7 @@ use crate::{
     },
     span::Span,
     util::{
+      parse_lambda,
       parse_name,
       parse_space,
       parse_tele_end,
@@ -32,11 +34,13 @@ use nom::{
 use sp_cid::Cid;
 use sp_std::{
   boxed::Box,
+  rc::Rc,
   vec::Vec,
 };
 
 pub fn parse_var(
   input: Cid,
+  defs: Rc<Defs>,
   ctx: Ctx,
 ) -> impl Fn(Span) -> IResult<Span, Pure, ParseError<Span>> {
   move |from: Span| {
@@ -45,6 +49,9 @@ pub fn parse_var(
     if let Some((idx, _)) = ctx.iter().enumerate().find(|(_, x)| **x == nam) {
       Ok((upto, Pure::Var(pos, nam.clone(), idx as u64)))
     }
+    else if let Some(cid) = defs.term_ref(&nam) {
+      Ok((upto, Pure::Ref(pos, nam.clone(), cid)))
+    }
     else {
       Err(Err::Error(ParseError::new(
         upto,
@@ -56,10 +63,11 @@ pub fn parse_var(
 
 pub fn parse_lam(
   input: Cid,
+  defs: Rc<Defs>,
   ctx: Ctx,
 ) -> impl Fn(Span) -> IResult<Span, Pure, ParseError<Span>> {
   move |from: Span| {
-    let (i, _) = tag("λ")(from)?;
+    let (i, _) = parse_lambda(from)?;
     let (i, _) = parse_space(i)?;
     let (i, bs) = many1(preceded(parse_space, parse_name))(i)?;
     let (i, _) = parse_space(i)?;
@@ -68,7 +76,7 @@ pub fn parse_lam(
     for b in bs.iter() {
       ctx2.push_front(b.clone());
     }
-    let (upto, bod) = parse_pure_telescope(input, ctx2)(i)?;
+    let (upto, bod) = parse_pure_telescope(input, defs.clone(), ctx2)(i)?;
     let pos = Pos::from_upto(input, from, upto);
     let trm =
       bs.into_iter().rev().fold(bod, |acc, n| Pure::Lam(pos, n, Box::new(acc)));
@@ -78,6 +86,7 @@ pub fn parse_lam(
 
 pub fn parse_args(
   input: Cid,
+  defs: Rc<Defs>,
   ctx: Ctx,
 ) -> impl FnMut(Span) -> IResult<Span, Vec<Pure>, ParseError<Span>> {
   move |mut i: Span| {
@@ -88,7 +97,9 @@ pub fn parse_args(
         Ok((i2, _)) => return Ok((i2, res)),
         _ => {}
       }
-      match preceded(parse_space, parse_pure(input, ctx.clone()))(i) {
+      match preceded(parse_space, parse_pure(input, defs.clone(), ctx.clone()))(
+        i,
+      ) {
         Err(e) => return Err(e),
         Ok((i2, x)) => {
           res.push(x);
@@ -101,12 +112,14 @@ pub fn parse_args(
 
 pub fn parse_pure_telescope(
   input: Cid,
+  defs: Rc<Defs>,
   ctx: Ctx,
 ) -> impl Fn(Span) -> IResult<Span, Pure, ParseError<Span>> {
   move |from: Span| {
-    let (i, fun) = context("app fun", parse_pure(input, ctx.clone()))(from)?;
+    let (i, fun) =
+      context("app fun", parse_pure(input, defs.clone(), ctx.clone()))(from)?;
     let (i, _) = parse_space(i)?;
-    let (upto, args) = parse_args(input, ctx.clone())(i)?;
+    let (upto, args) = parse_args(input, defs.clone(), ctx.clone())(i)?;
     let pos = Pos::from_upto(input, from, upto);
     let trm = args
       .into_iter()
@@ -117,6 +130,7 @@ pub fn parse_pure_telescope(
 
 pub fn parse_pure(
   input: Cid,
+  defs: Rc<Defs>,
   ctx: Ctx,
 ) -> impl Fn(Span) -> IResult<Span, Pure, ParseError<Span>> {
   move |i: Span| {
@@ -125,12 +139,12 @@ pub fn parse_pure(
         "Pure application telescope",
         delimited(
           preceded(tag("("), parse_space),
-          parse_pure_telescope(input, ctx.clone()),
+          parse_pure_telescope(input, defs.clone(), ctx.clone()),
           preceded(parse_space, tag(")")),
         ),
       ),
-      context("Pure lambda", parse_lam(input, ctx.clone())),
-      context("Pure variable", parse_var(input, ctx.clone())),
+      context("Pure lambda", parse_lam(input, defs.clone(), ctx.clone())),
+      context("Pure variable", parse_var(input, defs.clone(), ctx.clone())),
     ))(i)
   }
 }
@@ -146,7 +160,7 @@ pub mod tests {
   #[test]
   fn test_parse_pure() {
     fn test(i: &str) -> IResult<Span, Pure, ParseError<Span>> {
-      parse_pure(input_cid(i), Ctx::new())(Span::new(i))
+      parse_pure(input_cid(i), Rc::default(), Ctx::new())(Span::new(i))
     }
 
     let res = test("λ x y z => x");
@@ -202,9 +216,11 @@ pub mod tests {
   #[quickcheck]
   fn test_pure_parse_print(x: Pure) -> bool {
     let i = format!("{}", x);
-    match parse_pure_telescope(input_cid(&i), Ctx::new())(Span::new(&i)) {
+    match parse_pure_telescope(input_cid(&i), Rc::default(), Ctx::new())(
+      Span::new(&i),
+    ) {
       Ok((_, y)) => {
-        if x == y {
+        if x.syntactic_eq(&y) {
           true
         }
         else {
@@ -1,152 +1,680 @@
-// use crate::{
-//  kind::{
-//    BaseKind,
-//    Kind,
-//  },
-//  name::{
-//    is_valid_symbol_string,
-//    Name,
-//  },
-//  parse::{
-//    error::{
-//      ParseError,
-//      ParseErrorKind,
-//    },
-//    span::Span,
-//  },
-//  position::Pos,
-//  term::{
-//    Term,
-//    Tm,
-//    Uses,
-//  },
-//};
-// use alloc::string::{
-//  String,
-//  ToString,
-//};
-// use nom::{
-//  branch::alt,
-//  bytes::complete::{
-//    tag,
-//    take_till,
-//    take_till1,
-//  },
-//  character::complete::{
-//    digit1,
-//    multispace0,
-//    multispace1,
-//    satisfy,
-//  },
-//  combinator::{
-//    eof,
-//    peek,
-//    value,
-//  },
-//  error::context,
-//  multi::{
-//    many0,
-//    many1,
-//  },
-//  sequence::{
-//    delimited,
-//    preceded,
-//    terminated,
-//  },
-//  Err,
-//  IResult,
-//};
-// use sp_cid::Cid;
-// use sp_im::vector::Vector;
-// use sp_ipld::{
-//  dag_cbor::DagCborCodec,
-//  Codec,
-//};
-// use sp_multihash::{
-//  Code,
-//  MultihashDigest,
-//};
-// use sp_std::{
-//  borrow::ToOwned,
-//  boxed::Box,
-//  vec::Vec,
-//};
-//
-//// pub fn parse_pure_var() -> impl Fn(Span) -> IResult<Span, Uses,
-//// ParseError<Span>>
-//// {
-////  move |i: Span| {
-////    alt((
-////      value(Uses::None, terminated(tag("⁰"), multispace1)),
-////      value(Uses::Once, terminated(tag("¹"), multispace1)),
-////      value(Uses::Affi, terminated(tag("˚"), multispace1)),
-////      value(Uses::Many, terminated(tag("⁺"), multispace1)),
-////    ))(i)
-////  }
-//// }
-//// pub fn parse_var(
-////  input: Cid,
-////  defs: Defs,
-////  rec: Option<Name>,
-////  ctx: Ctx,
-//// ) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
-////  move |from: Span| {
-////    let (upto, nam) = context("local or global reference",
-//// parse_name)(from)?;    let pos = Pos::from_upto(input, from, upto);
-////    let is_rec_name = match rec.clone() {
-////      Some(rec_ref) => nam == rec_ref,
-////      _ => false,
-////    };
-////    if let Some((idx, _)) = ctx.iter().enumerate().find(|(_, x)| **x == nam)
-//// {      Ok((upto, Term::Var(pos, nam.clone(), idx as u64)))
-////    }
-////    else if is_rec_name {
-////      Ok((upto, Term::Rec(pos)))
-////    }
-////    else if let Some(def) = defs.get(&nam) {
-////      Ok((upto, Term::Ref(pos, nam.clone(), def.def_cid, def.ast_cid)))
-////    }
-////    else {
-////      Err(Err::Error(ParseError::new(
-////        upto,
-////        ParseErrorKind::UndefinedReference(nam.clone(), ctx.clone()),
-////      )))
-////    }
-////  }
-//// }
-// pub fn parse_uses() -> impl Fn(Span) -> IResult<Span, Uses, ParseError<Span>>
-// {  move |i: Span| {
-//    alt((
-//      value(Uses::None, tag("⁰")),
-//      value(Uses::Once, tag("¹")),
-//      value(Uses::Affi, tag("˚")),
-//      value(Uses::Many, tag("⁺")),
-//    ))(i)
-//  }
-//}
-//
-////#[cfg(test)]
-//// pub mod tests {
-////  use super::*;
-////
-////  #[test]
-////  fn test_parse_kind() {
-////    fn test<const T: Tm>(
-////      i: &str,
-////    ) -> IResult<Span, BaseKind<T>, ParseError<Span>> {
-////      parse_kind(input_cid(i))(Span::new(i))
-////    }
-////
-////    let res = test("Π Type -> Type");
-////    assert!(res.is_ok());
-////    let res: Kind = res.unwrap().1;
-////    assert_eq!(
-////      res,
-////      Kind::PiKd(
-////        Pos::None,
-////        Box::new(Kind::Type(Pos::None)),
-////        Box::new(Kind::Type(Pos::None))
-////      )
-////    );
-////  }
-//// }
+use crate::{
+  module::Defs,
+  parse::{
+    error::{
+      ParseError,
+      ParseErrorKind,
+    },
+    kind::parse_kind,
+    pure::parse_pure,
+    span::Span,
+    typ_::{
+      parse_binders,
+      parse_type,
+      parse_type_telescope,
+      Binder,
+    },
+    util::{
+      parse_lambda,
+      parse_name,
+      parse_space,
+      parse_space1,
+      parse_tele_end,
+      parse_uses,
+      Ctx,
+    },
+  },
+  position::Pos,
+  term::Term,
+  typ_::Type,
+  uses::Uses,
+};
+use nom::{
+  branch::alt,
+  bytes::complete::tag,
+  combinator::{
+    map,
+    opt,
+    peek,
+  },
+  error::context,
+  sequence::{
+    delimited,
+    pair,
+    preceded,
+    terminated,
+  },
+  Err,
+  IResult,
+};
+use sp_cid::Cid;
+use sp_std::{
+  boxed::Box,
+  rc::Rc,
+  vec::Vec,
+};
+
+/// An argument in an application telescope, either `⁺x` or a type `T`
+#[derive(Clone, Debug)]
+pub enum Arg {
+  Term(Uses, Term),
+  Type(Type),
+}
+
+pub fn parse_term_var(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (upto, nam) = context("local or global reference", parse_name)(from)?;
+    let pos = Pos::from_upto(input, from, upto);
+    if let Some((idx, _)) = ctx.iter().enumerate().find(|(_, x)| **x == nam) {
+      Ok((upto, Term::Var(pos, nam.clone(), idx as u64)))
+    }
+    else if let Some(cid) = defs.term_ref(&nam) {
+      Ok((upto, Term::Ref(pos, nam.clone(), cid)))
+    }
+    else {
+      Err(Err::Error(ParseError::new(
+        upto,
+        ParseErrorKind::UndefinedReference(nam.clone(), ctx.clone()),
+      )))
+    }
+  }
+}
+
+/// Parses `λ (⁰A: Type) (¹x: A) => x`
+pub fn parse_term_lam(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = parse_lambda(from)?;
+    let (i, (bs, ctx2, tctx2)) =
+      parse_binders(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("=>"), parse_space)(i)?;
+    let (upto, bod) =
+      parse_term_telescope(input, defs.clone(), ctx2, tctx2)(i)?;
+    // each binder's λ starts at the binder, so that it has its own position
+    let trm = bs.into_iter().rev().fold(bod, |acc, (start, b)| {
+      let pos = Pos::from_upto(input, start, upto);
+      match b {
+        Binder::Term(u, n, t) => {
+          Term::Lam(pos, u.unwrap_or(Uses::Many), n, Box::new(t), Box::new(acc))
+        }
+        Binder::Type(n, k) => Term::LamTy(pos, n, Box::new(k), Box::new(acc)),
+      }
+    });
+    Ok((upto, trm))
+  }
+}
+
+/// Parses `let ⁰x = y in b`
+pub fn parse_term_let(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = tag("let")(from)?;
+    let (i, _) = parse_space1(i)?;
+    let (i, uses) = opt(terminated(parse_uses(), parse_space))(i)?;
+    let (i, nam) = parse_name(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("="), parse_space)(i)?;
+    let (i, trm) =
+      parse_term_telescope(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("in"), parse_space1)(i)?;
+    let mut ctx2 = ctx.clone();
+    ctx2.push_front(nam.clone());
+    let (upto, bod) =
+      parse_term_telescope(input, defs.clone(), ctx2, tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    let uses = uses.unwrap_or(Uses::Many);
+    Ok((upto, Term::Let(pos, uses, nam, Box::new(trm), Box::new(bod))))
+  }
+}
+
+/// Parses `let T: K = A in b`
+pub fn parse_term_let_ty(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = tag("let")(from)?;
+    let (i, _) = parse_space1(i)?;
+    let (i, nam) = parse_name(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag(":"), parse_space)(i)?;
+    let (i, kind) =
+      parse_kind(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("="), parse_space)(i)?;
+    let (i, typ) =
+      parse_type_telescope(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("in"), parse_space1)(i)?;
+    let mut tctx2 = tctx.clone();
+    tctx2.push_front(nam.clone());
+    let (upto, bod) =
+      parse_term_telescope(input, defs.clone(), ctx.clone(), tctx2)(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((
+      upto,
+      Term::LetTy(pos, nam, Box::new(kind), Box::new(typ), Box::new(bod)),
+    ))
+  }
+}
+
+/// Parses `intersect x y T`
+pub fn parse_term_intersect(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("intersect"), parse_space1)(from)?;
+    let (i, x) = parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, y) = parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, typ) =
+      parse_type(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Term::Intersect(pos, Box::new(x), Box::new(y), Box::new(typ))))
+  }
+}
+
+/// Parses `projectL x`
+pub fn parse_term_project_l(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("projectL"), parse_space1)(from)?;
+    let (upto, x) =
+      parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Term::ProjectL(pos, Box::new(x))))
+  }
+}
+
+/// Parses `projectR x`
+pub fn parse_term_project_r(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("projectR"), parse_space1)(from)?;
+    let (upto, x) =
+      parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Term::ProjectR(pos, Box::new(x))))
+  }
+}
+
+/// Parses `refl x y`, where `x` and `y` are pure terms
+pub fn parse_term_refl(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("refl"), parse_space1)(from)?;
+    let (i, x) = parse_pure(input, defs.clone(), ctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, y) = parse_pure(input, defs.clone(), ctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Term::Refl(pos, Box::new(x), Box::new(y))))
+  }
+}
+
+/// Parses `sym x`
+pub fn parse_term_sym(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("sym"), parse_space1)(from)?;
+    let (upto, x) =
+      parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Term::Sym(pos, Box::new(x))))
+  }
+}
+
+/// Parses `absurd T x`
+pub fn parse_term_absurd(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("absurd"), parse_space1)(from)?;
+    let (i, typ) =
+      parse_type(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, x) =
+      parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Term::Absurd(pos, Box::new(typ), Box::new(x))))
+  }
+}
+
+/// Parses `rewrite x T y`, where `T` is a pure type
+pub fn parse_term_rewrite(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("rewrite"), parse_space1)(from)?;
+    let (i, x) = parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, typ) =
+      parse_type::<true>(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, y) =
+      parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Term::Rewrite(pos, Box::new(x), Box::new(typ), Box::new(y))))
+  }
+}
+
+/// Parses `cast x y z`, where `z` is a pure term
+pub fn parse_term_cast(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("cast"), parse_space1)(from)?;
+    let (i, x) = parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, y) = parse_term(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, z) = parse_pure(input, defs.clone(), ctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, Term::Cast(pos, Box::new(x), Box::new(y), Box::new(z))))
+  }
+}
+
+/// Parses a term argument `⁺x`, or a type argument `T`. An argument without a
+/// multiplicity is a `Uses::Many` term argument if it parses as a term, so a
+/// name bound as both a term and a type variable is read as the term.
+pub fn parse_term_arg(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Arg, ParseError<Span>> {
+  move |i: Span| {
+    alt((
+      map(
+        pair(
+          terminated(parse_uses(), parse_space),
+          parse_term(input, defs.clone(), ctx.clone(), tctx.clone()),
+        ),
+        |(u, x)| Arg::Term(u, x),
+      ),
+      map(parse_term(input, defs.clone(), ctx.clone(), tctx.clone()), |x| {
+        Arg::Term(Uses::Many, x)
+      }),
+      map(
+        parse_type(input, defs.clone(), ctx.clone(), tctx.clone()),
+        Arg::Type,
+      ),
+    ))(i)
+  }
+}
+
+pub fn parse_term_args(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl FnMut(Span) -> IResult<Span, Vec<Arg>, ParseError<Span>> {
+  move |mut i: Span| {
+    let mut res = Vec::new();
+
+    loop {
+      if let Ok((i2, _)) = preceded(parse_space, peek(parse_tele_end))(i) {
+        return Ok((i2, res));
+      }
+      match preceded(
+        parse_space,
+        parse_term_arg(input, defs.clone(), ctx.clone(), tctx.clone()),
+      )(i)
+      {
+        Err(e) => return Err(e),
+        Ok((i2, x)) => {
+          res.push(x);
+          i = i2;
+        }
+      }
+    }
+  }
+}
+
+pub fn parse_term_telescope(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |from: Span| {
+    let (i, fun) = context(
+      "app fun",
+      parse_term(input, defs.clone(), ctx.clone(), tctx.clone()),
+    )(from)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, args) =
+      parse_term_args(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    let trm = args.into_iter().fold(fun, |acc, arg| match arg {
+      Arg::Term(u, x) => Term::App(pos, u, Box::new(acc), Box::new(x)),
+      Arg::Type(t) => Term::AppTy(pos, Box::new(acc), Box::new(t)),
+    });
+    Ok((upto, trm))
+  }
+}
+
+pub fn parse_term(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Term, ParseError<Span>> {
+  move |i: Span| {
+    alt((
+      context(
+        "Term application telescope",
+        delimited(
+          preceded(tag("("), parse_space),
+          parse_term_telescope(input, defs.clone(), ctx.clone(), tctx.clone()),
+          preceded(parse_space, tag(")")),
+        ),
+      ),
+      context(
+        "Term lambda",
+        parse_term_lam(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "Let type",
+        parse_term_let_ty(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "Let term",
+        parse_term_let(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "intersect",
+        parse_term_intersect(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "projectL",
+        parse_term_project_l(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "projectR",
+        parse_term_project_r(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context("refl", parse_term_refl(input, defs.clone(), ctx.clone())),
+      context(
+        "sym",
+        parse_term_sym(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "absurd",
+        parse_term_absurd(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "rewrite",
+        parse_term_rewrite(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "cast",
+        parse_term_cast(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "Term variable",
+        parse_term_var(input, defs.clone(), ctx.clone()),
+      ),
+    ))(i)
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    kind::Kind,
+    name::Name,
+    parse::util::input_cid,
+    pure::Pure,
+  };
+
+  #[test]
+  fn test_parse_term() {
+    fn test(i: &str) -> IResult<Span<'_>, Term, ParseError<Span<'_>>> {
+      parse_term_telescope(input_cid(i), Rc::default(), Ctx::new(), Ctx::new())(
+        Span::new(i),
+      )
+    }
+
+    let res = test("λ (A: Type) (¹x: A) => x");
+    assert!(res.is_ok());
+    let res: Term = res.unwrap().1;
+    assert_eq!(
+      res,
+      Term::LamTy(
+        Pos::None,
+        Name::from("A"),
+        Box::new(Kind::Type(Pos::None)),
+        Box::new(Term::Lam(
+          Pos::None,
+          Uses::Once,
+          Name::from("x"),
+          Box::new(Type::Var(Pos::None, Name::from("A"), 0u64)),
+          Box::new(Term::Var(Pos::None, Name::from("x"), 0u64))
+        ))
+      )
+    );
+    let res =
+      test("λ (A: Type) (f: ∀ (⁰x: A) -> A) (⁰x: A) => let ˚y = f A ⁰x in y");
+    assert!(res.is_ok());
+    let res: Term = res.unwrap().1;
+    let a = |i| Box::new(Type::Var(Pos::None, Name::from("A"), i));
+    assert_eq!(
+      res,
+      Term::LamTy(
+        Pos::None,
+        Name::from("A"),
+        Box::new(Kind::Type(Pos::None)),
+        Box::new(Term::Lam(
+          Pos::None,
+          Uses::Many,
+          Name::from("f"),
+          Box::new(Type::Pi(
+            Pos::None,
+            Uses::None,
+            Name::from("x"),
+            a(0),
+            a(0)
+          )),
+          Box::new(Term::Lam(
+            Pos::None,
+            Uses::None,
+            Name::from("x"),
+            a(0),
+            Box::new(Term::Let(
+              Pos::None,
+              Uses::Affi,
+              Name::from("y"),
+              Box::new(Term::App(
+                Pos::None,
+                Uses::None,
+                Box::new(Term::AppTy(
+                  Pos::None,
+                  Box::new(Term::Var(Pos::None, Name::from("f"), 1u64)),
+                  a(0)
+                )),
+                Box::new(Term::Var(Pos::None, Name::from("x"), 0u64))
+              )),
+              Box::new(Term::Var(Pos::None, Name::from("y"), 0u64))
+            ))
+          ))
+        ))
+      )
+    );
+    let res = test("λ (A: Type) (x: A) => sym (refl x (λ y => y))");
+    assert!(res.is_ok());
+    let res: Term = res.unwrap().1;
+    assert_eq!(
+      res,
+      Term::LamTy(
+        Pos::None,
+        Name::from("A"),
+        Box::new(Kind::Type(Pos::None)),
+        Box::new(Term::Lam(
+          Pos::None,
+          Uses::Many,
+          Name::from("x"),
+          a(0),
+          Box::new(Term::Sym(
+            Pos::None,
+            Box::new(Term::Refl(
+              Pos::None,
+              Box::new(Pure::Var(Pos::None, Name::from("x"), 0u64)),
+              Box::new(Pure::Lam(
+                Pos::None,
+                Name::from("y"),
+                Box::new(Pure::Var(Pos::None, Name::from("y"), 0u64))
+              ))
+            ))
+          ))
+        ))
+      )
+    );
+    let res = test("let T: Type = ∀ (X: Type) -> X in λ (x: T) => x");
+    assert!(res.is_ok());
+    let res = test("λ (A: Type) (p: A) => projectL (projectR p)");
+    assert!(res.is_ok());
+    let res = test(
+      "λ (A: Type) (x: A) (e: {x ≃ x}) => rewrite e (λ (y: A) => {y ≃ x}) \
+       (refl x x)",
+    );
+    assert!(res.is_ok());
+    let res = test("λ (A: Type) (x: A) => intersect x x (ι (y: A) -> A)");
+    assert!(res.is_ok());
+    let res = test("λ (x: A) => x");
+    assert!(res.is_err());
+    let res = test("λ (A: Type) => A");
+    assert!(res.is_err());
+    // an argument named like a term and a type variable is the term
+    let res = test("λ (A: Type) (f: ∀ (x: A) -> A) (A: A) => f A");
+    match res.unwrap().1 {
+      Term::LamTy(_, _, _, f) => match *f {
+        Term::Lam(_, _, _, _, a) => match *a {
+          Term::Lam(_, _, _, _, b) => {
+            assert!(matches!(*b, Term::App(_, Uses::Many, _, _)))
+          }
+          t => panic!("{}", t),
+        },
+        t => panic!("{}", t),
+      },
+      t => panic!("{}", t),
+    }
+  }
+
+  #[test]
+  fn test_parse_term_binder_pos() {
+    let i = "λ (A: Type)  (x: A) => x";
+    let offsets = |t: &Term| match t.pos() {
+      Pos::Some(p) => (p.from_offset, p.upto_offset),
+      Pos::None => panic!("{}", t),
+    };
+    let t = parse_term_telescope(
+      input_cid(i),
+      Rc::default(),
+      Ctx::new(),
+      Ctx::new(),
+    )(Span::new(i))
+    .unwrap()
+    .1;
+    // the λ of each binder starts at the binder
+    assert_eq!(offsets(&t), (3, 25));
+    match t {
+      Term::LamTy(_, _, _, x) => assert_eq!(offsets(&x), (14, 25)),
+      t => panic!("{}", t),
+    }
+  }
+
+  #[test]
+  fn test_parse_term_ascii() {
+    fn test(i: &str) -> Term {
+      parse_term_telescope(input_cid(i), Rc::default(), Ctx::new(), Ctx::new())(
+        Span::new(i),
+      )
+      .unwrap_or_else(|e| panic!("{}: {}", i, e))
+      .1
+    }
+    for (u, a) in &[
+      ("λ (A: Type) (¹x: A) => x", "\\ (A: Type) ({1}x: A) => x"),
+      (
+        "λ (A: Type) (f: ∀ (⁰x: A) -> A) (⁰x: A) => let ˚y = f A ⁰x in y",
+        "lambda (A: Type) (f: forall ({0}x: A) -> A) ({0}x: A) => let {~}y = \
+         f A {0}x in y",
+      ),
+      (
+        "λ (A: Type) (x: ι (y: A) -> {y ≃ y}) => projectL x",
+        "lambda (A: Type) (x: iota (y: A) -> {y ~= y}) => projectL x",
+      ),
+      (
+        "λ (F: Π Type -> Type) (A: Type) (f: ∀ (x: F A) -> F A) (x: F A) => f \
+         ⁺x",
+        "lambda (F: Pi Type -> Type) (A: Type) (f: forall (x: F A) -> F A) \
+         (x: F A) => f {+}x",
+      ),
+    ] {
+      assert!(test(u).syntactic_eq(&test(a)), "{}", a);
+    }
+    // a keyword only ends where a name would
+    test("λ (lambdas: Type) (iotas: lambdas) => iotas");
+  }
+
+  #[test]
+  fn test_term_alpha_eq() {
+    fn test(i: &str) -> Term {
+      parse_term_telescope(input_cid(i), Rc::default(), Ctx::new(), Ctx::new())(
+        Span::new(i),
+      )
+      .unwrap()
+      .1
+    }
+    let a = test("λ (A: Type) (x: A) (e: {x ≃ x}) => sym e");
+    let b = test("λ (B: Type) (y: B) (d: {y ≃ y}) => sym d");
+    assert!(a.alpha_eq(&b));
+    assert!(!a.syntactic_eq(&b));
+    let c = test("λ (B: Type) (⁰y: B) (d: {y ≃ y}) => sym d");
+    assert!(!a.alpha_eq(&c));
+  }
+}
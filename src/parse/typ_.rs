@@ -0,0 +1,479 @@
+use crate::{
+  kind::BaseKind,
+  module::Defs,
+  name::Name,
+  parse::{
+    error::{
+      ParseError,
+      ParseErrorKind,
+    },
+    kind::parse_kind,
+    pure::parse_pure_telescope,
+    span::Span,
+    util::{
+      parse_eql_sign,
+      parse_lambda,
+      parse_name,
+      parse_space,
+      parse_tele_end,
+      parse_token,
+      parse_uses,
+      Ctx,
+    },
+  },
+  position::Pos,
+  term::Tm,
+  typ_::BaseType,
+  uses::Uses,
+};
+use nom::{
+  branch::alt,
+  bytes::complete::tag,
+  combinator::{
+    map,
+    opt,
+    peek,
+  },
+  error::context,
+  sequence::{
+    delimited,
+    preceded,
+    terminated,
+  },
+  Err,
+  IResult,
+};
+use sp_cid::Cid;
+use sp_std::{
+  boxed::Box,
+  rc::Rc,
+  vec::Vec,
+};
+
+/// A parenthesized binder: `(⁰x: A)` binds a term variable, `(X: K)` a type
+/// variable
+#[derive(Clone, Debug)]
+pub enum Binder<const T: Tm> {
+  Term(Option<Uses>, Name, BaseType<T>),
+  Type(Name, BaseKind<T>),
+}
+
+impl<const T: Tm> Binder<T> {
+  /// Extends the term and type contexts with the bound name
+  pub fn bind(&self, ctx: &mut Ctx, tctx: &mut Ctx) {
+    match self {
+      Self::Term(_, nam, _) => ctx.push_front(nam.clone()),
+      Self::Type(nam, _) => tctx.push_front(nam.clone()),
+    }
+  }
+}
+
+pub fn parse_binder<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Binder<T>, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = tag("(")(from)?;
+    let (i, _) = parse_space(i)?;
+    let (i, uses) = opt(terminated(parse_uses(), parse_space))(i)?;
+    let (i, nam) = parse_name(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = tag(":")(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, bind) = match uses {
+      Some(_) => {
+        let (i, typ) =
+          parse_type_telescope(input, defs.clone(), ctx.clone(), tctx.clone())(
+            i,
+          )?;
+        (i, Binder::Term(uses, nam, typ))
+      }
+      None => alt((
+        map(parse_kind(input, defs.clone(), ctx.clone(), tctx.clone()), |k| {
+          Binder::Type(nam.clone(), k)
+        }),
+        map(
+          parse_type_telescope(input, defs.clone(), ctx.clone(), tctx.clone()),
+          |t| Binder::Term(None, nam.clone(), t),
+        ),
+      ))(i)?,
+    };
+    let (i, _) = parse_space(i)?;
+    let (upto, _) = tag(")")(i)?;
+    Ok((upto, bind))
+  }
+}
+
+/// Binders, each with where it starts, and the term and type contexts they
+/// extend
+pub type Binders<'a, const T: Tm> = (Vec<(Span<'a>, Binder<T>)>, Ctx, Ctx);
+
+/// Parses one or more binders, each in the scope of the ones before it
+pub fn parse_binders<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, Binders<T>, ParseError<Span>> {
+  move |from: Span| {
+    let mut ctx = ctx.clone();
+    let mut tctx = tctx.clone();
+    let mut res = Vec::new();
+    let mut i = from;
+    loop {
+      let (start, _) = parse_space(i)?;
+      match parse_binder(input, defs.clone(), ctx.clone(), tctx.clone())(start)
+      {
+        Ok((i2, b)) => {
+          b.bind(&mut ctx, &mut tctx);
+          res.push((start, b));
+          i = i2;
+        }
+        Err(Err::Error(e)) => {
+          if res.is_empty() {
+            return Err(Err::Error(e));
+          }
+          return Ok((i, (res, ctx, tctx)));
+        }
+        Err(e) => return Err(e),
+      }
+    }
+  }
+}
+
+pub fn parse_type_var<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, BaseType<T>, ParseError<Span>> {
+  move |from: Span| {
+    let (upto, nam) = context("type variable", parse_name)(from)?;
+    let pos = Pos::from_upto(input, from, upto);
+    if let Some((idx, _)) = tctx.iter().enumerate().find(|(_, x)| **x == nam) {
+      Ok((upto, BaseType::Var(pos, nam.clone(), idx as u64)))
+    }
+    else if let Some(cid) = defs.type_ref(&nam) {
+      Ok((upto, BaseType::Ref(pos, nam.clone(), cid)))
+    }
+    else {
+      Err(Err::Error(ParseError::new(
+        upto,
+        ParseErrorKind::UndefinedReference(nam.clone(), tctx.clone()),
+      )))
+    }
+  }
+}
+
+/// Parses `∀ (⁰x: A) (X: K) -> B`
+pub fn parse_type_pi<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, BaseType<T>, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = parse_token("∀", "forall")(from)?;
+    let (i, (bs, ctx2, tctx2)) =
+      parse_binders(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("->"), parse_space)(i)?;
+    let (upto, bod) =
+      parse_type_telescope(input, defs.clone(), ctx2, tctx2)(i)?;
+    let typ = bs.into_iter().rev().fold(bod, |acc, (start, b)| {
+      let pos = Pos::from_upto(input, start, upto);
+      match b {
+        Binder::Term(u, n, t) => BaseType::Pi(
+          pos,
+          u.unwrap_or(Uses::Many),
+          n,
+          Box::new(t),
+          Box::new(acc),
+        ),
+        Binder::Type(n, k) => {
+          BaseType::PiTy(pos, n, Box::new(k), Box::new(acc))
+        }
+      }
+    });
+    Ok((upto, typ))
+  }
+}
+
+/// Parses `λ (x: A) (X: K) => B`
+pub fn parse_type_lam<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, BaseType<T>, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = parse_lambda(from)?;
+    let (i, (bs, ctx2, tctx2)) =
+      parse_binders(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    for (_, b) in bs.iter() {
+      if let Binder::Term(Some(_), nam, _) = b {
+        return Err(Err::Error(ParseError::new(
+          i,
+          ParseErrorKind::UsesInTypeBinder(nam.clone()),
+        )));
+      }
+    }
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("=>"), parse_space)(i)?;
+    let (upto, bod) =
+      parse_type_telescope(input, defs.clone(), ctx2, tctx2)(i)?;
+    let typ = bs.into_iter().rev().fold(bod, |acc, (start, b)| {
+      let pos = Pos::from_upto(input, start, upto);
+      match b {
+        Binder::Term(_, n, t) => {
+          BaseType::Lam(pos, n, Box::new(t), Box::new(acc))
+        }
+        Binder::Type(n, k) => {
+          BaseType::LamTy(pos, n, Box::new(k), Box::new(acc))
+        }
+      }
+    });
+    Ok((upto, typ))
+  }
+}
+
+/// Parses `ι (x: A) -> B`
+pub fn parse_type_iota<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, BaseType<T>, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = parse_token("ι", "iota")(from)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("("), parse_space)(i)?;
+    let (i, nam) = parse_name(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag(":"), parse_space)(i)?;
+    let (i, typ) =
+      parse_type_telescope(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = tag(")")(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(tag("->"), parse_space)(i)?;
+    let mut ctx2 = ctx.clone();
+    ctx2.push_front(nam.clone());
+    let (upto, bod) =
+      parse_type_telescope(input, defs.clone(), ctx2, tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, BaseType::Iota(pos, nam, Box::new(typ), Box::new(bod))))
+  }
+}
+
+/// Parses `{x ≃ y}`, where `x` and `y` are pure terms
+pub fn parse_type_eql<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, BaseType<T>, ParseError<Span>> {
+  move |from: Span| {
+    let (i, _) = terminated(tag("{"), parse_space)(from)?;
+    let (i, a) = parse_pure_telescope(input, defs.clone(), ctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (i, _) = terminated(parse_eql_sign, parse_space)(i)?;
+    let (i, b) = parse_pure_telescope(input, defs.clone(), ctx.clone())(i)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, _) = tag("}")(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    Ok((upto, BaseType::Eql(pos, Box::new(a), Box::new(b))))
+  }
+}
+
+pub fn parse_type_args<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl FnMut(Span) -> IResult<Span, Vec<BaseType<T>>, ParseError<Span>> {
+  move |mut i: Span| {
+    let mut res = Vec::new();
+
+    loop {
+      if let Ok((i2, _)) = preceded(parse_space, peek(parse_tele_end))(i) {
+        return Ok((i2, res));
+      }
+      match preceded(
+        parse_space,
+        parse_type(input, defs.clone(), ctx.clone(), tctx.clone()),
+      )(i)
+      {
+        Err(e) => return Err(e),
+        Ok((i2, x)) => {
+          res.push(x);
+          i = i2;
+        }
+      }
+    }
+  }
+}
+
+pub fn parse_type_telescope<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, BaseType<T>, ParseError<Span>> {
+  move |from: Span| {
+    let (i, fun) = context(
+      "type app fun",
+      parse_type(input, defs.clone(), ctx.clone(), tctx.clone()),
+    )(from)?;
+    let (i, _) = parse_space(i)?;
+    let (upto, args) =
+      parse_type_args(input, defs.clone(), ctx.clone(), tctx.clone())(i)?;
+    let pos = Pos::from_upto(input, from, upto);
+    let typ = args
+      .into_iter()
+      .fold(fun, |acc, arg| BaseType::AppTy(pos, Box::new(acc), Box::new(arg)));
+    Ok((upto, typ))
+  }
+}
+
+pub fn parse_type<const T: Tm>(
+  input: Cid,
+  defs: Rc<Defs>,
+  ctx: Ctx,
+  tctx: Ctx,
+) -> impl Fn(Span) -> IResult<Span, BaseType<T>, ParseError<Span>> {
+  move |i: Span| {
+    alt((
+      context(
+        "Type application telescope",
+        delimited(
+          preceded(tag("("), parse_space),
+          parse_type_telescope(input, defs.clone(), ctx.clone(), tctx.clone()),
+          preceded(parse_space, tag(")")),
+        ),
+      ),
+      context(
+        "∀ type",
+        parse_type_pi(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "Type lambda",
+        parse_type_lam(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "ι type",
+        parse_type_iota(input, defs.clone(), ctx.clone(), tctx.clone()),
+      ),
+      context(
+        "Equality type",
+        parse_type_eql(input, defs.clone(), ctx.clone()),
+      ),
+      context(
+        "Type variable",
+        parse_type_var(input, defs.clone(), tctx.clone()),
+      ),
+    ))(i)
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    kind::Kind,
+    parse::util::input_cid,
+    pure::Pure,
+    typ_::Type,
+  };
+
+  #[test]
+  fn test_parse_type() {
+    fn test(i: &str) -> IResult<Span<'_>, Type, ParseError<Span<'_>>> {
+      parse_type_telescope(input_cid(i), Rc::default(), Ctx::new(), Ctx::new())(
+        Span::new(i),
+      )
+    }
+
+    let res = test("∀ (A: Type) (¹x: A) -> A");
+    assert!(res.is_ok());
+    let res: Type = res.unwrap().1;
+    assert_eq!(
+      res,
+      Type::PiTy(
+        Pos::None,
+        Name::from("A"),
+        Box::new(Kind::Type(Pos::None)),
+        Box::new(Type::Pi(
+          Pos::None,
+          Uses::Once,
+          Name::from("x"),
+          Box::new(Type::Var(Pos::None, Name::from("A"), 0u64)),
+          Box::new(Type::Var(Pos::None, Name::from("A"), 0u64))
+        ))
+      )
+    );
+    let res = test("∀ (F: Π Type -> Type) (A: Type) -> F A");
+    assert!(res.is_ok());
+    let res: Type = res.unwrap().1;
+    assert_eq!(
+      res,
+      Type::PiTy(
+        Pos::None,
+        Name::from("F"),
+        Box::new(Kind::PiTy(
+          Pos::None,
+          Box::new(Kind::Type(Pos::None)),
+          Box::new(Kind::Type(Pos::None))
+        )),
+        Box::new(Type::PiTy(
+          Pos::None,
+          Name::from("A"),
+          Box::new(Kind::Type(Pos::None)),
+          Box::new(Type::AppTy(
+            Pos::None,
+            Box::new(Type::Var(Pos::None, Name::from("F"), 1u64)),
+            Box::new(Type::Var(Pos::None, Name::from("A"), 0u64))
+          ))
+        ))
+      )
+    );
+    let res = test("∀ (x: Type) -> y");
+    assert!(res.is_err());
+    let res = test("λ (X: Type) (x: X) => ι (y: X) -> {x ≃ λ z => y}");
+    assert!(res.is_ok());
+    let res: Type = res.unwrap().1;
+    assert_eq!(
+      res,
+      Type::LamTy(
+        Pos::None,
+        Name::from("X"),
+        Box::new(Kind::Type(Pos::None)),
+        Box::new(Type::Lam(
+          Pos::None,
+          Name::from("x"),
+          Box::new(Type::Var(Pos::None, Name::from("X"), 0u64)),
+          Box::new(Type::Iota(
+            Pos::None,
+            Name::from("y"),
+            Box::new(Type::Var(Pos::None, Name::from("X"), 0u64)),
+            Box::new(Type::Eql(
+              Pos::None,
+              Box::new(Pure::Var(Pos::None, Name::from("x"), 1u64)),
+              Box::new(Pure::Lam(
+                Pos::None,
+                Name::from("z"),
+                Box::new(Pure::Var(Pos::None, Name::from("y"), 1u64))
+              ))
+            ))
+          ))
+        ))
+      )
+    );
+    let res = test("∀ (X: Type) (x: X) -> {x ≃ x}");
+    assert!(res.is_ok());
+    let res = test("λ (X: Type) (¹x: X) => X");
+    assert!(res.is_err());
+    let res = test("ι (X: Type) -> X");
+    assert!(res.is_err());
+  }
+}
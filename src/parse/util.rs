@@ -22,14 +22,19 @@ use nom::{
   character::complete::{
     multispace0,
     multispace1,
+    satisfy,
   },
   combinator::{
     eof,
+    not,
     peek,
     value,
   },
   multi::many0,
-  sequence::terminated,
+  sequence::{
+    preceded,
+    terminated,
+  },
   Err,
   IResult,
 };
@@ -61,6 +66,10 @@ pub fn reserved_symbols() -> Vector<String> {
     String::from("∀"),
     String::from("lambda"),
     String::from("forall"),
+    String::from("\\"),
+    String::from("Pi"),
+    String::from("iota"),
+    String::from("~="),
     String::from("⁰"),
     String::from("¹"),
     String::from("˚"),
@@ -76,8 +85,20 @@ pub fn reserved_symbols() -> Vector<String> {
     String::from("data"),
     String::from("self"),
     String::from("def"),
+    String::from("import"),
     String::from("case"),
     String::from("Type"),
+    String::from("Π"),
+    String::from("ι"),
+    String::from("≃"),
+    String::from("intersect"),
+    String::from("projectL"),
+    String::from("projectR"),
+    String::from("refl"),
+    String::from("sym"),
+    String::from("absurd"),
+    String::from("rewrite"),
+    String::from("cast"),
   ])
 }
 pub fn parse_line_comment(i: Span) -> IResult<Span, Span, ParseError<Span>> {
@@ -111,10 +132,40 @@ pub fn parse_uses() -> impl Fn(Span) -> IResult<Span, Uses, ParseError<Span>> {
       value(Uses::Once, tag("¹")),
       value(Uses::Affi, tag("˚")),
       value(Uses::Many, tag("⁺")),
+      value(Uses::None, tag("{0}")),
+      value(Uses::Once, tag("{1}")),
+      value(Uses::Affi, tag("{~}")),
+      value(Uses::Many, tag("{+}")),
     ))(i)
   }
 }
 
+/// Parses the keyword `word`, which must not run on into a name, as `iotas`
+pub fn parse_keyword(
+  word: &'static str,
+) -> impl Fn(Span) -> IResult<Span, Span, ParseError<Span>> {
+  move |i: Span| terminated(tag(word), not(satisfy(|x| !is_name_end(x))))(i)
+}
+
+/// Parses a token spelled `unicode`, or the keyword `ascii` in the ASCII
+/// syntax
+pub fn parse_token(
+  unicode: &'static str,
+  ascii: &'static str,
+) -> impl Fn(Span) -> IResult<Span, Span, ParseError<Span>> {
+  move |i: Span| alt((tag(unicode), parse_keyword(ascii)))(i)
+}
+
+/// Parses `λ`, `\` or `lambda`
+pub fn parse_lambda(i: Span) -> IResult<Span, Span, ParseError<Span>> {
+  alt((tag("\\"), parse_token("λ", "lambda")))(i)
+}
+
+/// Parses `≃` or `~=`
+pub fn parse_eql_sign(i: Span) -> IResult<Span, Span, ParseError<Span>> {
+  alt((tag("≃"), tag("~=")))(i)
+}
+
 pub fn is_numeric_symbol_string1(s: &str) -> bool {
   s.starts_with('0')
     || s.starts_with('1')
@@ -150,17 +201,20 @@ pub fn is_numeric_symbol_string2(s: &str) -> bool {
     || s.starts_with("+9")
 }
 
+/// Whether `x` ends a name
+pub fn is_name_end(x: char) -> bool {
+  char::is_whitespace(x)
+    | (x == ':')
+    | (x == ';')
+    | (x == ')')
+    | (x == '(')
+    | (x == '{')
+    | (x == '}')
+    | (x == ',')
+}
+
 pub fn parse_name(from: Span) -> IResult<Span, Name, ParseError<Span>> {
-  let (i, s) = take_till1(|x| {
-    char::is_whitespace(x)
-      | (x == ':')
-      | (x == ';')
-      | (x == ')')
-      | (x == '(')
-      | (x == '{')
-      | (x == '}')
-      | (x == ',')
-  })(from)?;
+  let (i, s) = take_till1(is_name_end)(from)?;
   let s: String = String::from(s.fragment().to_owned());
   if reserved_symbols().contains(&s) {
     Err(Err::Error(ParseError::new(from, ParseErrorKind::ReservedKeyword(s))))
@@ -188,9 +242,10 @@ pub fn parse_tele_end(i: Span) -> IResult<Span, (), ParseError<Span>> {
     peek(tag("->")),
     peek(tag(";")),
     peek(tag(")")),
-    peek(tag("{")),
+    peek(preceded(not(parse_uses()), tag("{"))),
     peek(tag("}")),
     peek(tag(",")),
+    peek(parse_eql_sign),
     peek(eof),
   ))(i)?;
   Ok((i, ()))
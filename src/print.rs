@@ -1 +1,290 @@
+pub mod doc;
+pub mod kind;
 pub mod pure;
+pub mod term;
+pub mod typ_;
+
+use crate::{
+  name::Name,
+  parse::util::Ctx,
+  position::Pos,
+  uses::Uses,
+};
+
+use alloc::string::{
+  String,
+  ToString,
+};
+
+use sp_std::{
+  collections::btree_set::BTreeSet,
+  fmt,
+  rc::Rc,
+};
+
+/// The line width that the command line tools lay out terms in
+pub const WIDTH: usize = 80;
+
+/// How the printers spell, annotate and lay out syntax
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+  /// append the de Bruijn index to each variable, `x^0`
+  pub indices: bool,
+  /// spell the keywords and multiplicities in ASCII, `lambda` for `λ`
+  pub ascii: bool,
+  /// print the multiplicities of binders and arguments
+  pub uses: bool,
+  /// append the source position to each variable and reference, `x@1:5`
+  pub positions: bool,
+  /// the line width to break lines at
+  pub width: usize,
+  /// the spaces per indentation level of a broken line
+  pub indent: usize,
+}
+
+impl Default for Config {
+  /// Syntax that parses back to the printed term, on one line
+  fn default() -> Self {
+    Config {
+      indices: false,
+      ascii: false,
+      uses: true,
+      positions: false,
+      width: usize::MAX,
+      indent: 2,
+    }
+  }
+}
+
+impl Config {
+  /// The default configuration with indices and positions, for debugging
+  pub fn debug() -> Self {
+    Config { indices: true, positions: true, ..Config::default() }
+  }
+
+  /// The configuration a `Display` impl prints with: `debug` for the
+  /// alternate flag `{:#}`, and the line width of a width flag like `{:80}`
+  pub fn from_formatter(f: &fmt::Formatter<'_>) -> Self {
+    let cfg = if f.alternate() { Config::debug() } else { Config::default() };
+    Config { width: f.width().unwrap_or(cfg.width), ..cfg }
+  }
+
+  /// `unicode`, or `ascii` in ASCII mode
+  pub fn token(
+    &self,
+    unicode: &'static str,
+    ascii: &'static str,
+  ) -> &'static str {
+    if self.ascii {
+      ascii
+    }
+    else {
+      unicode
+    }
+  }
+
+  /// The multiplicity of an argument
+  pub fn uses(&self, uses: Uses) -> &'static str {
+    if !self.uses {
+      return "";
+    }
+    match uses {
+      Uses::None => self.token("⁰", "{0}"),
+      Uses::Once => self.token("¹", "{1}"),
+      Uses::Affi => self.token("˚", "{~}"),
+      Uses::Many => self.token("⁺", "{+}"),
+    }
+  }
+
+  /// The multiplicity of a binder, which is left out when it is the default
+  /// `Uses::Many`
+  pub fn binder_uses(&self, uses: Uses) -> &'static str {
+    match uses {
+      Uses::Many => "",
+      _ => self.uses(uses),
+    }
+  }
+
+  /// A variable displayed as `nam`, with its index and position if enabled
+  pub fn var(&self, nam: &str, idx: u64, pos: Pos) -> String {
+    let nam =
+      if self.indices { format!("{}^{}", nam, idx) } else { nam.to_string() };
+    self.at(nam, pos)
+  }
+
+  /// `s`, followed by the position `pos` if enabled and known
+  pub fn at(&self, s: String, pos: Pos) -> String {
+    match pos {
+      Pos::Some(p) if self.positions => {
+        format!("{}@{}:{}", s, p.from_line, p.from_column)
+      }
+      _ => s,
+    }
+  }
+}
+
+/// Binder names can be empty, which the parsers spell `_`
+pub fn print_name(nam: &str) -> &str {
+  if nam.is_empty() {
+    "_"
+  }
+  else {
+    nam
+  }
+}
+
+/// The names that the variables in scope are displayed with. A binder that
+/// would capture a variable or a reference that its body refers to is
+/// displayed with a subscript, `x₁`, so that the printed syntax parses back to
+/// the same de Bruijn indices.
+#[derive(Clone, Debug, Default)]
+pub struct Scope {
+  /// the displayed names of the term variables in scope, innermost first
+  pub terms: Ctx,
+  /// the displayed names of the type variables in scope, innermost first
+  pub types: Ctx,
+  /// the free variables and references of the printed term
+  pub free: Rc<Free>,
+  /// number renamed binders with ASCII digits, `x1`
+  pub ascii: bool,
+  /// keep the names of term and type variables apart, as in a term, whose
+  /// arguments the parser reads as terms before it tries types
+  pub apart: bool,
+}
+
+/// The names a term refers to outside of some binders: the displayed names
+/// of its free variables and the names of its references, by namespace
+#[derive(Clone, Debug, Default)]
+pub struct Free {
+  pub terms: BTreeSet<Name>,
+  pub types: BTreeSet<Name>,
+}
+
+impl Scope {
+  pub fn new(cfg: &Config, free: Free) -> Self {
+    Scope {
+      terms: Ctx::new(),
+      types: Ctx::new(),
+      free: Rc::new(free),
+      ascii: cfg.ascii,
+      apart: false,
+    }
+  }
+
+  /// The name to display a term binder named `nam` with, and the scope of its
+  /// body. `body` adds the names the body refers to outside of the binder,
+  /// and is only called if `nam` could capture one of them.
+  pub fn bind_term(
+    &self,
+    nam: &str,
+    body: impl FnOnce(&mut Free),
+  ) -> (Name, Scope) {
+    let nam = fresh(
+      nam,
+      self.ascii,
+      |x| self.shows(x, true),
+      || {
+        let mut free = Free::default();
+        body(&mut free);
+        if self.apart {
+          free.terms.append(&mut free.types);
+        }
+        free.terms
+      },
+    );
+    let mut scope = self.clone();
+    scope.terms.push_front(nam.clone());
+    (nam, scope)
+  }
+
+  /// The name to display a type binder named `nam` with, and the scope of its
+  /// body
+  pub fn bind_type(
+    &self,
+    nam: &str,
+    body: impl FnOnce(&mut Free),
+  ) -> (Name, Scope) {
+    let nam = fresh(
+      nam,
+      self.ascii,
+      |x| self.shows(x, false),
+      || {
+        let mut free = Free::default();
+        body(&mut free);
+        if self.apart {
+          free.types.extend(self.terms.iter().cloned());
+          free.types.extend(self.free.terms.iter().cloned());
+        }
+        free.types
+      },
+    );
+    let mut scope = self.clone();
+    scope.types.push_front(nam.clone());
+    (nam, scope)
+  }
+
+  /// Whether `nam` is the displayed name of a variable in scope or a free
+  /// name, of the term namespace if `term`, or of either if `apart`
+  fn shows(&self, nam: &str, term: bool) -> bool {
+    let shows = |names: &Ctx, free: &BTreeSet<Name>| {
+      names.iter().any(|x| **x == *nam) || free.contains(nam)
+    };
+    let terms = || shows(&self.terms, &self.free.terms);
+    let types = || shows(&self.types, &self.free.types);
+    match (self.apart, term) {
+      (true, _) => terms() || types(),
+      (false, true) => terms(),
+      (false, false) => types(),
+    }
+  }
+
+  /// The displayed name of the term variable `nam` of index `idx`, which is
+  /// its own name when it is free
+  pub fn term(&self, nam: &str, idx: u64) -> String {
+    display(&self.terms, nam, idx)
+  }
+
+  /// The displayed name of the type variable `nam` of index `idx`
+  pub fn typ(&self, nam: &str, idx: u64) -> String {
+    display(&self.types, nam, idx)
+  }
+}
+
+fn display(names: &Ctx, nam: &str, idx: u64) -> String {
+  match names.get(idx as usize) {
+    Some(nam) => nam.to_string(),
+    None => print_name(nam).to_string(),
+  }
+}
+
+/// `nam`, or if it `clash`es, `nam` with the first subscript not among the
+/// `taken` names that the body of its binder refers to, in ASCII digits if
+/// `ascii`
+fn fresh(
+  nam: &str,
+  ascii: bool,
+  clash: impl Fn(&str) -> bool,
+  taken: impl FnOnce() -> BTreeSet<Name>,
+) -> Name {
+  let nam = print_name(nam);
+  if !clash(nam) {
+    return Name::from(nam);
+  }
+  let taken = taken();
+  (0u64..)
+    .map(|i| match i {
+      0 => nam.to_string(),
+      _ if ascii => format!("{}{}", nam, i),
+      _ => format!("{}{}", nam, subscript(i)),
+    })
+    .find(|x| !taken.contains(x.as_str()))
+    .map(Name::from)
+    .unwrap()
+}
+
+fn subscript(i: u64) -> String {
+  i.to_string()
+    .chars()
+    .filter_map(|d| char::from_u32('₀' as u32 + d.to_digit(10)?))
+    .collect()
+}
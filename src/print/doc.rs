@@ -0,0 +1,142 @@
+use alloc::string::{
+  String,
+  ToString,
+};
+
+use sp_std::{
+  boxed::Box,
+  vec::Vec,
+};
+
+/// A document in the style of Wadler's "A prettier printer": text with line
+/// breaks that are laid out as spaces when their group fits the width
+#[derive(Clone, Debug)]
+pub enum Doc {
+  Text(String),
+  /// A space, or a newline followed by the current indentation
+  Line,
+  Cat(Vec<Doc>),
+  /// Indents the line breaks of a document by more levels
+  Nest(usize, Box<Doc>),
+  /// Lays out all line breaks of a document as spaces, if it fits
+  Group(Box<Doc>),
+}
+
+pub fn text(s: impl ToString) -> Doc { Doc::Text(s.to_string()) }
+
+pub fn line() -> Doc { Doc::Line }
+
+pub fn concat(docs: Vec<Doc>) -> Doc { Doc::Cat(docs) }
+
+pub fn nest(levels: usize, doc: Doc) -> Doc { Doc::Nest(levels, Box::new(doc)) }
+
+pub fn group(doc: Doc) -> Doc { Doc::Group(Box::new(doc)) }
+
+/// `head` followed by `docs`, each on its own indented line when they do not
+/// all fit on one, as in an application spine
+pub fn spine(head: Doc, docs: Vec<Doc>) -> Doc {
+  let docs = docs.into_iter().flat_map(|doc| vec![line(), doc]).collect();
+  group(concat(vec![head, nest(1, concat(docs))]))
+}
+
+/// `head` followed by `bod` on an indented line when they do not fit on one,
+/// as in the body of a binder
+pub fn hang(head: Doc, bod: Doc) -> Doc {
+  group(concat(vec![head, nest(1, concat(vec![line(), bod]))]))
+}
+
+/// Whether the document `doc`, laid out flat, and then whatever `rest` lays
+/// out up to its next line break, fit in `width` columns
+fn fits(mut width: usize, doc: &Doc, rest: &[(usize, bool, &Doc)]) -> bool {
+  let mut stack = vec![(true, doc)];
+  let mut rest = rest.iter().rev();
+  loop {
+    let (flat, doc) = match stack.pop() {
+      Some(next) => next,
+      None => match rest.next() {
+        Some((_, flat, doc)) => (*flat, *doc),
+        None => return true,
+      },
+    };
+    match doc {
+      Doc::Text(s) => match width.checked_sub(s.chars().count()) {
+        Some(w) => width = w,
+        None => return false,
+      },
+      Doc::Line if flat => match width.checked_sub(1) {
+        Some(w) => width = w,
+        None => return false,
+      },
+      Doc::Line => return true,
+      Doc::Cat(docs) => stack.extend(docs.iter().rev().map(|d| (flat, d))),
+      Doc::Nest(_, doc) | Doc::Group(doc) => stack.push((flat, doc)),
+    }
+  }
+}
+
+impl Doc {
+  /// Lays out the document in `width` columns, indenting each nesting level
+  /// by `indent` spaces. Text longer than the width still overflows it.
+  pub fn render(&self, width: usize, indent: usize) -> String {
+    let mut out = String::new();
+    let mut col = 0;
+    let mut stack: Vec<(usize, bool, &Doc)> = vec![(0, false, self)];
+    while let Some((ind, flat, doc)) = stack.pop() {
+      match doc {
+        Doc::Text(s) => {
+          out.push_str(s);
+          col += s.chars().count();
+        }
+        Doc::Line if flat => {
+          out.push(' ');
+          col += 1;
+        }
+        Doc::Line => {
+          out.push('\n');
+          (0..ind).for_each(|_| out.push(' '));
+          col = ind;
+        }
+        Doc::Cat(docs) => {
+          stack.extend(docs.iter().rev().map(|d| (ind, flat, d)))
+        }
+        Doc::Nest(levels, doc) => {
+          stack.push((ind + levels * indent, flat, doc))
+        }
+        Doc::Group(doc) => {
+          let flat = flat || fits(width.saturating_sub(col), doc, &stack);
+          stack.push((ind, flat, doc));
+        }
+      }
+    }
+    out
+  }
+
+  /// Lays out the document on a single line
+  pub fn flat(&self) -> String { self.render(usize::MAX, 0) }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+
+  #[test]
+  fn test_render() {
+    let app = spine(text("f"), vec![text("aaa"), text("bbb"), text("ccc")]);
+    let lam = hang(text("λ x =>"), app);
+    assert_eq!(lam.flat(), "λ x => f aaa bbb ccc");
+    assert_eq!(lam.render(20, 2), "λ x => f aaa bbb ccc");
+    assert_eq!(lam.render(16, 2), "λ x =>\n  f aaa bbb ccc");
+    assert_eq!(lam.render(10, 2), "λ x =>\n  f\n    aaa\n    bbb\n    ccc");
+    assert_eq!(
+      lam.render(10, 4),
+      "λ x =>\n    f\n        aaa\n        bbb\n        ccc"
+    );
+    // the text after a group counts towards its width, up to the next break
+    let doc = concat(vec![
+      group(concat(vec![text("a"), line(), text("b")])),
+      text("cd"),
+    ]);
+    assert_eq!(doc.render(5, 2), "a bcd");
+    assert_eq!(doc.render(4, 2), "a\nbcd");
+  }
+}
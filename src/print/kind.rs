@@ -0,0 +1,207 @@
+use crate::{
+  kind::BaseKind,
+  print::{
+    doc::{
+      concat,
+      text,
+      Doc,
+    },
+    typ_,
+    Config,
+    Free,
+    Scope,
+  },
+  term::Tm,
+};
+
+use alloc::string::String;
+
+use sp_std::vec::Vec;
+
+/// Adds the names of the references, and the displayed names in `scope` of
+/// the term and type variables of index `dep` and `tdep` or more in `kind`, to
+/// `names`
+pub fn free_names<const T: Tm>(
+  kind: &BaseKind<T>,
+  scope: &Scope,
+  dep: u64,
+  tdep: u64,
+  names: &mut Free,
+) {
+  match kind {
+    BaseKind::Type(_) => (),
+    BaseKind::Pi(_, dom, bod) => {
+      typ_::free_names(dom, scope, dep, tdep, names);
+      free_names(bod, scope, dep, tdep, names);
+    }
+    BaseKind::PiTy(_, dom, bod) => {
+      free_names(dom, scope, dep, tdep, names);
+      free_names(bod, scope, dep, tdep, names);
+    }
+  }
+}
+
+/// A `Π` domain: `Type` as is, other kinds in parentheses, and types as
+/// arguments
+fn doc_domain<const T: Tm>(
+  cfg: &Config,
+  scope: &Scope,
+  kind: &BaseKind<T>,
+) -> Doc {
+  match kind {
+    BaseKind::Type(_) => doc_in(cfg, scope, kind),
+    _ => concat(vec![text("("), doc_in(cfg, scope, kind), text(")")]),
+  }
+}
+
+/// Lays out `kind`, whose free variables are bound in `scope`
+pub fn doc_in<const T: Tm>(
+  cfg: &Config,
+  scope: &Scope,
+  kind: &BaseKind<T>,
+) -> Doc {
+  match kind {
+    BaseKind::Type(_) => text("Type"),
+    BaseKind::Pi(..) | BaseKind::PiTy(..) => {
+      let mut doms = Vec::new();
+      let mut bod = kind;
+      loop {
+        match bod {
+          BaseKind::Pi(_, dom, b) => {
+            doms.push(typ_::doc_parens(cfg, scope, dom));
+            bod = b;
+          }
+          BaseKind::PiTy(_, dom, b) => {
+            doms.push(doc_domain(cfg, scope, dom));
+            bod = b;
+          }
+          BaseKind::Type(_) => break,
+        }
+      }
+      typ_::doc_binders(
+        cfg.token("Π", "Pi"),
+        doms,
+        " ->",
+        doc_in(cfg, scope, bod),
+      )
+    }
+  }
+}
+
+pub fn doc<const T: Tm>(cfg: &Config, kind: &BaseKind<T>) -> Doc {
+  let mut free = Free::default();
+  free_names(kind, &Scope::default(), 0, 0, &mut free);
+  doc_in(cfg, &Scope::new(cfg, free), kind)
+}
+
+pub fn print<const T: Tm>(cfg: &Config, kind: &BaseKind<T>) -> String {
+  doc(cfg, kind).render(cfg.width, cfg.indent)
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    gen::tests::gen_range,
+    kind::Kind,
+    parse::{
+      kind::parse_kind,
+      span::Span,
+      util::{
+        input_cid,
+        Ctx,
+      },
+    },
+    position::Pos,
+    print::typ_::tests::arbitrary_scoped_type,
+  };
+  use quickcheck::{
+    Arbitrary,
+    Gen,
+  };
+  use sp_std::{
+    boxed::Box,
+    rc::Rc,
+  };
+
+  /// A kind of at most `depth` nested constructors whose variables are in
+  /// `ctx` and `tctx`
+  pub fn arbitrary_scoped_kind<const T: Tm>(
+    g: &mut Gen,
+    depth: usize,
+    ctx: &Ctx,
+    tctx: &Ctx,
+    collide: bool,
+  ) -> BaseKind<T> {
+    let case = if depth == 0 { 0 } else { gen_range(g, 0..3) };
+    let depth = depth.saturating_sub(1);
+    match case {
+      0 => BaseKind::Type(Pos::None),
+      1 => BaseKind::Pi(
+        Pos::None,
+        Box::new(arbitrary_scoped_type(g, depth, ctx, tctx, collide)),
+        Box::new(arbitrary_scoped_kind(g, depth, ctx, tctx, collide)),
+      ),
+      _ => BaseKind::PiTy(
+        Pos::None,
+        Box::new(arbitrary_scoped_kind(g, depth, ctx, tctx, collide)),
+        Box::new(arbitrary_scoped_kind(g, depth, ctx, tctx, collide)),
+      ),
+    }
+  }
+
+  /// A closed kind that round-trips through the printer and the parser
+  #[derive(Debug, Clone)]
+  pub struct Scoped(pub Kind);
+
+  impl Arbitrary for Scoped {
+    fn arbitrary(g: &mut Gen) -> Self {
+      Scoped(arbitrary_scoped_kind(g, 4, &Ctx::new(), &Ctx::new(), false))
+    }
+  }
+
+  #[test]
+  fn test_print_kind() {
+    fn parse(i: &str) -> Kind {
+      parse_kind(input_cid(i), Rc::default(), Ctx::new(), Ctx::new())(
+        Span::new(i),
+      )
+      .unwrap_or_else(|e| panic!("{}: {}", i, e))
+      .1
+    }
+    for i in &[
+      "Type",
+      "Π Type -> Type",
+      "Π (Π Type -> Type) Type -> Type",
+      "Π ({λ x => x ≃ λ x => x}) Type -> Type",
+    ] {
+      assert_eq!(print(&Config::default(), &parse(i)), *i);
+    }
+  }
+
+  #[quickcheck]
+  fn test_kind_parse_print(x: Scoped) -> bool {
+    let i = format!("{}", x.0);
+    match parse_kind(input_cid(&i), Rc::default(), Ctx::new(), Ctx::new())(
+      Span::new(&i),
+    ) {
+      Ok((rest, y)) => {
+        if rest.fragment().is_empty() && x.0.syntactic_eq(&y) {
+          true
+        }
+        else {
+          println!("{:?}", x.0);
+          println!("{}", x.0);
+          println!("{:?}", y);
+          println!("{}", y);
+          false
+        }
+      }
+      Err(e) => {
+        println!("{}", x.0);
+        println!("{}", e);
+        false
+      }
+    }
+  }
+}
@@ -1,56 +1,158 @@
-use crate::pure::Pure;
+use crate::{
+  name::Name,
+  print::{
+    doc::{
+      concat,
+      hang,
+      spine,
+      text,
+      Doc,
+    },
+    Config,
+    Free,
+    Scope,
+  },
+  pure::Pure,
+};
 
 use alloc::string::{
   String,
   ToString,
 };
 
-fn print_name(nam: &str) -> &str { if nam.is_empty() { "_" } else { nam } }
-
-fn is_atom(term: &Pure) -> bool { matches!(term, Pure::Var(..)) }
+use sp_std::vec::Vec;
 
-fn print_parens(ind: bool, term: &Pure) -> String {
-  if is_atom(term) {
-    print(ind, term)
-  }
-  else {
-    format!("({})", print(ind, term))
-  }
+fn is_atom(term: &Pure) -> bool {
+  matches!(term, Pure::Var(..) | Pure::Ref(..))
 }
 
-fn print_lambdas(ind: bool, name: &str, bod: &Pure) -> String {
-  match bod {
-    Pure::Lam(_, bod_nam, bod_bod) => {
-      format!("{} {}", print_name(name), print_lambdas(ind, bod_nam, bod_bod))
+/// Adds the names of the references, and the displayed names in `scope` of
+/// the variables of index `dep` or more in `term`, to `names`
+pub fn free_names(term: &Pure, scope: &Scope, dep: u64, names: &mut Free) {
+  match term {
+    Pure::Var(_, nam, idx) if *idx >= dep => {
+      names.terms.insert(Name::from(scope.term(nam, idx - dep)));
+    }
+    Pure::Var(..) => (),
+    Pure::Lam(_, _, bod) => free_names(bod, scope, dep + 1, names),
+    Pure::App(_, fun, arg) => {
+      free_names(fun, scope, dep, names);
+      free_names(arg, scope, dep, names);
+    }
+    Pure::Ref(_, nam, _) => {
+      names.terms.insert(nam.clone());
     }
-    _ => format!("{} => {}", print_name(name), print(ind, bod)),
   }
 }
 
-fn print_applications(ind: bool, fun: &Pure) -> String {
-  match fun {
-    Pure::App(_, f_fun, f_arg) => {
-      format!("{} {}", print_applications(ind, f_fun), print_parens(ind, f_arg),)
-    }
-    _ => format!("{}", print_parens(ind, fun)),
+pub fn doc_parens(cfg: &Config, scope: &Scope, term: &Pure) -> Doc {
+  if is_atom(term) {
+    doc_in(cfg, scope, term)
+  }
+  else {
+    concat(vec![text("("), doc_in(cfg, scope, term), text(")")])
   }
 }
 
-pub fn print(ind: bool, term: &Pure) -> String {
+/// Lays out `term`, whose free variables are bound in `scope`
+pub fn doc_in(cfg: &Config, scope: &Scope, term: &Pure) -> Doc {
   match term {
-    Pure::Var(_, nam, index) => {
-      if ind {
-        format!("{}^{}", nam, index)
-      }
-      else {
-        nam.to_string()
-      }
+    Pure::Var(pos, nam, index) => {
+      text(cfg.var(&scope.term(nam, *index), *index, *pos))
     }
-    Pure::Lam(_, name, bod) => {
-      format!("λ {}", print_lambdas(ind, &name.to_string(), bod))
+    Pure::Lam(..) => {
+      let mut names = Vec::new();
+      let mut scope = scope.clone();
+      let mut bod = term;
+      while let Pure::Lam(_, nam, b) = bod {
+        let (nam, s) =
+          scope.bind_term(nam, |names| free_names(b, &scope, 1, names));
+        names.push(nam);
+        scope = s;
+        bod = b;
+      }
+      let names: Vec<&str> = names.iter().map(|n| n as &str).collect();
+      let head = format!("{} {} =>", cfg.token("λ", "lambda"), names.join(" "));
+      hang(text(head), doc_in(cfg, &scope, bod))
     }
-    Pure::App(_, fun, arg) => {
-      format!("{} {}", print_applications(ind, fun), print_parens(ind, arg))
+    Pure::App(..) => {
+      let mut args = Vec::new();
+      let mut fun = term;
+      while let Pure::App(_, f, a) = fun {
+        args.push(doc_parens(cfg, scope, a));
+        fun = f;
+      }
+      args.reverse();
+      spine(doc_parens(cfg, scope, fun), args)
     }
+    Pure::Ref(pos, nam, _) => text(cfg.at(nam.to_string(), *pos)),
+  }
+}
+
+pub fn doc(cfg: &Config, term: &Pure) -> Doc {
+  let mut free = Free::default();
+  free_names(term, &Scope::default(), 0, &mut free);
+  doc_in(cfg, &Scope::new(cfg, free), term)
+}
+
+pub fn print(cfg: &Config, term: &Pure) -> String {
+  doc(cfg, term).render(cfg.width, cfg.indent)
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::position::Pos;
+  use sp_std::boxed::Box;
+
+  fn var(nam: &str, idx: u64) -> Pure {
+    Pure::Var(Pos::None, Name::from(nam), idx)
+  }
+
+  fn lam(nam: &str, bod: Pure) -> Pure {
+    Pure::Lam(Pos::None, Name::from(nam), Box::new(bod))
+  }
+
+  #[test]
+  fn test_print_shadowing() {
+    // a binder is renamed only when it would capture a variable
+    assert_eq!(
+      print(&Config::default(), &lam("x", lam("x", var("x", 0)))),
+      "λ x x => x"
+    );
+    assert_eq!(
+      print(&Config::default(), &lam("x", lam("x", var("x", 1)))),
+      "λ x x₁ => x"
+    );
+    let t = lam(
+      "x",
+      lam(
+        "x",
+        lam(
+          "x₁",
+          Pure::App(Pos::None, Box::new(var("x", 2)), Box::new(var("x", 1))),
+        ),
+      ),
+    );
+    assert_eq!(print(&Config::default(), &t), "λ x x₁ x₁₁ => x x₁");
+    // nor may a binder capture a free variable or a reference
+    assert_eq!(print(&Config::default(), &lam("y", var("y", 1))), "λ y₁ => y");
+  }
+
+  #[test]
+  fn test_print_config() {
+    let t = lam(
+      "x",
+      Pure::App(Pos::None, Box::new(var("x", 0)), Box::new(var("y", 1))),
+    );
+    assert_eq!(format!("{}", t), "λ x => x y");
+    assert_eq!(format!("{:#}", t), "λ x => x^0 y^1");
+    let cfg = Config { ascii: true, ..Config::default() };
+    assert_eq!(print(&cfg, &t), "lambda x => x y");
+    assert_eq!(
+      print(&cfg, &lam("x", lam("x", var("x", 1)))),
+      "lambda x x1 => x"
+    );
+    assert_eq!(format!("{:5}", t), "λ x =>\n  x y");
   }
 }
@@ -0,0 +1,540 @@
+use crate::{
+  name::Name,
+  print::{
+    doc::{
+      concat,
+      group,
+      line,
+      nest,
+      spine,
+      text,
+      Doc,
+    },
+    kind,
+    pure,
+    typ_,
+    Config,
+    Free,
+    Scope,
+  },
+  term::Term,
+  uses::Uses,
+};
+
+use alloc::string::{
+  String,
+  ToString,
+};
+
+use sp_std::vec::Vec;
+
+fn is_atom(term: &Term) -> bool {
+  matches!(term, Term::Var(..) | Term::Ref(..))
+}
+
+/// Adds the names of the references, and the displayed names in `scope` of
+/// the term and type variables of index `dep` and `tdep` or more in `term`, to
+/// `names`
+pub fn free_names(
+  term: &Term,
+  scope: &Scope,
+  dep: u64,
+  tdep: u64,
+  names: &mut Free,
+) {
+  match term {
+    Term::Var(_, nam, idx) if *idx >= dep => {
+      names.terms.insert(Name::from(scope.term(nam, idx - dep)));
+    }
+    Term::Var(..) => (),
+    Term::Lam(_, _, _, dom, bod) => {
+      typ_::free_names(dom, scope, dep, tdep, names);
+      free_names(bod, scope, dep + 1, tdep, names);
+    }
+    Term::LamTy(_, _, dom, bod) => {
+      kind::free_names(dom, scope, dep, tdep, names);
+      free_names(bod, scope, dep, tdep + 1, names);
+    }
+    Term::App(_, _, fun, arg) => {
+      free_names(fun, scope, dep, tdep, names);
+      free_names(arg, scope, dep, tdep, names);
+    }
+    Term::Intersect(_, x, y, typ) => {
+      free_names(x, scope, dep, tdep, names);
+      free_names(y, scope, dep, tdep, names);
+      typ_::free_names(typ, scope, dep, tdep, names);
+    }
+    Term::AppTy(_, fun, arg) => {
+      free_names(fun, scope, dep, tdep, names);
+      typ_::free_names(arg, scope, dep, tdep, names);
+    }
+    Term::Let(_, _, _, x, bod) => {
+      free_names(x, scope, dep, tdep, names);
+      free_names(bod, scope, dep + 1, tdep, names);
+    }
+    Term::LetTy(_, _, k, typ, bod) => {
+      kind::free_names(k, scope, dep, tdep, names);
+      typ_::free_names(typ, scope, dep, tdep, names);
+      free_names(bod, scope, dep, tdep + 1, names);
+    }
+    Term::ProjectL(_, x) | Term::ProjectR(_, x) | Term::Sym(_, x) => {
+      free_names(x, scope, dep, tdep, names)
+    }
+    Term::Refl(_, x, y) => {
+      pure::free_names(x, scope, dep, names);
+      pure::free_names(y, scope, dep, names);
+    }
+    Term::Absurd(_, typ, x) => {
+      typ_::free_names(typ, scope, dep, tdep, names);
+      free_names(x, scope, dep, tdep, names);
+    }
+    Term::Rewrite(_, x, typ, y) => {
+      free_names(x, scope, dep, tdep, names);
+      typ_::free_names(typ, scope, dep, tdep, names);
+      free_names(y, scope, dep, tdep, names);
+    }
+    Term::Cast(_, x, y, z) => {
+      free_names(x, scope, dep, tdep, names);
+      free_names(y, scope, dep, tdep, names);
+      pure::free_names(z, scope, dep, names);
+    }
+    Term::Ref(_, nam, _) => {
+      names.terms.insert(nam.clone());
+    }
+  }
+}
+
+fn doc_parens(cfg: &Config, scope: &Scope, term: &Term) -> Doc {
+  if is_atom(term) {
+    doc_in(cfg, scope, term)
+  }
+  else {
+    concat(vec![text("("), doc_in(cfg, scope, term), text(")")])
+  }
+}
+
+pub fn doc(cfg: &Config, term: &Term) -> Doc {
+  let mut free = Free::default();
+  free_names(term, &Scope::default(), 0, 0, &mut free);
+  doc_in(cfg, &Scope { apart: true, ..Scope::new(cfg, free) }, term)
+}
+
+pub fn print(cfg: &Config, term: &Term) -> String {
+  doc(cfg, term).render(cfg.width, cfg.indent)
+}
+
+/// Lays out `term`, whose free variables are bound in `scope`
+pub fn doc_in(cfg: &Config, scope: &Scope, term: &Term) -> Doc {
+  match term {
+    Term::Var(pos, nam, index) => {
+      text(cfg.var(&scope.term(nam, *index), *index, *pos))
+    }
+    Term::Lam(..) | Term::LamTy(..) => {
+      let mut binders = Vec::new();
+      let mut scope = scope.clone();
+      let mut bod = term;
+      loop {
+        match bod {
+          Term::Lam(_, uses, nam, dom, b) => {
+            let dom = typ_::doc_in(cfg, &scope, dom);
+            let (nam, s) =
+              scope.bind_term(nam, |names| free_names(b, &scope, 1, 0, names));
+            binders.push(typ_::doc_binder(cfg.binder_uses(*uses), &nam, dom));
+            scope = s;
+            bod = b;
+          }
+          Term::LamTy(_, nam, dom, b) => {
+            let dom = kind::doc_in(cfg, &scope, dom);
+            let (nam, s) =
+              scope.bind_type(nam, |names| free_names(b, &scope, 0, 1, names));
+            binders.push(typ_::doc_binder("", &nam, dom));
+            scope = s;
+            bod = b;
+          }
+          _ => break,
+        }
+      }
+      typ_::doc_binders(
+        cfg.token("λ", "lambda"),
+        binders,
+        " =>",
+        doc_in(cfg, &scope, bod),
+      )
+    }
+    Term::App(..) | Term::AppTy(..) => {
+      let mut args = Vec::new();
+      let mut fun = term;
+      loop {
+        match fun {
+          Term::App(_, uses, f, a) => {
+            let uses = match uses {
+              Uses::Many => "",
+              _ => cfg.uses(*uses),
+            };
+            args.push(concat(vec![text(uses), doc_parens(cfg, scope, a)]));
+            fun = f;
+          }
+          Term::AppTy(_, f, a) => {
+            args.push(typ_::doc_parens(cfg, scope, a));
+            fun = f;
+          }
+          _ => break,
+        }
+      }
+      args.reverse();
+      spine(doc_parens(cfg, scope, fun), args)
+    }
+    Term::Intersect(_, x, y, typ) => spine(
+      text("intersect"),
+      vec![
+        doc_parens(cfg, scope, x),
+        doc_parens(cfg, scope, y),
+        typ_::doc_parens(cfg, scope, typ),
+      ],
+    ),
+    Term::Let(_, uses, nam, x, bod) => {
+      let x = doc_in(cfg, scope, x);
+      let (nam, scope) =
+        scope.bind_term(nam, |names| free_names(bod, scope, 1, 0, names));
+      let head = format!("let {}{} =", cfg.binder_uses(*uses), nam);
+      doc_let(text(head), x, doc_in(cfg, &scope, bod))
+    }
+    Term::LetTy(_, nam, k, typ, bod) => {
+      let k = kind::doc_in(cfg, scope, k);
+      let typ = typ_::doc_in(cfg, scope, typ);
+      let (nam, scope) =
+        scope.bind_type(nam, |names| free_names(bod, scope, 0, 1, names));
+      let head = concat(vec![text(format!("let {}: ", nam)), k, text(" =")]);
+      doc_let(head, typ, doc_in(cfg, &scope, bod))
+    }
+    Term::ProjectL(_, x) => {
+      spine(text("projectL"), vec![doc_parens(cfg, scope, x)])
+    }
+    Term::ProjectR(_, x) => {
+      spine(text("projectR"), vec![doc_parens(cfg, scope, x)])
+    }
+    Term::Refl(_, x, y) => spine(
+      text("refl"),
+      vec![pure::doc_parens(cfg, scope, x), pure::doc_parens(cfg, scope, y)],
+    ),
+    Term::Sym(_, x) => spine(text("sym"), vec![doc_parens(cfg, scope, x)]),
+    Term::Absurd(_, typ, x) => spine(
+      text("absurd"),
+      vec![typ_::doc_parens(cfg, scope, typ), doc_parens(cfg, scope, x)],
+    ),
+    Term::Rewrite(_, x, typ, y) => spine(
+      text("rewrite"),
+      vec![
+        doc_parens(cfg, scope, x),
+        typ_::doc_parens(cfg, scope, typ),
+        doc_parens(cfg, scope, y),
+      ],
+    ),
+    Term::Cast(_, x, y, z) => spine(
+      text("cast"),
+      vec![
+        doc_parens(cfg, scope, x),
+        doc_parens(cfg, scope, y),
+        pure::doc_parens(cfg, scope, z),
+      ],
+    ),
+    Term::Ref(pos, nam, _) => text(cfg.at(nam.to_string(), *pos)),
+  }
+}
+
+/// `let x = t in b`, with `t` indented on its own line when it does not fit,
+/// and `b` on the line after `in` so that a chain of lets lines up
+fn doc_let(head: Doc, x: Doc, bod: Doc) -> Doc {
+  let def = group(concat(vec![
+    head,
+    nest(1, concat(vec![line(), x])),
+    line(),
+    text("in"),
+  ]));
+  group(concat(vec![def, line(), bod]))
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    gen::tests::gen_range,
+    parse::{
+      span::Span,
+      term::parse_term_telescope,
+      util::{
+        input_cid,
+        Ctx,
+      },
+    },
+    position::Pos,
+    print::{
+      kind::tests::arbitrary_scoped_kind,
+      typ_::tests::{
+        arbitrary_binder,
+        arbitrary_scoped_pure,
+        arbitrary_scoped_type,
+        arbitrary_type_binder,
+        arbitrary_var,
+      },
+    },
+    typ_::tests::arbitrary_uses,
+  };
+  use quickcheck::{
+    Arbitrary,
+    Gen,
+  };
+  use sp_std::{
+    boxed::Box,
+    rc::Rc,
+  };
+
+  fn bind(ctx: &Ctx, nam: &Name) -> Ctx {
+    let mut ctx = ctx.clone();
+    ctx.push_front(nam.clone());
+    ctx
+  }
+
+  /// A term of at most `depth` nested constructors whose term and type
+  /// variables are in `ctx` and `tctx`. It need not be well-typed.
+  pub fn arbitrary_scoped_term(
+    g: &mut Gen,
+    depth: usize,
+    ctx: &Ctx,
+    tctx: &Ctx,
+    collide: bool,
+  ) -> Term {
+    let pos = Pos::None;
+    let term = |g: &mut Gen, ctx: &Ctx, tctx: &Ctx| {
+      Box::new(arbitrary_scoped_term(
+        g,
+        depth.saturating_sub(1),
+        ctx,
+        tctx,
+        collide,
+      ))
+    };
+    let typ = |g: &mut Gen| {
+      Box::new(arbitrary_scoped_type(
+        g,
+        depth.saturating_sub(1),
+        ctx,
+        tctx,
+        collide,
+      ))
+    };
+    let pure = |g: &mut Gen| Box::new(arbitrary_scoped_pure(g, ctx, collide));
+    let case = match (depth, ctx.is_empty()) {
+      (0, true) => 1,
+      (0, false) => 0,
+      (_, true) => gen_range(g, 1..16),
+      (_, false) => gen_range(g, 0..16),
+    };
+    match case {
+      0 => {
+        let (nam, idx) = arbitrary_var(g, ctx, collide);
+        Term::Var(pos, nam, idx)
+      }
+      1 => {
+        let nam = arbitrary_binder(g, collide);
+        let dom = typ(g);
+        Term::Lam(
+          pos,
+          arbitrary_uses(g),
+          nam.clone(),
+          dom,
+          term(g, &bind(ctx, &nam), tctx),
+        )
+      }
+      2 => {
+        let nam = arbitrary_type_binder(g, collide);
+        let dom = arbitrary_scoped_kind(g, depth - 1, ctx, tctx, collide);
+        Term::LamTy(
+          pos,
+          nam.clone(),
+          Box::new(dom),
+          term(g, ctx, &bind(tctx, &nam)),
+        )
+      }
+      3 => Term::App(
+        pos,
+        arbitrary_uses(g),
+        term(g, ctx, tctx),
+        term(g, ctx, tctx),
+      ),
+      4 => Term::AppTy(pos, term(g, ctx, tctx), typ(g)),
+      5 => Term::Intersect(pos, term(g, ctx, tctx), term(g, ctx, tctx), typ(g)),
+      6 => {
+        let nam = arbitrary_binder(g, collide);
+        let x = term(g, ctx, tctx);
+        Term::Let(
+          pos,
+          arbitrary_uses(g),
+          nam.clone(),
+          x,
+          term(g, &bind(ctx, &nam), tctx),
+        )
+      }
+      7 => {
+        let nam = arbitrary_type_binder(g, collide);
+        let k = arbitrary_scoped_kind(g, depth - 1, ctx, tctx, collide);
+        Term::LetTy(
+          pos,
+          nam.clone(),
+          Box::new(k),
+          typ(g),
+          term(g, ctx, &bind(tctx, &nam)),
+        )
+      }
+      8 => Term::ProjectL(pos, term(g, ctx, tctx)),
+      9 => Term::ProjectR(pos, term(g, ctx, tctx)),
+      10 => Term::Refl(pos, pure(g), pure(g)),
+      11 => Term::Sym(pos, term(g, ctx, tctx)),
+      12 => Term::Absurd(pos, typ(g), term(g, ctx, tctx)),
+      13 => Term::Rewrite(
+        pos,
+        term(g, ctx, tctx),
+        Box::new(arbitrary_scoped_type(g, depth - 1, ctx, tctx, collide)),
+        term(g, ctx, tctx),
+      ),
+      14 => Term::Cast(pos, term(g, ctx, tctx), term(g, ctx, tctx), pure(g)),
+      _ => Term::App(pos, Uses::Many, term(g, ctx, tctx), term(g, ctx, tctx)),
+    }
+  }
+
+  /// A closed term that round-trips through the printer and the parser
+  #[derive(Debug, Clone)]
+  pub struct Scoped(pub Term);
+
+  impl Arbitrary for Scoped {
+    fn arbitrary(g: &mut Gen) -> Self {
+      Scoped(arbitrary_scoped_term(g, 4, &Ctx::new(), &Ctx::new(), false))
+    }
+  }
+
+  /// A closed term whose binders shadow each other, and whose variables may
+  /// refer past a binder of the same name
+  #[derive(Debug, Clone)]
+  pub struct Colliding(pub Term);
+
+  impl Arbitrary for Colliding {
+    fn arbitrary(g: &mut Gen) -> Self {
+      Colliding(arbitrary_scoped_term(g, 4, &Ctx::new(), &Ctx::new(), true))
+    }
+  }
+
+  fn parse(i: &str) -> Term {
+    parse_term_telescope(input_cid(i), Rc::default(), Ctx::new(), Ctx::new())(
+      Span::new(i),
+    )
+    .unwrap_or_else(|e| panic!("{}: {}", i, e))
+    .1
+  }
+
+  #[test]
+  fn test_print_term() {
+    for i in &[
+      "λ (A: Type) (⁰x: A) => x",
+      "λ (A: Type) (f: ∀ (¹x: A) -> A) (x: A) => f ¹(f ¹x)",
+      "λ (A: Type) (x: A) => let ˚y = x in (λ (z: A) => z) y",
+      "λ (A: Type) (x: A) => (λ (B: Type) (y: B) => y) A x",
+      "λ (A: Type) (x: A) => let B: Type = A in intersect x x (ι (y: B) -> B)",
+      "λ (A: Type) (e: {λ x => x ≃ λ y => y}) => cast (sym e) (refl (λ x => \
+       x) e) e",
+    ] {
+      assert_eq!(print(&Config::default(), &parse(i)), *i);
+    }
+    // a name bound as both a term and a type variable is read as the term
+    let t = parse("λ (A: Type) (f: ∀ (a: A) -> A) (A: A) => f A");
+    assert_eq!(
+      print(&Config::default(), &t),
+      "λ (A: Type) (f: ∀ (a: A) -> A) (A: A) => f A"
+    );
+    // so a term binder is renamed if its body uses a type of the same name
+    let mut t =
+      parse("λ (A: Type) (f: ∀ (B: Type) (b: B) -> B) (a: A) => f A a");
+    if let Term::LamTy(_, _, _, f) = &mut t {
+      if let Term::Lam(_, _, _, _, a) = &mut **f {
+        if let Term::Lam(_, _, nam, ..) = &mut **a {
+          *nam = Name::from("A");
+        }
+      }
+    }
+    assert_eq!(
+      print(&Config::default(), &t),
+      "λ (A: Type) (f: ∀ (B: Type) (b: B) -> B) (A₁: A) => f A A₁"
+    );
+    let t = parse(
+      "λ (A: Type) (x: A) => let y = x in let z = (λ (w: A) => w) y in z",
+    );
+    assert_eq!(
+      print(&Config { width: 30, ..Config::default() }, &t),
+      "λ (A: Type) (x: A) =>\n  let y = x in\n  let z = (λ (w: A) => w) y \
+       in\n  z"
+    );
+    let t = parse("λ (A: Type) (⁰x: A) (f: ∀ (˚y: A) -> A) => f ¹x");
+    assert_eq!(
+      print(&Config { ascii: true, ..Config::default() }, &t),
+      "lambda (A: Type) ({0}x: A) (f: forall ({~}y: A) -> A) => f {1}x"
+    );
+    assert_eq!(
+      print(&Config { uses: false, ..Config::default() }, &t),
+      "λ (A: Type) (x: A) (f: ∀ (y: A) -> A) => f x"
+    );
+  }
+
+  fn parses_back(x: &Term, i: &str, eq: fn(&Term, &Term) -> bool) -> bool {
+    match parse_term_telescope(
+      input_cid(i),
+      Rc::default(),
+      Ctx::new(),
+      Ctx::new(),
+    )(Span::new(i))
+    {
+      Ok((rest, y)) => {
+        if rest.fragment().is_empty() && eq(x, &y) {
+          true
+        }
+        else {
+          println!("{:?}", x);
+          println!("{}", i);
+          println!("{:?}", y);
+          println!("{}", y);
+          false
+        }
+      }
+      Err(e) => {
+        println!("{}", i);
+        println!("{}", e);
+        false
+      }
+    }
+  }
+
+  #[quickcheck]
+  fn test_term_parse_print(x: Scoped) -> bool {
+    parses_back(&x.0, &format!("{}", x.0), Term::syntactic_eq)
+  }
+
+  #[quickcheck]
+  fn test_term_parse_layout(x: Scoped) -> bool {
+    // so narrow that most groups break
+    parses_back(
+      &x.0,
+      &doc(&Config::default(), &x.0).render(8, 2),
+      Term::syntactic_eq,
+    )
+  }
+
+  #[quickcheck]
+  fn test_term_print_colliding(x: Colliding) -> bool {
+    // the names of shadowing binders change, but not the de Bruijn indices
+    parses_back(&x.0, &format!("{}", x.0), Term::alpha_eq)
+  }
+
+  #[quickcheck]
+  fn test_term_parse_ascii(x: Colliding) -> bool {
+    let cfg = Config { ascii: true, ..Config::default() };
+    parses_back(&x.0, &print(&cfg, &x.0), Term::alpha_eq)
+  }
+}
@@ -0,0 +1,495 @@
+use crate::{
+  name::Name,
+  print::{
+    doc::{
+      concat,
+      group,
+      hang,
+      line,
+      nest,
+      spine,
+      text,
+      Doc,
+    },
+    kind,
+    pure,
+    Config,
+    Free,
+    Scope,
+  },
+  term::Tm,
+  typ_::BaseType,
+};
+
+use alloc::string::{
+  String,
+  ToString,
+};
+
+use sp_std::vec::Vec;
+
+/// Variables and references are the only types that never need parentheses.
+/// An equality type `{x ≃ y}` does as an argument, since `{` ends an
+/// application telescope.
+fn is_atom<const T: Tm>(typ: &BaseType<T>) -> bool {
+  matches!(typ, BaseType::Var(..) | BaseType::Ref(..))
+}
+
+/// Adds the names of the references, and the displayed names in `scope` of
+/// the term and type variables of index `dep` and `tdep` or more in `typ`, to
+/// `names`
+pub fn free_names<const T: Tm>(
+  typ: &BaseType<T>,
+  scope: &Scope,
+  dep: u64,
+  tdep: u64,
+  names: &mut Free,
+) {
+  match typ {
+    BaseType::Var(_, nam, idx) if *idx >= tdep => {
+      names.types.insert(Name::from(scope.typ(nam, idx - tdep)));
+    }
+    BaseType::Var(..) => (),
+    BaseType::Lam(_, _, dom, bod)
+    | BaseType::Pi(_, _, _, dom, bod)
+    | BaseType::Iota(_, _, dom, bod) => {
+      free_names(dom, scope, dep, tdep, names);
+      free_names(bod, scope, dep + 1, tdep, names);
+    }
+    BaseType::LamTy(_, _, dom, bod) | BaseType::PiTy(_, _, dom, bod) => {
+      kind::free_names(dom, scope, dep, tdep, names);
+      free_names(bod, scope, dep, tdep + 1, names);
+    }
+    BaseType::Eql(_, a, b) => {
+      pure::free_names(a, scope, dep, names);
+      pure::free_names(b, scope, dep, names);
+    }
+    BaseType::AppTy(_, fun, arg) => {
+      free_names(fun, scope, dep, tdep, names);
+      free_names(arg, scope, dep, tdep, names);
+    }
+    BaseType::Ref(_, nam, _) => {
+      names.types.insert(nam.clone());
+    }
+  }
+}
+
+pub fn doc_parens<const T: Tm>(
+  cfg: &Config,
+  scope: &Scope,
+  typ: &BaseType<T>,
+) -> Doc {
+  if is_atom(typ) {
+    doc_in(cfg, scope, typ)
+  }
+  else {
+    concat(vec![text("("), doc_in(cfg, scope, typ), text(")")])
+  }
+}
+
+/// A binder `(x: A)`
+pub fn doc_binder(uses: &str, nam: &str, dom: Doc) -> Doc {
+  concat(vec![text(format!("({}{}: ", uses, nam)), dom, text(")")])
+}
+
+/// `λ`, `∀` or `Π` with its binders, which break onto their own lines only
+/// when they do not fit on one, then `sep` and the body
+pub fn doc_binders(head: &str, binders: Vec<Doc>, sep: &str, bod: Doc) -> Doc {
+  let head = spine(text(head), binders);
+  hang(concat(vec![head, text(sep)]), bod)
+}
+
+/// Lays out `typ`, whose free variables are bound in `scope`
+pub fn doc_in<const T: Tm>(
+  cfg: &Config,
+  scope: &Scope,
+  typ: &BaseType<T>,
+) -> Doc {
+  match typ {
+    BaseType::Var(pos, nam, index) => {
+      text(cfg.var(&scope.typ(nam, *index), *index, *pos))
+    }
+    BaseType::Lam(..) | BaseType::LamTy(..) => {
+      let mut binders = Vec::new();
+      let mut scope = scope.clone();
+      let mut bod = typ;
+      loop {
+        match bod {
+          BaseType::Lam(_, nam, dom, b) => {
+            let dom = doc_in(cfg, &scope, dom);
+            let (nam, s) =
+              scope.bind_term(nam, |names| free_names(b, &scope, 1, 0, names));
+            binders.push(doc_binder("", &nam, dom));
+            scope = s;
+            bod = b;
+          }
+          BaseType::LamTy(_, nam, dom, b) => {
+            let dom = kind::doc_in(cfg, &scope, dom);
+            let (nam, s) =
+              scope.bind_type(nam, |names| free_names(b, &scope, 0, 1, names));
+            binders.push(doc_binder("", &nam, dom));
+            scope = s;
+            bod = b;
+          }
+          _ => break,
+        }
+      }
+      doc_binders(
+        cfg.token("λ", "lambda"),
+        binders,
+        " =>",
+        doc_in(cfg, &scope, bod),
+      )
+    }
+    BaseType::Pi(..) | BaseType::PiTy(..) => {
+      let mut binders = Vec::new();
+      let mut scope = scope.clone();
+      let mut bod = typ;
+      loop {
+        match bod {
+          BaseType::Pi(_, uses, nam, dom, b) => {
+            let dom = doc_in(cfg, &scope, dom);
+            let (nam, s) =
+              scope.bind_term(nam, |names| free_names(b, &scope, 1, 0, names));
+            binders.push(doc_binder(cfg.binder_uses(*uses), &nam, dom));
+            scope = s;
+            bod = b;
+          }
+          BaseType::PiTy(_, nam, dom, b) => {
+            let dom = kind::doc_in(cfg, &scope, dom);
+            let (nam, s) =
+              scope.bind_type(nam, |names| free_names(b, &scope, 0, 1, names));
+            binders.push(doc_binder("", &nam, dom));
+            scope = s;
+            bod = b;
+          }
+          _ => break,
+        }
+      }
+      doc_binders(
+        cfg.token("∀", "forall"),
+        binders,
+        " ->",
+        doc_in(cfg, &scope, bod),
+      )
+    }
+    BaseType::Iota(_, nam, dom, bod) => {
+      let dom = doc_in(cfg, scope, dom);
+      let (nam, bod_scope) =
+        scope.bind_term(nam, |names| free_names(bod, scope, 1, 0, names));
+      let binder = doc_binder("", &nam, dom);
+      doc_binders(
+        cfg.token("ι", "iota"),
+        vec![binder],
+        " ->",
+        doc_in(cfg, &bod_scope, bod),
+      )
+    }
+    BaseType::Eql(_, a, b) => group(concat(vec![
+      text("{"),
+      pure::doc_in(cfg, scope, a),
+      text(format!(" {}", cfg.token("≃", "~="))),
+      nest(1, concat(vec![line(), pure::doc_in(cfg, scope, b)])),
+      text("}"),
+    ])),
+    BaseType::AppTy(..) => {
+      let mut args = Vec::new();
+      let mut fun = typ;
+      while let BaseType::AppTy(_, f, a) = fun {
+        args.push(doc_parens(cfg, scope, a));
+        fun = f;
+      }
+      args.reverse();
+      spine(doc_parens(cfg, scope, fun), args)
+    }
+    BaseType::Ref(pos, nam, _) => text(cfg.at(nam.to_string(), *pos)),
+  }
+}
+
+pub fn doc<const T: Tm>(cfg: &Config, typ: &BaseType<T>) -> Doc {
+  let mut free = Free::default();
+  free_names(typ, &Scope::default(), 0, 0, &mut free);
+  doc_in(cfg, &Scope::new(cfg, free), typ)
+}
+
+pub fn print<const T: Tm>(cfg: &Config, typ: &BaseType<T>) -> String {
+  doc(cfg, typ).render(cfg.width, cfg.indent)
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    gen::{
+      pure::tests::arbitrary_pure,
+      tests::{
+        arbitrary_name,
+        gen_range,
+      },
+    },
+    kind::BaseKind,
+    name::Name,
+    parse::{
+      span::Span,
+      typ_::parse_type_telescope,
+      util::{
+        input_cid,
+        Ctx,
+      },
+    },
+    position::Pos,
+    print::kind::tests::arbitrary_scoped_kind,
+    pure::Pure,
+    typ_::{
+      tests::arbitrary_uses,
+      Type,
+    },
+  };
+  use quickcheck::{
+    Arbitrary,
+    Gen,
+  };
+  use sp_std::{
+    boxed::Box,
+    mem,
+    rc::Rc,
+  };
+
+  /// A binder name. If `collide`, it is one of two, so that binders often
+  /// shadow each other.
+  pub fn arbitrary_binder(g: &mut Gen, collide: bool) -> Name {
+    if collide {
+      Name::from(if bool::arbitrary(g) { "x" } else { "y" })
+    }
+    else {
+      arbitrary_name(g)
+    }
+  }
+
+  /// A type binder name. Unless `collide`, it differs from every term binder
+  /// name, which the parser would read a type argument of the same name as.
+  pub fn arbitrary_type_binder(g: &mut Gen, collide: bool) -> Name {
+    if collide {
+      arbitrary_binder(g, collide)
+    }
+    else {
+      Name::from(format!("T{}", arbitrary_name(g)))
+    }
+  }
+
+  /// A variable of `ctx` and its index. Unless `collide`, it is the innermost
+  /// variable of its name, which the parser resolves the name to.
+  pub fn arbitrary_var(g: &mut Gen, ctx: &Ctx, collide: bool) -> (Name, u64) {
+    let idx = gen_range(g, 0..ctx.len());
+    let nam = ctx[idx].clone();
+    if collide {
+      (nam, idx as u64)
+    }
+    else {
+      let idx = ctx.iter().position(|x| *x == nam).unwrap();
+      (nam, idx as u64)
+    }
+  }
+
+  /// A pure term whose free variables are in `ctx`
+  pub fn arbitrary_scoped_pure(g: &mut Gen, ctx: &Ctx, collide: bool) -> Pure {
+    if !collide {
+      let gen_pure = arbitrary_pure(g, ctx.clone());
+      return unsafe {
+        mem::transmute::<crate::gen::pure::Pure, Pure>(gen_pure)
+      };
+    }
+    match gen_range(g, 0..if ctx.is_empty() { 1 } else { 4 }) {
+      0 => {
+        let nam = arbitrary_binder(g, collide);
+        let mut ctx = ctx.clone();
+        ctx.push_front(nam.clone());
+        let bod = arbitrary_scoped_pure(g, &ctx, collide);
+        Pure::Lam(Pos::None, nam, Box::new(bod))
+      }
+      1 => Pure::App(
+        Pos::None,
+        Box::new(arbitrary_scoped_pure(g, ctx, collide)),
+        Box::new(arbitrary_scoped_pure(g, ctx, collide)),
+      ),
+      _ => {
+        let (nam, idx) = arbitrary_var(g, ctx, collide);
+        Pure::Var(Pos::None, nam, idx)
+      }
+    }
+  }
+
+  /// A type of at most `depth` nested constructors whose term and type
+  /// variables are in `ctx` and `tctx`, and which therefore prints to syntax
+  /// that parses back to it
+  pub fn arbitrary_scoped_type<const T: Tm>(
+    g: &mut Gen,
+    depth: usize,
+    ctx: &Ctx,
+    tctx: &Ctx,
+    collide: bool,
+  ) -> BaseType<T> {
+    let pos = Pos::None;
+    let bind = |ctx: &Ctx, nam: &Name| {
+      let mut ctx = ctx.clone();
+      ctx.push_front(nam.clone());
+      ctx
+    };
+    let case = match (depth, tctx.is_empty()) {
+      (0, true) => 1,
+      (0, false) => gen_range(g, 0..2),
+      (_, true) => gen_range(g, 1..8),
+      (_, false) => gen_range(g, 0..8),
+    };
+    let depth = depth.saturating_sub(1);
+    match case {
+      0 => {
+        let (nam, idx) = arbitrary_var(g, tctx, collide);
+        BaseType::Var(pos, nam, idx)
+      }
+      1 => BaseType::Eql(
+        pos,
+        Box::new(arbitrary_scoped_pure(g, ctx, collide)),
+        Box::new(arbitrary_scoped_pure(g, ctx, collide)),
+      ),
+      2 => {
+        let nam = arbitrary_binder(g, collide);
+        let dom = arbitrary_scoped_type(g, depth, ctx, tctx, collide);
+        let bod =
+          arbitrary_scoped_type(g, depth, &bind(ctx, &nam), tctx, collide);
+        BaseType::Lam(pos, nam, Box::new(dom), Box::new(bod))
+      }
+      3 => {
+        let nam = arbitrary_type_binder(g, collide);
+        let dom = arbitrary_scoped_kind(g, depth, ctx, tctx, collide);
+        let bod =
+          arbitrary_scoped_type(g, depth, ctx, &bind(tctx, &nam), collide);
+        BaseType::LamTy(pos, nam, Box::new(dom), Box::new(bod))
+      }
+      4 => {
+        let nam = arbitrary_binder(g, collide);
+        let dom = arbitrary_scoped_type(g, depth, ctx, tctx, collide);
+        let bod =
+          arbitrary_scoped_type(g, depth, &bind(ctx, &nam), tctx, collide);
+        BaseType::Pi(pos, arbitrary_uses(g), nam, Box::new(dom), Box::new(bod))
+      }
+      5 => {
+        let nam = arbitrary_type_binder(g, collide);
+        let dom = arbitrary_scoped_kind(g, depth, ctx, tctx, collide);
+        let bod =
+          arbitrary_scoped_type(g, depth, ctx, &bind(tctx, &nam), collide);
+        BaseType::PiTy(pos, nam, Box::new(dom), Box::new(bod))
+      }
+      6 => {
+        let nam = arbitrary_binder(g, collide);
+        let dom = arbitrary_scoped_type(g, depth, ctx, tctx, collide);
+        let bod =
+          arbitrary_scoped_type(g, depth, &bind(ctx, &nam), tctx, collide);
+        BaseType::Iota(pos, nam, Box::new(dom), Box::new(bod))
+      }
+      _ => BaseType::AppTy(
+        pos,
+        Box::new(arbitrary_scoped_type(g, depth, ctx, tctx, collide)),
+        Box::new(arbitrary_scoped_type(g, depth, ctx, tctx, collide)),
+      ),
+    }
+  }
+
+  /// A closed type that round-trips through the printer and the parser
+  #[derive(Debug, Clone)]
+  pub struct Scoped(pub Type);
+
+  impl Arbitrary for Scoped {
+    fn arbitrary(g: &mut Gen) -> Self {
+      Scoped(arbitrary_scoped_type(g, 4, &Ctx::new(), &Ctx::new(), false))
+    }
+  }
+
+  /// A closed type whose binders shadow each other, and whose variables may
+  /// refer past a binder of the same name
+  #[derive(Debug, Clone)]
+  pub struct Colliding(pub Type);
+
+  impl Arbitrary for Colliding {
+    fn arbitrary(g: &mut Gen) -> Self {
+      Colliding(arbitrary_scoped_type(g, 4, &Ctx::new(), &Ctx::new(), true))
+    }
+  }
+
+  #[test]
+  fn test_print_type() {
+    fn parse(i: &str) -> Type {
+      parse_type_telescope(input_cid(i), Rc::default(), Ctx::new(), Ctx::new())(
+        Span::new(i),
+      )
+      .unwrap_or_else(|e| panic!("{}: {}", i, e))
+      .1
+    }
+    for i in &[
+      "∀ (A: Type) (¹x: A) -> A",
+      "∀ (F: Π Type -> Type) (A: Type) -> F A",
+      "λ (F: Π Type Type -> Type) (A: Type) => F A (F A A)",
+      "∀ (A: Type) (P: Π A -> Type) (⁰x: A) -> A",
+      "ι (x: ∀ (A: Type) -> A) -> {x ≃ λ y => y}",
+      "∀ (A: Type) (a: A) -> (λ (X: Type) => X) ({a ≃ a})",
+    ] {
+      assert_eq!(print(&Config::default(), &parse(i)), *i);
+    }
+    let typ: Type = BaseType::LamTy(
+      Pos::None,
+      Name::from(""),
+      Box::new(BaseKind::Type(Pos::None)),
+      Box::new(BaseType::Var(Pos::None, Name::from(""), 0)),
+    );
+    assert_eq!(typ.to_string(), "λ (_: Type) => _");
+  }
+
+  #[quickcheck]
+  fn test_type_parse_print(x: Scoped) -> bool {
+    let i = format!("{}", x.0);
+    match parse_type_telescope(
+      input_cid(&i),
+      Rc::default(),
+      Ctx::new(),
+      Ctx::new(),
+    )(Span::new(&i))
+    {
+      Ok((rest, y)) => {
+        if rest.fragment().is_empty() && x.0.syntactic_eq(&y) {
+          true
+        }
+        else {
+          println!("{:?}", x.0);
+          println!("{}", x.0);
+          println!("{:?}", y);
+          println!("{}", y);
+          false
+        }
+      }
+      Err(e) => {
+        println!("{}", x.0);
+        println!("{}", e);
+        false
+      }
+    }
+  }
+
+  #[quickcheck]
+  fn test_type_print_colliding(x: Colliding) -> bool {
+    let i = format!("{}", x.0);
+    match parse_type_telescope(
+      input_cid(&i),
+      Rc::default(),
+      Ctx::new(),
+      Ctx::new(),
+    )(Span::new(&i))
+    {
+      Ok((rest, y)) => rest.fragment().is_empty() && x.0.alpha_eq(&y),
+      Err(e) => {
+        println!("{}", i);
+        println!("{}", e);
+        false
+      }
+    }
+  }
+}
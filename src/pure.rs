@@ -1,12 +1,35 @@
+pub mod eval;
+
 use crate::{
+  anon::{
+    bind,
+    ctor,
+    Anon,
+  },
+  embed_error::EmbedError,
+  hash::ipld_cid,
+  ipld_error::IpldError,
+  meta::Meta,
+  module::Defs,
   name::Name,
   position::Pos,
-  print::pure,
+  print::{
+    pure,
+    Config,
+  },
 };
 
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
+use sp_im::Vector;
+
 use sp_std::{
+  borrow::ToOwned,
   boxed::Box,
+  convert::TryInto,
   fmt,
+  vec::Vec,
 };
 
 /// pure terms in the untyped lambda calculus
@@ -18,48 +41,491 @@ pub enum Pure {
   Lam(Pos, Name, Box<Pure>),
   /// application: f x
   App(Pos, Box<Pure>, Box<Pure>),
+  /// reference to the erasure of a global definition: f
+  Ref(Pos, Name, Cid),
 }
 
-impl fmt::Debug for Pure {
-  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl Pure {
+  pub fn pos(&self) -> Pos {
     match self {
-      Self::Var(_, n, i) => fmt.debug_tuple("Var").field(&n).field(i).finish(),
-      Self::Lam(_, n, b) => fmt.debug_tuple("Lam").field(&n).field(&b).finish(),
-      Self::App(_, f, a) => fmt.debug_tuple("App").field(&f).field(&a).finish(),
+      Self::Var(pos, ..) => *pos,
+      Self::Lam(pos, ..) => *pos,
+      Self::App(pos, ..) => *pos,
+      Self::Ref(pos, ..) => *pos,
     }
   }
-}
 
-impl PartialEq for Pure {
-  fn eq(&self, other: &Self) -> bool {
+  pub fn to_ipld(&self) -> Ipld {
+    match self {
+      Self::Var(pos, n, i) => Ipld::List(vec![
+        Ipld::Integer(0),
+        pos.to_ipld(),
+        n.to_ipld(),
+        Ipld::Integer(*i as i128),
+      ]),
+      Self::Lam(pos, n, b) => Ipld::List(vec![
+        Ipld::Integer(1),
+        pos.to_ipld(),
+        n.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::App(pos, f, a) => Ipld::List(vec![
+        Ipld::Integer(2),
+        pos.to_ipld(),
+        f.to_ipld(),
+        a.to_ipld(),
+      ]),
+      Self::Ref(pos, n, cid) => Ipld::List(vec![
+        Ipld::Integer(3),
+        pos.to_ipld(),
+        n.to_ipld(),
+        Ipld::Link(*cid),
+      ]),
+    }
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [Ipld::Integer(tag), pos, args @ ..] => {
+          let pos = Pos::from_ipld(pos)?;
+          match (*tag, args) {
+            (0, [n, Ipld::Integer(i)]) => {
+              let i: u64 = (*i).try_into().map_err(IpldError::U64)?;
+              Ok(Self::Var(pos, Name::from_ipld(n)?, i))
+            }
+            (1, [n, b]) => Ok(Self::Lam(
+              pos,
+              Name::from_ipld(n)?,
+              Box::new(Self::from_ipld(b)?),
+            )),
+            (2, [f, a]) => Ok(Self::App(
+              pos,
+              Box::new(Self::from_ipld(f)?),
+              Box::new(Self::from_ipld(a)?),
+            )),
+            (3, [n, Ipld::Link(cid)]) => {
+              Ok(Self::Ref(pos, Name::from_ipld(n)?, *cid))
+            }
+            _ => Err(IpldError::Pure(ipld.to_owned())),
+          }
+        }
+        xs => Err(IpldError::Pure(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::Pure(xs.to_owned())),
+    }
+  }
+
+  /// Content address of the DAG-CBOR encoding of this term
+  pub fn cid(&self) -> Cid { ipld_cid(&self.to_ipld()) }
+
+  /// Splits into an anonymous tree and its metadata
+  pub fn embed(&self) -> (Anon, Meta) {
+    match self {
+      Self::Var(pos, nam, idx) => {
+        (Anon::Vari(*idx), Meta::Vari(*pos, nam.clone()))
+      }
+      Self::Lam(pos, n, a) => ctor("lam", *pos, vec![bind(n, a.embed())]),
+      Self::App(pos, a, b) => ctor("app", *pos, vec![a.embed(), b.embed()]),
+      Self::Ref(pos, nam, cid) => {
+        (Anon::Link(*cid), Meta::Link(*pos, nam.clone()))
+      }
+    }
+  }
+
+  /// Recombines an anonymous tree with its metadata
+  pub fn unembed(anon: &Anon, meta: &Meta) -> Result<Self, EmbedError> {
+    let err =
+      || EmbedError::Pure(Box::new(anon.clone()), Box::new(meta.clone()));
+    let pure = |a: &Anon, m: &Meta| Self::unembed(a, m).map(Box::new);
+    match (anon, meta) {
+      (Anon::Vari(idx), Meta::Vari(pos, nam)) => {
+        Ok(Self::Var(*pos, nam.clone(), *idx))
+      }
+      (Anon::Link(cid), Meta::Link(pos, nam)) => {
+        Ok(Self::Ref(*pos, nam.clone(), *cid))
+      }
+      (Anon::Ctor(tag, xs), Meta::Ctor(pos, ms)) => {
+        match (tag.as_str(), xs.as_slice(), ms.as_slice()) {
+          ("lam", [Anon::Bind(aa)], [Meta::Bind(n, am)]) => {
+            Ok(Self::Lam(*pos, n.clone(), pure(aa, am)?))
+          }
+          ("app", [aa, ba], [am, bm]) => {
+            Ok(Self::App(*pos, pure(aa, am)?, pure(ba, bm)?))
+          }
+          _ => Err(err()),
+        }
+      }
+      _ => Err(err()),
+    }
+  }
+
+  /// Shifts the free variables at or above `c` by `d`
+  pub fn shift(&self, d: i64, c: u64) -> Self {
+    match self {
+      Self::Var(pos, n, i) => {
+        let i = if *i >= c { (*i as i64 + d) as u64 } else { *i };
+        Self::Var(*pos, n.clone(), i)
+      }
+      Self::Lam(pos, n, b) => {
+        Self::Lam(*pos, n.clone(), Box::new(b.shift(d, c + 1)))
+      }
+      Self::App(pos, f, a) => {
+        Self::App(*pos, Box::new(f.shift(d, c)), Box::new(a.shift(d, c)))
+      }
+      Self::Ref(..) => self.clone(),
+    }
+  }
+
+  /// Substitutes `v` for the free variable `j`, removing its binder
+  pub fn subst(&self, j: u64, v: &Pure) -> Self {
+    fn go(p: &Pure, j: u64, v: &Pure, k: u64) -> Pure {
+      match p {
+        Pure::Var(pos, n, i) => {
+          if *i == j + k {
+            v.shift(k as i64, 0)
+          }
+          else if *i > j + k {
+            Pure::Var(*pos, n.clone(), i - 1)
+          }
+          else {
+            p.clone()
+          }
+        }
+        Pure::Lam(pos, n, b) => {
+          Pure::Lam(*pos, n.clone(), Box::new(go(b, j, v, k + 1)))
+        }
+        Pure::App(pos, f, a) => {
+          Pure::App(*pos, Box::new(go(f, j, v, k)), Box::new(go(a, j, v, k)))
+        }
+        Pure::Ref(..) => p.clone(),
+      }
+    }
+    go(self, j, v, 0)
+  }
+
+  /// Structural equality up to the names of binders and variables
+  pub fn alpha_eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Var(_, _, ia), Self::Var(_, _, ib)) => ia == ib,
+      (Self::Lam(_, _, ba), Self::Lam(_, _, bb)) => ba.alpha_eq(bb),
+      (Self::App(_, fa, aa), Self::App(_, fb, ab)) => {
+        fa.alpha_eq(fb) && aa.alpha_eq(ab)
+      }
+      (Self::Ref(_, _, ca), Self::Ref(_, _, cb)) => ca == cb,
+      _ => false,
+    }
+  }
+
+  /// Structural equality including names, ignoring only positions
+  pub fn syntactic_eq(&self, other: &Self) -> bool {
     match (self, other) {
       (Self::Var(_, na, ia), Self::Var(_, nb, ib)) => na == nb && ia == ib,
       (Self::Lam(_, na, ba), Self::Lam(_, nb, bb)) => na == nb && ba == bb,
       (Self::App(_, fa, aa), Self::App(_, fb, ab)) => fa == fb && aa == ab,
+      (Self::Ref(_, na, ca), Self::Ref(_, nb, cb)) => na == nb && ca == cb,
       _ => false,
     }
   }
 }
 
+impl fmt::Debug for Pure {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Var(_, n, i) => fmt.debug_tuple("Var").field(&n).field(i).finish(),
+      Self::Lam(_, n, b) => fmt.debug_tuple("Lam").field(&n).field(&b).finish(),
+      Self::App(_, f, a) => fmt.debug_tuple("App").field(&f).field(&a).finish(),
+      Self::Ref(_, n, c) => fmt.debug_tuple("Ref").field(&n).field(c).finish(),
+    }
+  }
+}
+
+impl PartialEq for Pure {
+  fn eq(&self, other: &Self) -> bool { self.syntactic_eq(other) }
+}
+
 impl fmt::Display for Pure {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    write!(f, "{}", pure::print(false, self))
+    write!(f, "{}", pure::print(&Config::from_formatter(f), self))
+  }
+}
+
+/// The outcome of a conversion check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvResult {
+  /// the terms are βη-equal
+  Equal,
+  /// the terms have distinct βη-normal forms
+  NotEqual,
+  /// the fuel ran out before the terms could be compared
+  Unknown,
+}
+
+/// Semantic values for normalization-by-evaluation. Variables are de Bruijn
+/// levels, so values can be moved under binders without shifting.
+#[derive(Clone)]
+enum Value {
+  Lam(Vector<Thunk>, Pure),
+  Neu(u64, Vector<Thunk>),
+  /// a folded reference applied to arguments
+  Ref(Cid, Vector<Thunk>),
+}
+
+/// Arguments are passed unevaluated, so evaluation is call-by-name and finds
+/// a normal form whenever one exists
+#[derive(Clone)]
+enum Thunk {
+  Val(Value),
+  Delay(Vector<Thunk>, Pure),
+}
+
+fn eval(env: &Vector<Thunk>, term: &Pure, fuel: &mut u64) -> Option<Value> {
+  let mut env = env.clone();
+  let mut term = term.clone();
+  // β-redexes and forced thunks in tail position loop instead of recursing,
+  // so a divergent term runs out of fuel rather than stack
+  loop {
+    match term {
+      Pure::Var(_, _, idx) => match env[idx as usize].clone() {
+        Thunk::Val(val) => return Some(val),
+        Thunk::Delay(clo, trm) => {
+          env = clo;
+          term = trm;
+        }
+      },
+      Pure::Lam(_, _, bod) => return Some(Value::Lam(env, *bod)),
+      Pure::Ref(_, _, cid) => return Some(Value::Ref(cid, Vector::new())),
+      Pure::App(_, fun, arg) => {
+        // a variable is passed on as is, so that arguments do not build up
+        // chains of thunks that each lead to the next
+        let arg = match *arg {
+          Pure::Var(_, _, idx) => env[idx as usize].clone(),
+          arg => Thunk::Delay(env.clone(), arg),
+        };
+        match eval(&env, &fun, fuel)? {
+          Value::Lam(mut clo, bod) => {
+            if *fuel == 0 {
+              return None;
+            }
+            *fuel -= 1;
+            clo.push_front(arg);
+            env = clo;
+            term = bod;
+          }
+          Value::Neu(lvl, mut args) => {
+            args.push_back(arg);
+            return Some(Value::Neu(lvl, args));
+          }
+          Value::Ref(cid, mut args) => {
+            args.push_back(arg);
+            return Some(Value::Ref(cid, args));
+          }
+        }
+      }
+    }
+  }
+}
+
+fn force(thunk: Thunk, fuel: &mut u64) -> Option<Value> {
+  match thunk {
+    Thunk::Val(val) => Some(val),
+    Thunk::Delay(env, trm) => eval(&env, &trm, fuel),
+  }
+}
+
+fn apply(fun: Value, arg: Thunk, fuel: &mut u64) -> Option<Value> {
+  match fun {
+    Value::Lam(mut env, bod) => {
+      if *fuel == 0 {
+        return None;
+      }
+      *fuel -= 1;
+      env.push_front(arg);
+      eval(&env, &bod, fuel)
+    }
+    Value::Neu(lvl, mut args) => {
+      args.push_back(arg);
+      Some(Value::Neu(lvl, args))
+    }
+    Value::Ref(cid, mut args) => {
+      args.push_back(arg);
+      Some(Value::Ref(cid, args))
+    }
+  }
+}
+
+/// Unfolds a reference to the erasure of its definition, applied to `args`
+fn delta(
+  defs: &Defs,
+  cid: &Cid,
+  args: Vector<Thunk>,
+  fuel: &mut u64,
+) -> Option<Value> {
+  if *fuel == 0 {
+    return None;
+  }
+  *fuel -= 1;
+  let mut val = eval(&Vector::new(), &defs.erased(cid)?, fuel)?;
+  for arg in args {
+    val = apply(val, arg, fuel)?;
+  }
+  Some(val)
+}
+
+/// How many comparisons of folded references may be nested before a
+/// reference is unfolded without first trying its arguments
+const MAX_NEST: u64 = 64;
+
+/// Compares two argument lists pointwise
+fn conv_spine(
+  defs: &Defs,
+  xs: Vector<Thunk>,
+  ys: Vector<Thunk>,
+  dep: u64,
+  fuel: &mut u64,
+  nest: u64,
+) -> Option<bool> {
+  if xs.len() != ys.len() {
+    return Some(false);
+  }
+  let mut work = Vec::new();
+  push_spine(&mut work, xs, ys, dep);
+  conv_work(defs, work, fuel, nest)
+}
+
+/// Queues the pairs of two argument lists of equal length, so that they are
+/// compared from left to right
+fn push_spine(
+  work: &mut Vec<(Thunk, Thunk, u64)>,
+  xs: Vector<Thunk>,
+  ys: Vector<Thunk>,
+  dep: u64,
+) {
+  for (x, y) in xs.into_iter().zip(ys).rev() {
+    work.push((x, y, dep));
+  }
+}
+
+/// Compares each pair of values in `work` at its binder depth, η-expanding a
+/// neutral term when it meets a lambda. References are unfolded only when
+/// they cannot be compared folded, and references to unknown definitions stay
+/// rigid. Pairs are taken from an explicit stack rather than the call stack,
+/// so a divergent comparison runs out of fuel rather than stack.
+fn conv_work(
+  defs: &Defs,
+  mut work: Vec<(Thunk, Thunk, u64)>,
+  fuel: &mut u64,
+  nest: u64,
+) -> Option<bool> {
+  while let Some((a, b, dep)) = work.pop() {
+    let a = force(a, fuel)?;
+    let b = force(b, fuel)?;
+    match (a, b) {
+      (Value::Neu(la, xs), Value::Neu(lb, ys)) => {
+        if la != lb || xs.len() != ys.len() {
+          return Some(false);
+        }
+        push_spine(&mut work, xs, ys, dep);
+      }
+      (Value::Ref(ca, xs), Value::Ref(cb, ys))
+        if ca == cb && !defs.contains(&ca) =>
+      {
+        if xs.len() != ys.len() {
+          return Some(false);
+        }
+        push_spine(&mut work, xs, ys, dep);
+      }
+      (Value::Ref(ca, xs), Value::Ref(cb, ys))
+        if ca == cb && nest < MAX_NEST =>
+      {
+        let ok = conv_spine(defs, xs.clone(), ys.clone(), dep, fuel, nest + 1)?;
+        if !ok {
+          let a = delta(defs, &ca, xs, fuel)?;
+          work.push((Thunk::Val(a), Thunk::Val(Value::Ref(cb, ys)), dep));
+        }
+      }
+      (Value::Ref(ca, xs), b) if defs.contains(&ca) => {
+        let a = delta(defs, &ca, xs, fuel)?;
+        work.push((Thunk::Val(a), Thunk::Val(b), dep));
+      }
+      (a, Value::Ref(cb, ys)) if defs.contains(&cb) => {
+        let b = delta(defs, &cb, ys, fuel)?;
+        work.push((Thunk::Val(a), Thunk::Val(b), dep));
+      }
+      (a @ Value::Lam(..), b) | (a, b @ Value::Lam(..)) => {
+        let var = Thunk::Val(Value::Neu(dep, Vector::new()));
+        let a = apply(a, var.clone(), fuel)?;
+        let b = apply(b, var, fuel)?;
+        work.push((Thunk::Val(a), Thunk::Val(b), dep + 1));
+      }
+      _ => return Some(false),
+    }
+  }
+  Some(true)
+}
+
+/// One more than the largest free variable of `term`
+fn free_vars(term: &Pure, dep: u64) -> u64 {
+  match term {
+    Pure::Var(_, _, idx) => (idx + 1).saturating_sub(dep),
+    Pure::Lam(_, _, bod) => free_vars(bod, dep + 1),
+    Pure::App(_, fun, arg) => free_vars(fun, dep).max(free_vars(arg, dep)),
+    Pure::Ref(..) => 0,
+  }
+}
+
+/// Decides βη-equality of two pure terms by normalization-by-evaluation,
+/// spending at most `fuel` β-reductions. Free variables are compared by
+/// index, binder names are ignored, and references are rigid constants.
+pub fn conv(a: &Pure, b: &Pure, fuel: u64) -> ConvResult {
+  conv_in(&Defs::new(), a, b, fuel)
+}
+
+/// Decides βδη-equality of two pure terms like `conv`, unfolding references
+/// to the definitions in `defs` on demand. Each unfolding costs one unit of
+/// fuel.
+pub fn conv_in(defs: &Defs, a: &Pure, b: &Pure, fuel: u64) -> ConvResult {
+  let len = free_vars(a, 0).max(free_vars(b, 0));
+  let env: Vector<Thunk> = (0..len)
+    .rev()
+    .map(|lvl| Thunk::Val(Value::Neu(lvl, Vector::new())))
+    .collect();
+  let mut fuel = fuel;
+  let work = vec![(
+    Thunk::Delay(env.clone(), a.clone()),
+    Thunk::Delay(env, b.clone()),
+    len,
+  )];
+  let res = conv_work(defs, work, &mut fuel, 0);
+  match res {
+    Some(true) => ConvResult::Equal,
+    Some(false) => ConvResult::NotEqual,
+    None => ConvResult::Unknown,
   }
 }
 
 #[cfg(test)]
 pub mod tests {
   use super::*;
-  use crate::gen::pure::{
-    tests::arbitrary_pure,
-    Pure as GenPure,
+  use crate::{
+    gen::pure::{
+      tests::arbitrary_pure,
+      Pure as GenPure,
+    },
+    parse::{
+      pure::parse_pure_telescope,
+      span::Span,
+      util::input_cid,
+    },
   };
   use quickcheck::{
     Arbitrary,
     Gen,
   };
-  use sp_im::Vector;
-  use sp_std::mem;
+  use sp_std::{
+    mem,
+    rc::Rc,
+  };
 
   impl Arbitrary for Pure {
     fn arbitrary(g: &mut Gen) -> Self {
@@ -67,4 +533,79 @@ pub mod tests {
       unsafe { mem::transmute::<GenPure, Pure>(gen_pure) }
     }
   }
+
+  fn parse(i: &str) -> Pure {
+    let ctx = Vector::from(vec![Name::from("f"), Name::from("g")]);
+    parse_pure_telescope(input_cid(i), Rc::default(), ctx)(Span::new(i))
+      .unwrap()
+      .1
+  }
+
+  #[test]
+  fn test_conv() {
+    fn test(a: &str, b: &str) -> ConvResult { conv(&parse(a), &parse(b), 100) }
+    assert_eq!(test("λ x => x", "λ y => y"), ConvResult::Equal);
+    assert_eq!(test("(λ x => x) f", "f"), ConvResult::Equal);
+    assert_eq!(test("λ x => f x", "f"), ConvResult::Equal);
+    assert_eq!(test("λ x y => f x y", "f"), ConvResult::Equal);
+    assert_eq!(
+      test("λ x => λ y => x", "λ x => λ y => y"),
+      ConvResult::NotEqual
+    );
+    assert_eq!(test("f", "g"), ConvResult::NotEqual);
+    assert_eq!(test("f g", "f f"), ConvResult::NotEqual);
+    assert_eq!(test("(λ x => x x) (λ x => x x)", "f"), ConvResult::Unknown);
+    assert_eq!(
+      test("(λ x y => y) ((λ x => x x) (λ x => x x))", "λ y => y"),
+      ConvResult::Equal
+    );
+    // each unrolling of a fixed point nests its comparison one level deeper
+    let y = "(λ x => f (x x)) (λ x => f (x x))";
+    assert_eq!(
+      conv(&parse(y), &parse(y), crate::check::CONV_FUEL),
+      ConvResult::Unknown
+    );
+    let fy = format!("f ({})", y);
+    assert_eq!(
+      conv(&parse(y), &parse(&fy), crate::check::CONV_FUEL),
+      ConvResult::Unknown
+    );
+  }
+
+  #[test]
+  fn test_alpha_eq() {
+    assert!(parse("λ x => x").alpha_eq(&parse("λ y => y")));
+    assert!(!parse("λ x => x").syntactic_eq(&parse("λ y => y")));
+    assert!(parse("λ x y => f x").alpha_eq(&parse("λ a b => f a")));
+    assert!(!parse("λ x y => x").alpha_eq(&parse("λ x y => y")));
+    assert!(!parse("f").alpha_eq(&parse("g")));
+  }
+
+  #[quickcheck]
+  fn alpha_eq_refl(x: Pure) -> bool { x.alpha_eq(&x) && x.syntactic_eq(&x) }
+
+  #[quickcheck]
+  fn pure_embed(x: Pure) -> bool {
+    let (anon, meta) = x.embed();
+    let anon = Anon::from_ipld(&anon.to_ipld());
+    let meta = Meta::from_ipld(&meta.to_ipld());
+    match (anon, meta) {
+      (Ok(anon), Ok(meta)) => match Pure::unembed(&anon, &meta) {
+        Ok(y) => x.syntactic_eq(&y),
+        _ => false,
+      },
+      _ => false,
+    }
+  }
+
+  #[quickcheck]
+  fn pure_ipld(x: Pure) -> bool {
+    match Pure::from_ipld(&x.to_ipld()) {
+      Ok(y) => x.syntactic_eq(&y) && x.cid() == y.cid(),
+      _ => false,
+    }
+  }
+
+  #[quickcheck]
+  fn conv_refl(x: Pure) -> bool { conv(&x, &x, 100) != ConvResult::NotEqual }
 }
@@ -0,0 +1,586 @@
+use crate::{
+  module::Defs,
+  name::Name,
+  position::Pos,
+  pure::Pure,
+};
+
+use sp_cid::Cid;
+use sp_im::Vector;
+
+use sp_std::{
+  boxed::Box,
+  cell::RefCell,
+  fmt,
+  rc::Rc,
+  vec::Vec,
+};
+
+/// Reduction strategy of the evaluator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+  /// leftmost-outermost reduction to normal form. Finds the normal form
+  /// whenever one exists.
+  NormalOrder,
+  /// applicative order: arguments are normalized before they are substituted,
+  /// and bodies are normalized under their binders
+  CallByValue,
+  /// lazy evaluation to normal form, where each argument is reduced at most
+  /// once and its value is shared between its occurrences
+  CallByNeed,
+  /// leftmost-outermost reduction until the head is a lambda or a variable
+  WeakHead,
+}
+
+/// An evaluated term with the number of β-reductions it took
+#[derive(Debug, Clone)]
+pub struct Evaluated {
+  pub term: Pure,
+  pub steps: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+  /// the step limit was reached before the term was evaluated
+  StepLimit(u64),
+}
+
+impl fmt::Display for EvalError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::StepLimit(limit) => {
+        write!(f, "Evaluation did not finish within {} steps", limit)
+      }
+    }
+  }
+}
+
+/// Counts β-reductions against a limit
+struct Steps {
+  steps: u64,
+  limit: u64,
+}
+
+impl Steps {
+  fn tick(&mut self) -> Result<(), EvalError> {
+    if self.steps >= self.limit {
+      return Err(EvalError::StepLimit(self.limit));
+    }
+    self.steps += 1;
+    Ok(())
+  }
+}
+
+/// Evaluates `term` with `strategy`, performing at most `limit` β-reductions.
+/// A reference to a definition of `defs` is unfolded to its erased body when
+/// the evaluation reaches it. Free variables and other references are left in
+/// place.
+pub fn eval(
+  defs: &Defs,
+  term: &Pure,
+  strategy: Strategy,
+  limit: u64,
+) -> Result<Evaluated, EvalError> {
+  let mut steps = Steps { steps: 0, limit };
+  let term = match strategy {
+    Strategy::NormalOrder => normalize(defs, false, term, &mut steps)?,
+    Strategy::CallByValue => call_by_value(defs, term, &mut steps)?,
+    Strategy::CallByNeed => normalize(defs, true, term, &mut steps)?,
+    Strategy::WeakHead => weak_head(defs, term, &mut steps)?,
+  };
+  Ok(Evaluated { term, steps: steps.steps })
+}
+
+fn weak_head(
+  defs: &Defs,
+  term: &Pure,
+  steps: &mut Steps,
+) -> Result<Pure, EvalError> {
+  let mut spine = Vec::new();
+  let mut head = term.clone();
+  loop {
+    match head {
+      Pure::App(pos, fun, arg) => {
+        spine.push((pos, arg));
+        head = *fun;
+      }
+      Pure::Lam(_, _, bod) if !spine.is_empty() => {
+        let (_, arg) = spine.pop().unwrap();
+        steps.tick()?;
+        head = bod.subst(0, &arg);
+      }
+      Pure::Ref(_, _, ref cid) => match defs.erased(cid) {
+        Some(bod) => head = bod,
+        None => break,
+      },
+      _ => break,
+    }
+  }
+  Ok(
+    spine
+      .into_iter()
+      .rev()
+      .fold(head, |fun, (pos, arg)| Pure::App(pos, Box::new(fun), arg)),
+  )
+}
+
+/// What remains to be done with the value of a subterm in `call_by_value`
+enum Frame {
+  /// put the value under a lambda
+  Lam(Pos, Name),
+  /// evaluate the argument of an application whose function is the value
+  Arg(Pos, Box<Pure>),
+  /// apply the evaluated function to the value
+  Fun(Pos, Pure),
+}
+
+fn call_by_value(
+  defs: &Defs,
+  term: &Pure,
+  steps: &mut Steps,
+) -> Result<Pure, EvalError> {
+  let mut stack = Vec::new();
+  let mut term = term.clone();
+  // β-reductions loop instead of recursing, and subterms are evaluated from
+  // an explicit stack, so a divergent term runs out of steps rather than stack
+  loop {
+    let mut val = loop {
+      match term {
+        Pure::Lam(pos, nam, bod) => {
+          stack.push(Frame::Lam(pos, nam));
+          term = *bod;
+        }
+        Pure::App(pos, fun, arg) => {
+          stack.push(Frame::Arg(pos, arg));
+          term = *fun;
+        }
+        Pure::Ref(pos, nam, cid) => match defs.erased(&cid) {
+          Some(bod) => term = bod,
+          None => break Pure::Ref(pos, nam, cid),
+        },
+        var => break var,
+      }
+    };
+    loop {
+      match stack.pop() {
+        None => return Ok(val),
+        Some(Frame::Lam(pos, nam)) => val = Pure::Lam(pos, nam, Box::new(val)),
+        Some(Frame::Arg(pos, arg)) => {
+          stack.push(Frame::Fun(pos, val));
+          term = *arg;
+          break;
+        }
+        Some(Frame::Fun(_, Pure::Lam(_, _, bod))) => {
+          steps.tick()?;
+          term = bod.subst(0, &val);
+          break;
+        }
+        Some(Frame::Fun(pos, fun)) => {
+          val = Pure::App(pos, Box::new(fun), Box::new(val))
+        }
+      }
+    }
+  }
+}
+
+/// A pure term whose subterms are shared, so that closures and suspensions
+/// hold on to them without copying
+enum Code {
+  Var(u64),
+  Lam(Pos, Name, Rc<Code>),
+  App(Rc<Code>, Rc<Code>),
+  Ref(Pos, Name, Cid),
+}
+
+impl From<&Pure> for Code {
+  fn from(term: &Pure) -> Self {
+    match term {
+      Pure::Var(_, _, idx) => Code::Var(*idx),
+      Pure::Lam(pos, nam, bod) => {
+        Code::Lam(*pos, nam.clone(), Rc::new(Code::from(&**bod)))
+      }
+      Pure::App(_, fun, arg) => {
+        Code::App(Rc::new(Code::from(&**fun)), Rc::new(Code::from(&**arg)))
+      }
+      Pure::Ref(pos, nam, cid) => Code::Ref(*pos, nam.clone(), *cid),
+    }
+  }
+}
+
+/// Values of the environment machine. Variables are de Bruijn levels.
+#[derive(Clone)]
+enum Value {
+  Lam(Pos, Name, Env, Rc<Code>),
+  Neu(Pos, Name, u64, Vector<Thunk>),
+  Ref(Pos, Name, Cid, Vector<Thunk>),
+}
+
+/// A shared argument, memoized when evaluation shares arguments
+type Thunk = Rc<RefCell<Suspension>>;
+
+enum Suspension {
+  Delayed(Env, Rc<Code>),
+  Forced(Value),
+}
+
+type Env = Vector<Thunk>;
+
+fn delay(env: Env, term: Rc<Code>) -> Thunk {
+  Rc::new(RefCell::new(Suspension::Delayed(env, term)))
+}
+
+fn force(
+  defs: &Defs,
+  share: bool,
+  thunk: &Thunk,
+  steps: &mut Steps,
+) -> Result<Value, EvalError> {
+  let susp = match &*thunk.borrow() {
+    Suspension::Forced(val) => return Ok(val.clone()),
+    Suspension::Delayed(env, term) => (env.clone(), term.clone()),
+  };
+  let val = eval_code(defs, share, susp.0, susp.1, steps)?;
+  if share {
+    *thunk.borrow_mut() = Suspension::Forced(val.clone());
+  }
+  Ok(val)
+}
+
+/// What remains to be done with a value in the environment machine
+enum Cont {
+  /// memoize the value as the value of a forced argument
+  Update(Thunk),
+  /// apply the value to an argument
+  Apply(Thunk),
+}
+
+fn eval_code(
+  defs: &Defs,
+  share: bool,
+  env: Env,
+  term: Rc<Code>,
+  steps: &mut Steps,
+) -> Result<Value, EvalError> {
+  let mut stack = Vec::new();
+  let mut env = env;
+  let mut term = term;
+  // β-reductions and forced arguments loop instead of recursing, so a
+  // divergent term runs out of steps rather than stack
+  loop {
+    let mut val = loop {
+      let (next_env, next_term) = match &*term {
+        Code::Var(idx) => {
+          let thunk = env[*idx as usize].clone();
+          let susp = match &*thunk.borrow() {
+            Suspension::Forced(val) => break val.clone(),
+            Suspension::Delayed(env, term) => (env.clone(), term.clone()),
+          };
+          if share {
+            stack.push(Cont::Update(thunk));
+          }
+          susp
+        }
+        Code::Lam(pos, nam, bod) => {
+          break Value::Lam(*pos, nam.clone(), env.clone(), bod.clone());
+        }
+        Code::Ref(pos, nam, cid) => match defs.erased(cid) {
+          Some(bod) => (Env::new(), Rc::new(Code::from(&bod))),
+          None => break Value::Ref(*pos, nam.clone(), *cid, Vector::new()),
+        },
+        Code::App(fun, arg) => {
+          // a variable is passed on as is, so that arguments do not build up
+          // chains of suspensions that each lead to the next
+          let arg = match &**arg {
+            Code::Var(idx) => env[*idx as usize].clone(),
+            _ => delay(env.clone(), arg.clone()),
+          };
+          stack.push(Cont::Apply(arg));
+          (env.clone(), fun.clone())
+        }
+      };
+      env = next_env;
+      term = next_term;
+    };
+    loop {
+      match stack.pop() {
+        None => return Ok(val),
+        Some(Cont::Update(thunk)) => {
+          *thunk.borrow_mut() = Suspension::Forced(val.clone())
+        }
+        Some(Cont::Apply(arg)) => match val {
+          Value::Lam(_, _, mut clo, bod) => {
+            steps.tick()?;
+            clo.push_front(arg);
+            env = clo;
+            term = bod;
+            break;
+          }
+          Value::Neu(pos, nam, lvl, mut args) => {
+            args.push_back(arg);
+            val = Value::Neu(pos, nam, lvl, args);
+          }
+          Value::Ref(pos, nam, cid, mut args) => {
+            args.push_back(arg);
+            val = Value::Ref(pos, nam, cid, args);
+          }
+        },
+      }
+    }
+  }
+}
+
+/// What remains to be done with a quoted subterm
+enum Quote {
+  /// put it under a lambda
+  Lam(Pos, Name),
+  /// apply the quoted function to it, then quote the remaining arguments
+  Arg(Pure, Vector<Thunk>),
+}
+
+/// Reads a value back into a normal form at binder depth `dep`
+fn quote(
+  defs: &Defs,
+  share: bool,
+  val: Value,
+  dep: u64,
+  steps: &mut Steps,
+) -> Result<Pure, EvalError> {
+  let mut stack = Vec::new();
+  let mut val = val;
+  let mut dep = dep;
+  // the normal form is built from an explicit stack rather than the call
+  // stack, so a value that keeps unfolding runs out of steps rather than stack
+  loop {
+    let mut term = loop {
+      let (head, mut args) = match val {
+        Value::Lam(pos, nam, mut env, bod) => {
+          let var = Value::Neu(pos, nam.clone(), dep, Vector::new());
+          env.push_front(Rc::new(RefCell::new(Suspension::Forced(var))));
+          stack.push(Quote::Lam(pos, nam));
+          val = eval_code(defs, share, env, bod, steps)?;
+          dep += 1;
+          continue;
+        }
+        Value::Neu(pos, nam, lvl, args) => {
+          (Pure::Var(pos, nam, dep - lvl - 1), args)
+        }
+        Value::Ref(pos, nam, cid, args) => (Pure::Ref(pos, nam, cid), args),
+      };
+      match args.pop_front() {
+        None => break head,
+        Some(arg) => {
+          stack.push(Quote::Arg(head, args));
+          val = force(defs, share, &arg, steps)?;
+        }
+      }
+    };
+    loop {
+      match stack.pop() {
+        None => return Ok(term),
+        Some(Quote::Lam(pos, nam)) => {
+          dep -= 1;
+          term = Pure::Lam(pos, nam, Box::new(term));
+        }
+        Some(Quote::Arg(fun, mut args)) => {
+          let fun = Pure::App(fun.pos(), Box::new(fun), Box::new(term));
+          match args.pop_front() {
+            None => term = fun,
+            Some(arg) => {
+              stack.push(Quote::Arg(fun, args));
+              val = force(defs, share, &arg, steps)?;
+              break;
+            }
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Collects the names of the free variables of `term`, indexed by their de
+/// Bruijn index
+fn free_vars(term: &Pure, dep: u64, names: &mut Vec<Name>) {
+  match term {
+    Pure::Var(_, nam, idx) => {
+      if *idx >= dep {
+        let idx = (idx - dep) as usize;
+        while names.len() <= idx {
+          names.push(nam.clone());
+        }
+        names[idx] = nam.clone();
+      }
+    }
+    Pure::Lam(_, _, bod) => free_vars(bod, dep + 1, names),
+    Pure::App(_, fun, arg) => {
+      free_vars(fun, dep, names);
+      free_vars(arg, dep, names);
+    }
+    Pure::Ref(..) => (),
+  }
+}
+
+/// Normalizes `term` on the environment machine. With `share`, each argument
+/// is evaluated at most once and its value is shared between its occurrences.
+/// Without, each occurrence is evaluated anew, which takes the β-reductions of
+/// normal order without copying arguments into the term.
+fn normalize(
+  defs: &Defs,
+  share: bool,
+  term: &Pure,
+  steps: &mut Steps,
+) -> Result<Pure, EvalError> {
+  let mut names = Vec::new();
+  free_vars(term, 0, &mut names);
+  let len = names.len() as u64;
+  let env: Env = names
+    .into_iter()
+    .enumerate()
+    .map(|(idx, nam)| {
+      let var = Value::Neu(Pos::None, nam, len - idx as u64 - 1, Vector::new());
+      Rc::new(RefCell::new(Suspension::Forced(var)))
+    })
+    .collect();
+  let val = eval_code(defs, share, env, Rc::new(Code::from(term)), steps)?;
+  quote(defs, share, val, len, steps)
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    check::check_module,
+    parse::{
+      file::parse_file,
+      pure::parse_pure_telescope,
+      span::Span,
+      util::input_cid,
+    },
+    pure::{
+      conv,
+      ConvResult,
+    },
+  };
+
+  fn parse(i: &str) -> Pure {
+    let ctx = Vector::from(vec![Name::from("f"), Name::from("g")]);
+    parse_pure_telescope(input_cid(i), Rc::default(), ctx)(Span::new(i))
+      .unwrap()
+      .1
+  }
+
+  const STRATEGIES: [Strategy; 4] = [
+    Strategy::NormalOrder,
+    Strategy::CallByValue,
+    Strategy::CallByNeed,
+    Strategy::WeakHead,
+  ];
+
+  #[test]
+  fn test_eval() {
+    let two = "(λ s z => s (s z))";
+    let four = parse("λ s z => s (s (s (s z)))");
+    for strategy in &STRATEGIES[0..3] {
+      let res =
+        eval(&Defs::new(), &parse(&format!("{} {}", two, two)), *strategy, 100);
+      assert!(res.unwrap().term.alpha_eq(&four));
+    }
+    let res = eval(
+      &Defs::new(),
+      &parse("(λ x => x) (λ y => (λ x => x) y)"),
+      Strategy::WeakHead,
+      100,
+    );
+    let res = res.unwrap();
+    assert!(res.term.alpha_eq(&parse("λ y => (λ x => x) y")));
+    assert_eq!(res.steps, 1);
+    let res =
+      eval(&Defs::new(), &parse("(λ x => x) f g"), Strategy::WeakHead, 100);
+    assert!(res.unwrap().term.alpha_eq(&parse("f g")));
+  }
+
+  #[test]
+  fn test_eval_limit() {
+    let omega = "((λ x => x x) (λ x => x x))";
+    for strategy in &STRATEGIES {
+      let res = eval(&Defs::new(), &parse(omega), *strategy, 50);
+      assert_eq!(res.unwrap_err(), EvalError::StepLimit(50));
+    }
+    // lazy strategies discard the divergent argument
+    let term = parse(&format!("(λ x y => y) {}", omega));
+    for strategy in &[Strategy::NormalOrder, Strategy::CallByNeed] {
+      let res = eval(&Defs::new(), &term, *strategy, 50).unwrap();
+      assert!(res.term.alpha_eq(&parse("λ y => y")));
+      assert_eq!(res.steps, 1);
+    }
+    assert!(eval(&Defs::new(), &term, Strategy::CallByValue, 50).is_err());
+    // a divergent term runs out of steps rather than stack
+    for strategy in &[Strategy::CallByValue, Strategy::CallByNeed] {
+      let res = eval(&Defs::new(), &parse(omega), *strategy, 1_000_000);
+      assert_eq!(res.unwrap_err(), EvalError::StepLimit(1_000_000));
+    }
+    // even when each step nests the normal form one level deeper
+    let fix = parse("(λ x => f (x x)) (λ x => f (x x))");
+    for strategy in &STRATEGIES[0..3] {
+      let res = eval(&Defs::new(), &fix, *strategy, 100_000);
+      assert_eq!(res.unwrap_err(), EvalError::StepLimit(100_000));
+    }
+  }
+
+  #[test]
+  fn test_eval_sharing() {
+    let term = parse("(λ x => f x x x) ((λ y => y) g)");
+    let by_name =
+      eval(&Defs::new(), &term, Strategy::NormalOrder, 100).unwrap();
+    let by_need = eval(&Defs::new(), &term, Strategy::CallByNeed, 100).unwrap();
+    let by_value =
+      eval(&Defs::new(), &term, Strategy::CallByValue, 100).unwrap();
+    assert!(by_name.term.alpha_eq(&parse("f g g g")));
+    assert!(by_need.term.alpha_eq(&by_name.term));
+    assert!(by_value.term.alpha_eq(&by_name.term));
+    assert_eq!(by_name.steps, 4);
+    assert_eq!(by_need.steps, 2);
+    assert_eq!(by_value.steps, 2);
+    assert!(by_need.term.syntactic_eq(&by_name.term));
+  }
+
+  #[test]
+  fn test_eval_refs() {
+    let i = "type Bool : Type = ∀ (A: Type) (t: A) (f: A) -> A;
+       def true : Bool = λ (A: Type) (t: A) (f: A) => t;
+       def false : Bool = λ (A: Type) (t: A) (f: A) => f;
+       def not : ∀ (b: Bool) -> Bool = λ (b: Bool) => b Bool false true;";
+    let module =
+      parse_file(input_cid(i), Rc::default())(Span::new(i)).unwrap().1;
+    let defs = check_module(&Defs::new(), &module).unwrap();
+    let r = |nam: &str| {
+      let cid = defs.term_ref(nam).unwrap();
+      Box::new(Pure::Ref(Pos::None, Name::from(nam), cid))
+    };
+    let term = Pure::App(Pos::None, r("not"), r("true"));
+    let fals = defs.erased(&defs.term_ref("false").unwrap()).unwrap();
+    for strategy in &STRATEGIES[0..3] {
+      let res = eval(&defs, &term, *strategy, 100).unwrap();
+      assert!(res.term.alpha_eq(&fals));
+    }
+    // references are only unfolded when they are reached
+    let res = eval(&defs, &term, Strategy::WeakHead, 100).unwrap();
+    assert!(res.term.alpha_eq(&parse("λ t f => f")));
+    let res = eval(&Defs::new(), &term, Strategy::NormalOrder, 100).unwrap();
+    assert!(res.term.syntactic_eq(&term));
+  }
+
+  #[quickcheck]
+  fn eval_strategies_agree(x: Pure) -> bool {
+    let by_name = eval(&Defs::new(), &x, Strategy::NormalOrder, 100);
+    let by_need = eval(&Defs::new(), &x, Strategy::CallByNeed, 100);
+    match (by_name, by_need) {
+      (Ok(a), Ok(b)) => {
+        a.term.alpha_eq(&b.term)
+          && b.steps <= a.steps
+          && conv(&x, &a.term, 1000) != ConvResult::NotEqual
+      }
+      (Ok(_), Err(_)) => false,
+      _ => true,
+    }
+  }
+}
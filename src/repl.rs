@@ -0,0 +1,275 @@
+pub mod error;
+
+use crate::{
+  check::{
+    check_def,
+    check_kind,
+    ctx::Ctx,
+    infer,
+  },
+  diagnostic::Diagnostic,
+  erase::erase,
+  kind::Kind,
+  module::{
+    Def,
+    Defs,
+  },
+  package::fs::Dir,
+  parse::{
+    error::ParseError,
+    file::{
+      parse_def,
+      parse_type_def,
+    },
+    pure::parse_pure_telescope,
+    span::Span,
+    term::parse_term_telescope,
+    typ_::parse_type_telescope,
+    util::{
+      input_cid,
+      parse_space,
+      Ctx as NameCtx,
+    },
+  },
+  print::{
+    self,
+    kind,
+    pure,
+    typ_,
+    Config,
+  },
+  pure::{
+    conv_in,
+    eval::{
+      eval,
+      Strategy,
+    },
+    ConvResult,
+    Pure,
+  },
+  repl::error::ReplError,
+  term::Term,
+  typ_::Type,
+};
+
+use nom::{
+  branch::alt,
+  bytes::complete::tag,
+  combinator::eof,
+  IResult,
+};
+
+use std::rc::Rc;
+
+pub const HELP: &str = "def name : T = t;   add a term definition
+type Name : K = T;  add a type definition
+:type t             infer the type of a term
+:kind T             infer the kind of a type
+:erase t            erase a well-typed term
+:norm t             normalize a pure term
+:conv a ≃ b         decide whether two pure terms are convertible
+:load file.cedc     build a package and add its definitions
+:cid name           print the CID of a definition
+:help               show this message
+:quit               leave";
+
+/// An interactive session: the definitions entered or loaded so far, which
+/// every later input can refer to
+#[derive(Clone, Debug)]
+pub struct Repl {
+  pub defs: Rc<Defs>,
+  /// β-reductions allowed to `:norm` and `:conv`
+  pub fuel: u64,
+  /// How results are printed
+  pub print: Config,
+}
+
+impl Default for Repl {
+  fn default() -> Self { Repl::new() }
+}
+
+impl Repl {
+  pub fn new() -> Self {
+    Repl {
+      defs: Rc::default(),
+      fuel: 1_000_000,
+      print: Config { width: print::WIDTH, ..Config::default() },
+    }
+  }
+
+  /// Runs a definition or a command, returning what to print
+  pub fn run(&mut self, line: &str) -> Result<String, ReplError> {
+    let trimmed = line.trim();
+    if !trimmed.starts_with(':') {
+      return self.define(line);
+    }
+    let mut words = trimmed.splitn(2, char::is_whitespace);
+    let cmd = words.next().unwrap_or("");
+    let arg = words.next().unwrap_or("").trim();
+    let input = input_cid(line);
+    let ctx = Ctx::with_defs(self.defs.clone());
+    let defs = self.defs.clone();
+    match cmd {
+      ":help" => Ok(HELP.to_owned()),
+      ":type" => {
+        let trm: Term = parse_arg(
+          line,
+          cmd,
+          parse_term_telescope(input, defs, NameCtx::new(), NameCtx::new()),
+        )?;
+        Ok(typ_::print(&self.print, &infer(&ctx, &trm)?))
+      }
+      ":kind" => {
+        let typ: Type = parse_arg(
+          line,
+          cmd,
+          parse_type_telescope(input, defs, NameCtx::new(), NameCtx::new()),
+        )?;
+        let kind: Kind = check_kind(&ctx, &typ)?;
+        Ok(kind::print(&self.print, &kind))
+      }
+      ":erase" => {
+        let trm: Term = parse_arg(
+          line,
+          cmd,
+          parse_term_telescope(input, defs, NameCtx::new(), NameCtx::new()),
+        )?;
+        infer(&ctx, &trm)?;
+        Ok(pure::print(&self.print, &erase(&trm)))
+      }
+      ":norm" => {
+        let trm: Pure = parse_arg(
+          line,
+          cmd,
+          parse_pure_telescope(input, defs, NameCtx::new()),
+        )?;
+        let res = eval(&self.defs, &trm, Strategy::NormalOrder, self.fuel)?;
+        Ok(pure::print(&self.print, &res.term))
+      }
+      ":conv" => {
+        let pure = || parse_pure_telescope(input, defs.clone(), NameCtx::new());
+        let (a, b) = parse_arg(line, cmd, |i| {
+          let (i, a) = pure()(i)?;
+          let (i, _) = parse_space(i)?;
+          let (i, _) = tag("≃")(i)?;
+          let (i, _) = parse_space(i)?;
+          let (i, b) = pure()(i)?;
+          Ok((i, (a, b)))
+        })?;
+        Ok(
+          match conv_in(&self.defs, &a, &b, self.fuel) {
+            ConvResult::Equal => "convertible",
+            ConvResult::NotEqual => "not convertible",
+            ConvResult::Unknown => "unknown: ran out of fuel",
+          }
+          .to_owned(),
+        )
+      }
+      ":load" => {
+        let (dir, name) = Dir::open(arg).ok_or_else(|| {
+          ReplError::Usage(format!("Not a .cedc file: {}", arg))
+        })?;
+        let (package, loaded) = dir.build(&name)?;
+        Rc::make_mut(&mut self.defs).import(&loaded);
+        Ok(format!(
+          "Loaded {} with {} definitions: {}",
+          package.name,
+          loaded.names.len(),
+          package.cid()
+        ))
+      }
+      ":cid" => match self.defs.names.get(arg) {
+        Some(cid) => Ok(cid.to_string()),
+        None => Err(ReplError::Undefined(arg.to_owned())),
+      },
+      _ => Err(ReplError::Usage(format!("Unknown command {}, see :help", cmd))),
+    }
+  }
+
+  /// Checks a definition and adds it to the session, replacing any earlier
+  /// definition with the same name
+  fn define(&mut self, line: &str) -> Result<String, ReplError> {
+    let input = input_cid(line);
+    let defs = self.defs.clone();
+    let def: Def = parse_arg(
+      line,
+      "",
+      alt((
+        parse_def(input, defs.clone(), NameCtx::new(), NameCtx::new()),
+        parse_type_def(input, defs, NameCtx::new(), NameCtx::new()),
+      )),
+    )?;
+    check_def(&self.defs, &def)?;
+    let cid = Rc::make_mut(&mut self.defs).insert(def.clone());
+    Ok(format!("{} {}", def.name(), cid))
+  }
+}
+
+/// Parses all of `line` after the command `cmd` with `parser`. Positions are
+/// relative to the whole line.
+fn parse_arg<'a, O>(
+  line: &'a str,
+  cmd: &str,
+  mut parser: impl FnMut(Span<'a>) -> IResult<Span<'a>, O, ParseError<Span<'a>>>,
+) -> Result<O, ReplError> {
+  let res = parse_space(Span::new(line))
+    .and_then(|(i, _)| tag(cmd)(i))
+    .and_then(|(i, _)| parse_space(i))
+    .and_then(|(i, _)| parser(i))
+    .and_then(|(i, o)| {
+      let (i, _) = parse_space(i)?;
+      let (i, _) = eof(i)?;
+      Ok((i, o))
+    });
+  match res {
+    Ok((_, o)) => Ok(o),
+    Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+      Err(ReplError::Parse(Box::new(Diagnostic::from(&e))))
+    }
+    Err(nom::Err::Incomplete(_)) => {
+      Err(ReplError::Parse(Box::new(Diagnostic::error("Incomplete input"))))
+    }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::pure::eval::EvalError;
+
+  #[test]
+  fn test_repl() {
+    let mut repl = Repl::new();
+    let mut run = |line: &str| repl.run(line);
+    run("type Bool : Type = ∀ (A: Type) (t: A) (f: A) -> A;").unwrap();
+    run("def true : Bool = λ (A: Type) (t: A) (f: A) => t;").unwrap();
+    run("def false : Bool = λ (A: Type) (t: A) (f: A) => f;").unwrap();
+    run("def not : ∀ (b: Bool) -> Bool = λ (b: Bool) => b Bool false true;")
+      .unwrap();
+    assert_eq!(run(":erase not true").unwrap(), "not true");
+    assert_eq!(run(":norm not true").unwrap(), "λ t f => f");
+    assert_eq!(run(":conv not (not true) ≃ true").unwrap(), "convertible");
+    assert_eq!(run(":conv not true ≃ true").unwrap(), "not convertible");
+    assert!(run(":type not true").is_ok());
+    assert!(run(":kind Bool").is_ok());
+    assert!(run(":cid not").is_ok());
+    assert!(matches!(run(":cid nope"), Err(ReplError::Undefined(_))));
+    assert!(matches!(run(":type true true"), Err(ReplError::Type(_))));
+    assert!(matches!(run(":norm (x"), Err(ReplError::Parse(_))));
+    assert!(matches!(run(":frob"), Err(ReplError::Usage(_))));
+    assert!(matches!(run(":load nope.cedc"), Err(ReplError::Package(_))));
+    // a failed definition leaves the session unchanged
+    assert!(run("def bad : Bool = not;").is_err());
+    assert!(matches!(run(":cid bad"), Err(ReplError::Undefined(_))));
+    // divergent terms run out of fuel
+    let fix = "λ f => (λ x => f (x x)) (λ x => f (x x))";
+    assert_eq!(
+      run(&format!(":conv {} ≃ {}", fix, fix)).unwrap(),
+      "unknown: ran out of fuel"
+    );
+    assert!(matches!(
+      run(":norm (λ x => x x) (λ x => x x)"),
+      Err(ReplError::Eval(EvalError::StepLimit(_)))
+    ));
+  }
+}
@@ -0,0 +1,68 @@
+use crate::{
+  check::error::TypeError,
+  diagnostic::Diagnostic,
+  package::error::PackageError,
+  position::Pos,
+  pure::eval::EvalError,
+};
+
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub enum ReplError {
+  /// unknown command or missing arguments
+  Usage(String),
+  /// parse error, with its labels in the input line
+  Parse(Box<Diagnostic>),
+  Type(Box<TypeError>),
+  Eval(EvalError),
+  Package(Box<PackageError>),
+  /// no definition with this name
+  Undefined(String),
+}
+
+impl ReplError {
+  /// Where in the input line the error occurred
+  pub fn pos(&self) -> Pos {
+    match self {
+      Self::Type(e) => e.pos(),
+      _ => Pos::None,
+    }
+  }
+}
+
+/// The labels point into the input line
+impl From<&ReplError> for Diagnostic {
+  fn from(e: &ReplError) -> Self {
+    match e {
+      ReplError::Parse(diag) => (**diag).clone(),
+      ReplError::Type(e) => Diagnostic::from(&**e),
+      _ => Diagnostic::error(e),
+    }
+  }
+}
+
+impl From<TypeError> for ReplError {
+  fn from(e: TypeError) -> Self { Self::Type(Box::new(e)) }
+}
+
+impl From<EvalError> for ReplError {
+  fn from(e: EvalError) -> Self { Self::Eval(e) }
+}
+
+impl From<PackageError> for ReplError {
+  fn from(e: PackageError) -> Self { Self::Package(Box::new(e)) }
+}
+
+impl fmt::Display for ReplError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Usage(msg) => write!(f, "{}", msg),
+      Self::Parse(e) => write!(f, "{}", e),
+      Self::Type(e) => write!(f, "{}", e),
+      Self::Eval(e) => write!(f, "{}", e),
+      Self::Package(e) => write!(f, "{}", e),
+      Self::Undefined(nam) => write!(f, "No definition named {}", nam),
+    }
+  }
+}
@@ -0,0 +1,68 @@
+#[cfg(feature = "std")]
+pub mod fs;
+pub mod mem;
+
+use crate::hash::ipld_cid;
+
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
+use sp_std::fmt;
+
+use alloc::string::String;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoreError {
+  /// no block is stored under this CID
+  Missing(Cid),
+  /// the block stored under this CID has different content
+  Corrupt(Cid),
+  Io(String),
+}
+
+impl fmt::Display for StoreError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Missing(cid) => write!(f, "No block with CID {}", cid),
+      Self::Corrupt(cid) => {
+        write!(f, "The block stored under {} does not match its CID", cid)
+      }
+      Self::Io(e) => write!(f, "{}", e),
+    }
+  }
+}
+
+/// Content-addressed storage of IPLD blocks. Blocks are addressed by
+/// `hash::ipld_cid`, so storing a block twice is harmless.
+pub trait Store {
+  /// Stores a block, returning its CID
+  fn put(&self, ipld: &Ipld) -> Result<Cid, StoreError>;
+
+  /// The block stored under `cid`, without checking it against the CID
+  fn get_unchecked(&self, cid: &Cid) -> Result<Option<Ipld>, StoreError>;
+
+  /// The block stored under `cid`
+  fn get(&self, cid: &Cid) -> Result<Ipld, StoreError> {
+    match self.get_unchecked(cid)? {
+      Some(ipld) if ipld_cid(&ipld) == *cid => Ok(ipld),
+      Some(_) => Err(StoreError::Corrupt(*cid)),
+      None => Err(StoreError::Missing(*cid)),
+    }
+  }
+
+  fn contains(&self, cid: &Cid) -> bool {
+    matches!(self.get_unchecked(cid), Ok(Some(_)))
+  }
+}
+
+/// A view of a store that reads its blocks but writes nothing: `put` only
+/// computes the CID of a block
+pub struct ReadOnly<'a>(pub &'a dyn Store);
+
+impl Store for ReadOnly<'_> {
+  fn put(&self, ipld: &Ipld) -> Result<Cid, StoreError> { Ok(ipld_cid(ipld)) }
+
+  fn get_unchecked(&self, cid: &Cid) -> Result<Option<Ipld>, StoreError> {
+    self.0.get_unchecked(cid)
+  }
+}
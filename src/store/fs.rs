@@ -0,0 +1,98 @@
+use crate::{
+  hash::ipld_cid,
+  store::{
+    Store,
+    StoreError,
+  },
+};
+
+use sp_cid::Cid;
+use sp_ipld::{
+  bytecursor::ByteCursor,
+  dag_cbor::DagCborCodec,
+  Codec,
+  Ipld,
+};
+
+use std::{
+  fs,
+  io,
+  path::PathBuf,
+  process,
+};
+
+/// A store that keeps each block in its own file, named by its CID, in a
+/// directory
+#[derive(Clone, Debug)]
+pub struct FsStore {
+  pub dir: PathBuf,
+}
+
+impl FsStore {
+  pub fn new(dir: impl Into<PathBuf>) -> Self { FsStore { dir: dir.into() } }
+
+  fn path(&self, cid: &Cid) -> PathBuf { self.dir.join(cid.to_string()) }
+}
+
+fn io_error(e: io::Error) -> StoreError { StoreError::Io(e.to_string()) }
+
+impl Store for FsStore {
+  /// Stores a block unless a valid copy is already there. The block is
+  /// written to a temporary file that is renamed into place, so a reader
+  /// never sees a partly written block.
+  fn put(&self, ipld: &Ipld) -> Result<Cid, StoreError> {
+    let cid = ipld_cid(ipld);
+    if self.contains(&cid) {
+      return Ok(cid);
+    }
+    let bytes = DagCborCodec.encode(ipld).map_err(StoreError::Io)?;
+    fs::create_dir_all(&self.dir).map_err(io_error)?;
+    let tmp = self.dir.join(format!(".{}.{}.tmp", cid, process::id()));
+    fs::write(&tmp, bytes.into_inner()).map_err(io_error)?;
+    fs::rename(&tmp, self.path(&cid)).map_err(|e| {
+      let _ = fs::remove_file(&tmp);
+      io_error(e)
+    })?;
+    Ok(cid)
+  }
+
+  fn get_unchecked(&self, cid: &Cid) -> Result<Option<Ipld>, StoreError> {
+    match fs::read(self.path(cid)) {
+      Ok(bytes) => {
+        let ipld = DagCborCodec.decode(ByteCursor::new(bytes));
+        Ok(Some(ipld.map_err(StoreError::Io)?))
+      }
+      Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+      Err(e) => Err(io_error(e)),
+    }
+  }
+
+  /// Whether a block is stored under `cid` and matches it
+  fn contains(&self, cid: &Cid) -> bool { self.get(cid).is_ok() }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+
+  #[test]
+  fn test_fs_store() {
+    let dir = std::env::temp_dir()
+      .join(format!("cedille-core-store-{}", std::process::id()));
+    let store = FsStore::new(&dir);
+    let ipld = Ipld::List(vec![Ipld::Integer(1), Ipld::String("x".into())]);
+    let cid = store.put(&ipld).unwrap();
+    assert!(store.contains(&cid));
+    assert_eq!(store.get(&cid), Ok(ipld.clone()));
+    // blocks outlive the store value
+    assert_eq!(FsStore::new(&dir).get(&cid), Ok(ipld.clone()));
+    let other = ipld_cid(&Ipld::Null);
+    assert_eq!(store.get(&other), Err(StoreError::Missing(other)));
+    // a corrupt block is not counted as stored, and storing it repairs it
+    fs::write(store.path(&cid), b"corrupt").unwrap();
+    assert!(!store.contains(&cid));
+    assert_eq!(store.put(&ipld), Ok(cid));
+    assert_eq!(store.get(&cid), Ok(ipld));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
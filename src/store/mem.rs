@@ -0,0 +1,56 @@
+use crate::{
+  hash::ipld_cid,
+  store::{
+    Store,
+    StoreError,
+  },
+};
+
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
+use sp_std::{
+  cell::RefCell,
+  collections::btree_map::BTreeMap,
+};
+
+/// A store that keeps its blocks in memory
+#[derive(Clone, Debug, Default)]
+pub struct MemStore {
+  pub blocks: RefCell<BTreeMap<Cid, Ipld>>,
+}
+
+impl MemStore {
+  pub fn new() -> Self { MemStore::default() }
+}
+
+impl Store for MemStore {
+  fn put(&self, ipld: &Ipld) -> Result<Cid, StoreError> {
+    let cid = ipld_cid(ipld);
+    self.blocks.borrow_mut().insert(cid, ipld.clone());
+    Ok(cid)
+  }
+
+  fn get_unchecked(&self, cid: &Cid) -> Result<Option<Ipld>, StoreError> {
+    Ok(self.blocks.borrow().get(cid).cloned())
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+
+  #[test]
+  fn test_mem_store() {
+    let store = MemStore::new();
+    let ipld = Ipld::List(vec![Ipld::Integer(1), Ipld::String("x".into())]);
+    let cid = store.put(&ipld).unwrap();
+    assert_eq!(cid, ipld_cid(&ipld));
+    assert_eq!(store.get(&cid), Ok(ipld));
+    let other = ipld_cid(&Ipld::Null);
+    assert!(!store.contains(&other));
+    assert_eq!(store.get(&other), Err(StoreError::Missing(other)));
+    store.blocks.borrow_mut().insert(cid, Ipld::Null);
+    assert_eq!(store.get(&cid), Err(StoreError::Corrupt(cid)));
+  }
+}
@@ -1,7 +1,22 @@
 use crate::{
+  anon::{
+    bind,
+    ctor,
+    uses,
+    Anon,
+  },
+  embed_error::EmbedError,
+  erase::erase,
+  hash::ipld_cid,
+  ipld_error::IpldError,
   kind::Kind,
+  meta::Meta,
   name::Name,
   position::Pos,
+  print::{
+    term,
+    Config,
+  },
   pure::Pure,
   typ_::{
     PureType,
@@ -10,8 +25,13 @@ use crate::{
   uses::Uses,
 };
 
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
 use sp_std::{
+  borrow::ToOwned,
   boxed::Box,
+  convert::TryInto,
   fmt,
 };
 
@@ -51,12 +71,609 @@ pub enum Term {
   Rewrite(Pos, Box<Term>, Box<PureType>, Box<Term>),
   /// cast by equality, `cast x y z`
   Cast(Pos, Box<Term>, Box<Term>, Box<Pure>),
+  /// reference to a global term definition
+  Ref(Pos, Name, Cid),
+}
+
+impl Term {
+  pub fn pos(&self) -> Pos {
+    match self {
+      Self::Var(pos, ..) => *pos,
+      Self::Lam(pos, ..) => *pos,
+      Self::LamTy(pos, ..) => *pos,
+      Self::App(pos, ..) => *pos,
+      Self::AppTy(pos, ..) => *pos,
+      Self::Intersect(pos, ..) => *pos,
+      Self::Let(pos, ..) => *pos,
+      Self::LetTy(pos, ..) => *pos,
+      Self::ProjectL(pos, ..) => *pos,
+      Self::ProjectR(pos, ..) => *pos,
+      Self::Refl(pos, ..) => *pos,
+      Self::Sym(pos, ..) => *pos,
+      Self::Absurd(pos, ..) => *pos,
+      Self::Rewrite(pos, ..) => *pos,
+      Self::Cast(pos, ..) => *pos,
+      Self::Ref(pos, ..) => *pos,
+    }
+  }
+
+  pub fn to_ipld(&self) -> Ipld {
+    match self {
+      Self::Var(pos, n, i) => Ipld::List(vec![
+        Ipld::Integer(0),
+        pos.to_ipld(),
+        n.to_ipld(),
+        Ipld::Integer(*i as i128),
+      ]),
+      Self::Ref(pos, n, cid) => Ipld::List(vec![
+        Ipld::Integer(15),
+        pos.to_ipld(),
+        n.to_ipld(),
+        Ipld::Link(*cid),
+      ]),
+      Self::Lam(pos, u, n, a, b) => Ipld::List(vec![
+        Ipld::Integer(1),
+        pos.to_ipld(),
+        u.to_ipld(),
+        n.to_ipld(),
+        a.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::LamTy(pos, n, k, b) => Ipld::List(vec![
+        Ipld::Integer(2),
+        pos.to_ipld(),
+        n.to_ipld(),
+        k.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::App(pos, u, f, a) => Ipld::List(vec![
+        Ipld::Integer(3),
+        pos.to_ipld(),
+        u.to_ipld(),
+        f.to_ipld(),
+        a.to_ipld(),
+      ]),
+      Self::AppTy(pos, f, a) => Ipld::List(vec![
+        Ipld::Integer(4),
+        pos.to_ipld(),
+        f.to_ipld(),
+        a.to_ipld(),
+      ]),
+      Self::Intersect(pos, x, y, a) => Ipld::List(vec![
+        Ipld::Integer(5),
+        pos.to_ipld(),
+        x.to_ipld(),
+        y.to_ipld(),
+        a.to_ipld(),
+      ]),
+      Self::Let(pos, u, n, x, b) => Ipld::List(vec![
+        Ipld::Integer(6),
+        pos.to_ipld(),
+        u.to_ipld(),
+        n.to_ipld(),
+        x.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::LetTy(pos, n, k, a, b) => Ipld::List(vec![
+        Ipld::Integer(7),
+        pos.to_ipld(),
+        n.to_ipld(),
+        k.to_ipld(),
+        a.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::ProjectL(pos, x) => {
+        Ipld::List(vec![Ipld::Integer(8), pos.to_ipld(), x.to_ipld()])
+      }
+      Self::ProjectR(pos, x) => {
+        Ipld::List(vec![Ipld::Integer(9), pos.to_ipld(), x.to_ipld()])
+      }
+      Self::Refl(pos, x, y) => Ipld::List(vec![
+        Ipld::Integer(10),
+        pos.to_ipld(),
+        x.to_ipld(),
+        y.to_ipld(),
+      ]),
+      Self::Sym(pos, x) => {
+        Ipld::List(vec![Ipld::Integer(11), pos.to_ipld(), x.to_ipld()])
+      }
+      Self::Absurd(pos, a, x) => Ipld::List(vec![
+        Ipld::Integer(12),
+        pos.to_ipld(),
+        a.to_ipld(),
+        x.to_ipld(),
+      ]),
+      Self::Rewrite(pos, x, a, y) => Ipld::List(vec![
+        Ipld::Integer(13),
+        pos.to_ipld(),
+        x.to_ipld(),
+        a.to_ipld(),
+        y.to_ipld(),
+      ]),
+      Self::Cast(pos, x, y, z) => Ipld::List(vec![
+        Ipld::Integer(14),
+        pos.to_ipld(),
+        x.to_ipld(),
+        y.to_ipld(),
+        z.to_ipld(),
+      ]),
+    }
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    let term = |x: &Ipld| Self::from_ipld(x).map(Box::new);
+    let typ = |x: &Ipld| Type::from_ipld(x).map(Box::new);
+    let kind = |x: &Ipld| Kind::from_ipld(x).map(Box::new);
+    let pure = |x: &Ipld| Pure::from_ipld(x).map(Box::new);
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [Ipld::Integer(tag), pos, args @ ..] => {
+          let pos = Pos::from_ipld(pos)?;
+          match (*tag, args) {
+            (0, [n, Ipld::Integer(i)]) => {
+              let i: u64 = (*i).try_into().map_err(IpldError::U64)?;
+              Ok(Self::Var(pos, Name::from_ipld(n)?, i))
+            }
+            (15, [n, Ipld::Link(cid)]) => {
+              Ok(Self::Ref(pos, Name::from_ipld(n)?, *cid))
+            }
+            (1, [u, n, a, b]) => Ok(Self::Lam(
+              pos,
+              Uses::from_ipld(u)?,
+              Name::from_ipld(n)?,
+              typ(a)?,
+              term(b)?,
+            )),
+            (2, [n, k, b]) => {
+              Ok(Self::LamTy(pos, Name::from_ipld(n)?, kind(k)?, term(b)?))
+            }
+            (3, [u, f, a]) => {
+              Ok(Self::App(pos, Uses::from_ipld(u)?, term(f)?, term(a)?))
+            }
+            (4, [f, a]) => Ok(Self::AppTy(pos, term(f)?, typ(a)?)),
+            (5, [x, y, a]) => {
+              Ok(Self::Intersect(pos, term(x)?, term(y)?, typ(a)?))
+            }
+            (6, [u, n, x, b]) => Ok(Self::Let(
+              pos,
+              Uses::from_ipld(u)?,
+              Name::from_ipld(n)?,
+              term(x)?,
+              term(b)?,
+            )),
+            (7, [n, k, a, b]) => Ok(Self::LetTy(
+              pos,
+              Name::from_ipld(n)?,
+              kind(k)?,
+              typ(a)?,
+              term(b)?,
+            )),
+            (8, [x]) => Ok(Self::ProjectL(pos, term(x)?)),
+            (9, [x]) => Ok(Self::ProjectR(pos, term(x)?)),
+            (10, [x, y]) => Ok(Self::Refl(pos, pure(x)?, pure(y)?)),
+            (11, [x]) => Ok(Self::Sym(pos, term(x)?)),
+            (12, [a, x]) => Ok(Self::Absurd(pos, typ(a)?, term(x)?)),
+            (13, [x, a, y]) => Ok(Self::Rewrite(
+              pos,
+              term(x)?,
+              Box::new(PureType::from_ipld(a)?),
+              term(y)?,
+            )),
+            (14, [x, y, z]) => {
+              Ok(Self::Cast(pos, term(x)?, term(y)?, pure(z)?))
+            }
+            _ => Err(IpldError::Term(ipld.to_owned())),
+          }
+        }
+        xs => Err(IpldError::Term(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::Term(xs.to_owned())),
+    }
+  }
+
+  /// Content address of the DAG-CBOR encoding of this term
+  pub fn cid(&self) -> Cid { ipld_cid(&self.to_ipld()) }
+
+  /// Splits into an anonymous tree and its metadata
+  pub fn embed(&self) -> (Anon, Meta) {
+    match self {
+      Self::Var(pos, nam, idx) => {
+        (Anon::Vari(*idx), Meta::Vari(*pos, nam.clone()))
+      }
+      Self::Ref(pos, nam, cid) => {
+        (Anon::Link(*cid), Meta::Link(*pos, nam.clone()))
+      }
+      Self::Lam(pos, u, n, a, b) => {
+        ctor("lam", *pos, vec![uses(*u), a.embed(), bind(n, b.embed())])
+      }
+      Self::LamTy(pos, n, a, b) => {
+        ctor("lam_ty", *pos, vec![a.embed(), bind(n, b.embed())])
+      }
+      Self::App(pos, u, a, b) => {
+        ctor("app", *pos, vec![uses(*u), a.embed(), b.embed()])
+      }
+      Self::AppTy(pos, a, b) => {
+        ctor("app_ty", *pos, vec![a.embed(), b.embed()])
+      }
+      Self::Intersect(pos, a, b, c) => {
+        ctor("intersect", *pos, vec![a.embed(), b.embed(), c.embed()])
+      }
+      Self::Let(pos, u, n, a, b) => {
+        ctor("let", *pos, vec![uses(*u), a.embed(), bind(n, b.embed())])
+      }
+      Self::LetTy(pos, n, a, b, c) => {
+        ctor("let_ty", *pos, vec![a.embed(), b.embed(), bind(n, c.embed())])
+      }
+      Self::ProjectL(pos, a) => ctor("project_l", *pos, vec![a.embed()]),
+      Self::ProjectR(pos, a) => ctor("project_r", *pos, vec![a.embed()]),
+      Self::Refl(pos, a, b) => ctor("refl", *pos, vec![a.embed(), b.embed()]),
+      Self::Sym(pos, a) => ctor("sym", *pos, vec![a.embed()]),
+      Self::Absurd(pos, a, b) => {
+        ctor("absurd", *pos, vec![a.embed(), b.embed()])
+      }
+      Self::Rewrite(pos, a, b, c) => {
+        ctor("rewrite", *pos, vec![a.embed(), b.embed(), c.embed()])
+      }
+      Self::Cast(pos, a, b, c) => {
+        ctor("cast", *pos, vec![a.embed(), b.embed(), c.embed()])
+      }
+    }
+  }
+
+  /// Recombines an anonymous tree with its metadata
+  pub fn unembed(anon: &Anon, meta: &Meta) -> Result<Self, EmbedError> {
+    let err =
+      || EmbedError::Term(Box::new(anon.clone()), Box::new(meta.clone()));
+    let typ = |a: &Anon, m: &Meta| Type::unembed(a, m).map(Box::new);
+    let term = |a: &Anon, m: &Meta| Self::unembed(a, m).map(Box::new);
+    let kind = |a: &Anon, m: &Meta| Kind::unembed(a, m).map(Box::new);
+    let pure = |a: &Anon, m: &Meta| Pure::unembed(a, m).map(Box::new);
+    let pure_typ = |a: &Anon, m: &Meta| PureType::unembed(a, m).map(Box::new);
+    match (anon, meta) {
+      (Anon::Vari(idx), Meta::Vari(pos, nam)) => {
+        Ok(Self::Var(*pos, nam.clone(), *idx))
+      }
+      (Anon::Link(cid), Meta::Link(pos, nam)) => {
+        Ok(Self::Ref(*pos, nam.clone(), *cid))
+      }
+      (Anon::Ctor(tag, xs), Meta::Ctor(pos, ms)) => {
+        match (tag.as_str(), xs.as_slice(), ms.as_slice()) {
+          (
+            "lam",
+            [Anon::Uses(u), aa, Anon::Bind(ba)],
+            [Meta::Leaf, am, Meta::Bind(n, bm)],
+          ) => Ok(Self::Lam(*pos, *u, n.clone(), typ(aa, am)?, term(ba, bm)?)),
+          ("lam_ty", [aa, Anon::Bind(ba)], [am, Meta::Bind(n, bm)]) => {
+            Ok(Self::LamTy(*pos, n.clone(), kind(aa, am)?, term(ba, bm)?))
+          }
+          ("app", [Anon::Uses(u), aa, ba], [Meta::Leaf, am, bm]) => {
+            Ok(Self::App(*pos, *u, term(aa, am)?, term(ba, bm)?))
+          }
+          ("app_ty", [aa, ba], [am, bm]) => {
+            Ok(Self::AppTy(*pos, term(aa, am)?, typ(ba, bm)?))
+          }
+          ("intersect", [aa, ba, ca], [am, bm, cm]) => Ok(Self::Intersect(
+            *pos,
+            term(aa, am)?,
+            term(ba, bm)?,
+            typ(ca, cm)?,
+          )),
+          (
+            "let",
+            [Anon::Uses(u), aa, Anon::Bind(ba)],
+            [Meta::Leaf, am, Meta::Bind(n, bm)],
+          ) => Ok(Self::Let(*pos, *u, n.clone(), term(aa, am)?, term(ba, bm)?)),
+          ("let_ty", [aa, ba, Anon::Bind(ca)], [am, bm, Meta::Bind(n, cm)]) => {
+            Ok(Self::LetTy(
+              *pos,
+              n.clone(),
+              kind(aa, am)?,
+              typ(ba, bm)?,
+              term(ca, cm)?,
+            ))
+          }
+          ("project_l", [aa], [am]) => Ok(Self::ProjectL(*pos, term(aa, am)?)),
+          ("project_r", [aa], [am]) => Ok(Self::ProjectR(*pos, term(aa, am)?)),
+          ("refl", [aa, ba], [am, bm]) => {
+            Ok(Self::Refl(*pos, pure(aa, am)?, pure(ba, bm)?))
+          }
+          ("sym", [aa], [am]) => Ok(Self::Sym(*pos, term(aa, am)?)),
+          ("absurd", [aa, ba], [am, bm]) => {
+            Ok(Self::Absurd(*pos, typ(aa, am)?, term(ba, bm)?))
+          }
+          ("rewrite", [aa, ba, ca], [am, bm, cm]) => Ok(Self::Rewrite(
+            *pos,
+            term(aa, am)?,
+            pure_typ(ba, bm)?,
+            term(ca, cm)?,
+          )),
+          ("cast", [aa, ba, ca], [am, bm, cm]) => {
+            Ok(Self::Cast(*pos, term(aa, am)?, term(ba, bm)?, pure(ca, cm)?))
+          }
+          _ => Err(err()),
+        }
+      }
+      _ => Err(err()),
+    }
+  }
+
+  /// Shifts the free term variables at or above `ct` by `dt` and the free
+  /// type variables at or above `cy` by `dy`
+  pub fn shift(&self, dt: i64, ct: u64, dy: i64, cy: u64) -> Self {
+    let go = |t: &Term| Box::new(t.shift(dt, ct, dy, cy));
+    match self {
+      Self::Var(pos, n, i) => {
+        let i = if *i >= ct { (*i as i64 + dt) as u64 } else { *i };
+        Self::Var(*pos, n.clone(), i)
+      }
+      Self::Ref(..) => self.clone(),
+      Self::Lam(pos, u, n, a, b) => Self::Lam(
+        *pos,
+        *u,
+        n.clone(),
+        Box::new(a.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct + 1, dy, cy)),
+      ),
+      Self::LamTy(pos, n, k, b) => Self::LamTy(
+        *pos,
+        n.clone(),
+        Box::new(k.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct, dy, cy + 1)),
+      ),
+      Self::App(pos, u, f, a) => Self::App(*pos, *u, go(f), go(a)),
+      Self::AppTy(pos, f, a) => {
+        Self::AppTy(*pos, go(f), Box::new(a.shift(dt, ct, dy, cy)))
+      }
+      Self::Intersect(pos, x, y, a) => {
+        Self::Intersect(*pos, go(x), go(y), Box::new(a.shift(dt, ct, dy, cy)))
+      }
+      Self::Let(pos, u, n, x, b) => Self::Let(
+        *pos,
+        *u,
+        n.clone(),
+        go(x),
+        Box::new(b.shift(dt, ct + 1, dy, cy)),
+      ),
+      Self::LetTy(pos, n, k, a, b) => Self::LetTy(
+        *pos,
+        n.clone(),
+        Box::new(k.shift(dt, ct, dy, cy)),
+        Box::new(a.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct, dy, cy + 1)),
+      ),
+      Self::ProjectL(pos, x) => Self::ProjectL(*pos, go(x)),
+      Self::ProjectR(pos, x) => Self::ProjectR(*pos, go(x)),
+      Self::Refl(pos, x, y) => {
+        Self::Refl(*pos, Box::new(x.shift(dt, ct)), Box::new(y.shift(dt, ct)))
+      }
+      Self::Sym(pos, x) => Self::Sym(*pos, go(x)),
+      Self::Absurd(pos, a, x) => {
+        Self::Absurd(*pos, Box::new(a.shift(dt, ct, dy, cy)), go(x))
+      }
+      Self::Rewrite(pos, x, p, y) => {
+        Self::Rewrite(*pos, go(x), Box::new(p.shift(dt, ct, dy, cy)), go(y))
+      }
+      Self::Cast(pos, x, y, z) => {
+        Self::Cast(*pos, go(x), go(y), Box::new(z.shift(dt, ct)))
+      }
+    }
+  }
+
+  /// Substitutes the term `v` for the free term variable `j`, removing its
+  /// binder. Annotations and embedded pure terms receive the erasure of `v`.
+  pub fn subst(&self, j: u64, v: &Term) -> Self {
+    let p = || erase(v);
+    let go = |t: &Term| Box::new(t.subst(j, v));
+    match self {
+      Self::Var(pos, n, i) => {
+        if *i == j {
+          v.clone()
+        }
+        else if *i > j {
+          Self::Var(*pos, n.clone(), i - 1)
+        }
+        else {
+          self.clone()
+        }
+      }
+      Self::Ref(..) => self.clone(),
+      Self::Lam(pos, u, n, a, b) => Self::Lam(
+        *pos,
+        *u,
+        n.clone(),
+        Box::new(a.subst_tm(j, &p())),
+        Box::new(b.subst(j + 1, &v.shift(1, 0, 0, 0))),
+      ),
+      Self::LamTy(pos, n, k, b) => Self::LamTy(
+        *pos,
+        n.clone(),
+        Box::new(k.subst_tm(j, &p())),
+        Box::new(b.subst(j, &v.shift(0, 0, 1, 0))),
+      ),
+      Self::App(pos, u, f, a) => Self::App(*pos, *u, go(f), go(a)),
+      Self::AppTy(pos, f, a) => {
+        Self::AppTy(*pos, go(f), Box::new(a.subst_tm(j, &p())))
+      }
+      Self::Intersect(pos, x, y, a) => {
+        Self::Intersect(*pos, go(x), go(y), Box::new(a.subst_tm(j, &p())))
+      }
+      Self::Let(pos, u, n, x, b) => Self::Let(
+        *pos,
+        *u,
+        n.clone(),
+        go(x),
+        Box::new(b.subst(j + 1, &v.shift(1, 0, 0, 0))),
+      ),
+      Self::LetTy(pos, n, k, a, b) => Self::LetTy(
+        *pos,
+        n.clone(),
+        Box::new(k.subst_tm(j, &p())),
+        Box::new(a.subst_tm(j, &p())),
+        Box::new(b.subst(j, &v.shift(0, 0, 1, 0))),
+      ),
+      Self::ProjectL(pos, x) => Self::ProjectL(*pos, go(x)),
+      Self::ProjectR(pos, x) => Self::ProjectR(*pos, go(x)),
+      Self::Refl(pos, x, y) => {
+        Self::Refl(*pos, Box::new(x.subst(j, &p())), Box::new(y.subst(j, &p())))
+      }
+      Self::Sym(pos, x) => Self::Sym(*pos, go(x)),
+      Self::Absurd(pos, a, x) => {
+        Self::Absurd(*pos, Box::new(a.subst_tm(j, &p())), go(x))
+      }
+      Self::Rewrite(pos, x, m, y) => {
+        Self::Rewrite(*pos, go(x), Box::new(m.subst_tm(j, &p())), go(y))
+      }
+      Self::Cast(pos, x, y, z) => {
+        Self::Cast(*pos, go(x), go(y), Box::new(z.subst(j, &p())))
+      }
+    }
+  }
+
+  /// Substitutes the type `v` for the free type variable `j` in the
+  /// annotations of this term, removing its binder
+  pub fn subst_ty(&self, j: u64, v: &Type) -> Self {
+    let go = |t: &Term| Box::new(t.subst_ty(j, v));
+    match self {
+      Self::Var(..) | Self::Refl(..) | Self::Ref(..) => self.clone(),
+      Self::Lam(pos, u, n, a, b) => Self::Lam(
+        *pos,
+        *u,
+        n.clone(),
+        Box::new(a.subst_ty(j, v)),
+        Box::new(b.subst_ty(j, &v.shift(1, 0, 0, 0))),
+      ),
+      Self::LamTy(pos, n, k, b) => Self::LamTy(
+        *pos,
+        n.clone(),
+        Box::new(k.subst_ty(j, v)),
+        Box::new(b.subst_ty(j + 1, &v.shift(0, 0, 1, 0))),
+      ),
+      Self::App(pos, u, f, a) => Self::App(*pos, *u, go(f), go(a)),
+      Self::AppTy(pos, f, a) => {
+        Self::AppTy(*pos, go(f), Box::new(a.subst_ty(j, v)))
+      }
+      Self::Intersect(pos, x, y, a) => {
+        Self::Intersect(*pos, go(x), go(y), Box::new(a.subst_ty(j, v)))
+      }
+      Self::Let(pos, u, n, x, b) => Self::Let(
+        *pos,
+        *u,
+        n.clone(),
+        go(x),
+        Box::new(b.subst_ty(j, &v.shift(1, 0, 0, 0))),
+      ),
+      Self::LetTy(pos, n, k, a, b) => Self::LetTy(
+        *pos,
+        n.clone(),
+        Box::new(k.subst_ty(j, v)),
+        Box::new(a.subst_ty(j, v)),
+        Box::new(b.subst_ty(j + 1, &v.shift(0, 0, 1, 0))),
+      ),
+      Self::ProjectL(pos, x) => Self::ProjectL(*pos, go(x)),
+      Self::ProjectR(pos, x) => Self::ProjectR(*pos, go(x)),
+      Self::Sym(pos, x) => Self::Sym(*pos, go(x)),
+      Self::Absurd(pos, a, x) => {
+        Self::Absurd(*pos, Box::new(a.subst_ty(j, v)), go(x))
+      }
+      Self::Rewrite(pos, x, m, y) => {
+        Self::Rewrite(*pos, go(x), Box::new(m.subst_ty(j, &v.retag())), go(y))
+      }
+      Self::Cast(pos, x, y, z) => Self::Cast(*pos, go(x), go(y), z.clone()),
+    }
+  }
+
+  /// Structural equality up to the names of binders and variables
+  pub fn alpha_eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Var(_, _, ia), Self::Var(_, _, ib)) => ia == ib,
+      (Self::Ref(_, _, ca), Self::Ref(_, _, cb)) => ca == cb,
+      (Self::Lam(_, ua, _, ta, ba), Self::Lam(_, ub, _, tb, bb)) => {
+        ua == ub && ta.alpha_eq(tb) && ba.alpha_eq(bb)
+      }
+      (Self::LamTy(_, _, ka, ba), Self::LamTy(_, _, kb, bb)) => {
+        ka.alpha_eq(kb) && ba.alpha_eq(bb)
+      }
+      (Self::App(_, ua, fa, aa), Self::App(_, ub, fb, ab)) => {
+        ua == ub && fa.alpha_eq(fb) && aa.alpha_eq(ab)
+      }
+      (Self::AppTy(_, fa, aa), Self::AppTy(_, fb, ab)) => {
+        fa.alpha_eq(fb) && aa.alpha_eq(ab)
+      }
+      (Self::Intersect(_, xa, ya, ta), Self::Intersect(_, xb, yb, tb)) => {
+        xa.alpha_eq(xb) && ya.alpha_eq(yb) && ta.alpha_eq(tb)
+      }
+      (Self::Let(_, ua, _, xa, ba), Self::Let(_, ub, _, xb, bb)) => {
+        ua == ub && xa.alpha_eq(xb) && ba.alpha_eq(bb)
+      }
+      (Self::LetTy(_, _, ka, xa, ba), Self::LetTy(_, _, kb, xb, bb)) => {
+        xa.alpha_eq(xb) && ka.alpha_eq(kb) && ba.alpha_eq(bb)
+      }
+      (Self::ProjectL(_, xa), Self::ProjectL(_, xb)) => xa.alpha_eq(xb),
+      (Self::ProjectR(_, xa), Self::ProjectR(_, xb)) => xa.alpha_eq(xb),
+      (Self::Refl(_, xa, ea), Self::Refl(_, xb, eb)) => {
+        xa.alpha_eq(xb) && ea.alpha_eq(eb)
+      }
+      (Self::Sym(_, xa), Self::Sym(_, xb)) => xa.alpha_eq(xb),
+      (Self::Absurd(_, ta, xa), Self::Absurd(_, tb, xb)) => {
+        ta.alpha_eq(tb) && xa.alpha_eq(xb)
+      }
+      (Self::Rewrite(_, xa, ta, ya), Self::Rewrite(_, xb, tb, yb)) => {
+        xa.alpha_eq(xb) && ta.alpha_eq(tb) && ya.alpha_eq(yb)
+      }
+      (Self::Cast(_, xa, ya, za), Self::Cast(_, xb, yb, zb)) => {
+        xa.alpha_eq(xb) && ya.alpha_eq(yb) && za.alpha_eq(zb)
+      }
+      _ => false,
+    }
+  }
+
+  /// Structural equality including names, ignoring only positions
+  pub fn syntactic_eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Var(_, na, ia), Self::Var(_, nb, ib)) => na == nb && ia == ib,
+      (Self::Ref(_, na, ca), Self::Ref(_, nb, cb)) => na == nb && ca == cb,
+      (Self::Lam(_, ua, na, ta, ba), Self::Lam(_, ub, nb, tb, bb)) => {
+        ua == ub && na == nb && ta == tb && ba == bb
+      }
+      (Self::LamTy(_, na, ka, ba), Self::LamTy(_, nb, kb, bb)) => {
+        na == nb && ka == kb && ba == bb
+      }
+      (Self::App(_, ua, fa, aa), Self::App(_, ub, fb, ab)) => {
+        ua == ub && fa == fb && aa == ab
+      }
+      (Self::AppTy(_, fa, aa), Self::AppTy(_, fb, ab)) => fa == fb && aa == ab,
+      (Self::Intersect(_, xa, ya, ta), Self::Intersect(_, xb, yb, tb)) => {
+        xa == xb && ya == yb && ta == tb
+      }
+      (Self::Let(_, ua, na, xa, ba), Self::Let(_, ub, nb, xb, bb)) => {
+        ua == ub && na == nb && xa == xb && ba == bb
+      }
+      (Self::LetTy(_, na, ka, xa, ba), Self::LetTy(_, nb, kb, xb, bb)) => {
+        na == nb && xa == xb && ka == kb && ba == bb
+      }
+      (Self::ProjectL(_, xa), Self::ProjectL(_, xb)) => xa == xb,
+      (Self::ProjectR(_, xa), Self::ProjectR(_, xb)) => xa == xb,
+      (Self::Refl(_, xa, ea), Self::Refl(_, xb, eb)) => xa == xb && ea == eb,
+      (Self::Sym(_, xa), Self::Sym(_, xb)) => xa == xb,
+      (Self::Absurd(_, ta, xa), Self::Absurd(_, tb, xb)) => {
+        ta == tb && xa == xb
+      }
+      (Self::Rewrite(_, xa, ta, ya), Self::Rewrite(_, xb, tb, yb)) => {
+        xa == xb && ta == tb && ya == yb
+      }
+      (Self::Cast(_, xa, ya, za), Self::Cast(_, xb, yb, zb)) => {
+        xa == xb && ya == yb && za == zb
+      }
+      _ => false,
+    }
+  }
 }
 
 impl fmt::Debug for Term {
   fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
       Self::Var(_, n, i) => fmt.debug_tuple("Var").field(&n).field(i).finish(),
+      Self::Ref(_, n, c) => fmt.debug_tuple("Ref").field(&n).field(c).finish(),
       Self::Lam(_, u, n, t, b) => {
         fmt.debug_tuple("Lam").field(&u).field(&n).field(&t).field(&b).finish()
       }
@@ -101,40 +718,167 @@ impl fmt::Debug for Term {
   }
 }
 
+impl fmt::Display for Term {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", term::print(&Config::from_formatter(f), self))
+  }
+}
+
 impl PartialEq for Term {
-  fn eq(&self, other: &Self) -> bool {
-    match (self, other) {
-      (Self::Var(_, na, ia), Self::Var(_, nb, ib)) => na == nb && ia == ib,
-      (Self::Lam(_, ua, na, ta, ba), Self::Lam(_, ub, nb, tb, bb)) => {
-        ua == ub && na == nb && ta == tb && ba == bb
-      }
-      (Self::App(_, ua, fa, aa), Self::App(_, ub, fb, ab)) => {
-        ua == ub && fa == fb && aa == ab
-      }
-      (Self::AppTy(_, fa, aa), Self::AppTy(_, fb, ab)) => fa == fb && aa == ab,
-      (Self::Intersect(_, xa, ya, ta), Self::Intersect(_, xb, yb, tb)) => {
-        xa == xb && ya == yb && ta == tb
-      }
-      (Self::Let(_, ua, na, xa, ba), Self::Let(_, ub, nb, xb, bb)) => {
-        ua == ub && na == nb && xa == xb && ba == bb
-      }
-      (Self::LetTy(_, na, ka, xa, ba), Self::LetTy(_, nb, kb, xb, bb)) => {
-        na == nb && xa == xb && ka == kb && ba == bb
+  fn eq(&self, other: &Self) -> bool { self.syntactic_eq(other) }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    gen::tests::gen_range,
+    kind::tests::arbitrary_kind,
+    parse::{
+      span::Span,
+      term::parse_term_telescope,
+      util::{
+        input_cid,
+        Ctx,
+      },
+    },
+    typ_::tests::{
+      arbitrary_open_pure,
+      arbitrary_type,
+      arbitrary_uses,
+    },
+  };
+  use quickcheck::{
+    Arbitrary,
+    Gen,
+  };
+  use sp_std::rc::Rc;
+
+  /// Annotates a pure term with arbitrary types, erased arguments and
+  /// equality proofs, so that erasing the result gives back `p`
+  pub fn embed(g: &mut Gen, p: &Pure) -> Term {
+    let pos = Pos::None;
+    let typ = |g: &mut Gen| Box::new(arbitrary_type(g, 2));
+    let term = match p {
+      Pure::Var(_, n, i) => Term::Var(pos, n.clone(), *i),
+      Pure::Lam(_, n, b) => {
+        let u = match arbitrary_uses(g) {
+          Uses::None => Uses::Many,
+          u => u,
+        };
+        Term::Lam(pos, u, n.clone(), typ(g), Box::new(embed(g, b)))
       }
-      (Self::ProjectL(_, xa), Self::ProjectL(_, xb)) => xa == xb,
-      (Self::ProjectR(_, xa), Self::ProjectR(_, xb)) => xa == xb,
-      (Self::Refl(_, xa, ea), Self::Refl(_, xb, eb)) => xa == xb && ea == eb,
-      (Self::Sym(_, xa), Self::Sym(_, xb)) => xa == xb,
-      (Self::Absurd(_, ta, xa), Self::Absurd(_, tb, xb)) => {
-        ta == tb && xa == xb
+      Pure::App(_, f, a) => {
+        let u = match arbitrary_uses(g) {
+          Uses::None => Uses::Many,
+          u => u,
+        };
+        Term::App(pos, u, Box::new(embed(g, f)), Box::new(embed(g, a)))
       }
-      (Self::Rewrite(_, xa, ta, ya), Self::Rewrite(_, xb, tb, yb)) => {
-        xa == xb && ta == tb && ya == yb
+      Pure::Ref(_, n, cid) => Term::Ref(pos, n.clone(), *cid),
+    };
+    match gen_range(g, 0..6) {
+      0 => {
+        let b = Box::new(term.shift(1, 0, 0, 0));
+        Term::Lam(pos, Uses::None, Name::from("e"), typ(g), b)
       }
-      (Self::Cast(_, xa, ya, za), Self::Cast(_, xb, yb, zb)) => {
-        xa == xb && ya == yb && za == zb
+      1 => {
+        let b = Box::new(term.shift(0, 0, 1, 0));
+        Term::LamTy(pos, Name::from("X"), Box::new(arbitrary_kind(g, 1)), b)
       }
+      2 => Term::App(
+        pos,
+        Uses::None,
+        Box::new(term),
+        Box::new(Term::Var(pos, Name::from("e"), 0)),
+      ),
+      3 => Term::Sym(pos, Box::new(term)),
+      4 => Term::Cast(
+        pos,
+        Box::new(Term::Refl(
+          pos,
+          Box::new(arbitrary_open_pure(g)),
+          Box::new(arbitrary_open_pure(g)),
+        )),
+        Box::new(Term::ProjectL(pos, Box::new(term))),
+        Box::new(p.clone()),
+      ),
+      _ => term,
+    }
+  }
+
+  #[derive(Debug, Clone)]
+  pub struct Embedded(Pure, Term);
+
+  impl Arbitrary for Embedded {
+    fn arbitrary(g: &mut Gen) -> Self {
+      let p = arbitrary_open_pure(g);
+      let t = embed(g, &p);
+      Embedded(p, t)
+    }
+  }
+
+  #[quickcheck]
+  fn term_erase_embed(x: Embedded) -> bool { erase(&x.1).syntactic_eq(&x.0) }
+
+  #[test]
+  fn test_anon_cid() {
+    fn anon(i: &str) -> Anon {
+      let ctx = Ctx::new();
+      let t =
+        parse_term_telescope(input_cid(i), Rc::default(), ctx.clone(), ctx)(
+          Span::new(i),
+        );
+      t.unwrap().1.embed().0
+    }
+    let a = anon("λ (A: Type) (x: A) => x");
+    let b = anon("λ (B : Type)  (y : B) =>\n  y");
+    assert_eq!(a.cid(), b.cid());
+    assert_ne!(a.cid(), anon("λ (A: Type) (⁰x: A) => x").cid());
+    assert_ne!(a.cid(), anon("λ (A: Type) (x: A) (y: A) => x").cid());
+  }
+
+  #[quickcheck]
+  fn term_embed(x: Embedded) -> bool {
+    let (anon, meta) = x.1.embed();
+    let anon = Anon::from_ipld(&anon.to_ipld());
+    let meta = Meta::from_ipld(&meta.to_ipld());
+    match (anon, meta) {
+      (Ok(anon), Ok(meta)) => match Term::unembed(&anon, &meta) {
+        Ok(y) => x.1.syntactic_eq(&y),
+        _ => false,
+      },
       _ => false,
     }
   }
+
+  #[quickcheck]
+  fn term_ipld(x: Embedded) -> bool {
+    match Term::from_ipld(&x.1.to_ipld()) {
+      Ok(y) => x.1.syntactic_eq(&y) && x.1.cid() == y.cid(),
+      _ => false,
+    }
+  }
+
+  #[quickcheck]
+  fn term_shift_inverse(x: Embedded, dt: u8, dy: u8) -> bool {
+    let (dt, dy) = (dt as i64, dy as i64);
+    x.1.shift(dt, 0, dy, 0).shift(-dt, 0, -dy, 0).alpha_eq(&x.1)
+  }
+
+  #[quickcheck]
+  fn term_shift_erase(x: Embedded, dt: u8) -> bool {
+    erase(&x.1.shift(dt as i64, 0, 0, 0)).alpha_eq(&x.0.shift(dt as i64, 0))
+  }
+
+  #[quickcheck]
+  fn term_subst_erase(x: Embedded, v: Embedded) -> bool {
+    erase(&x.1.subst(0, &v.1)).alpha_eq(&x.0.subst(0, &v.0))
+  }
+
+  #[quickcheck]
+  fn term_subst_fresh(x: Embedded, v: Embedded, t: Type) -> bool {
+    x.1.shift(1, 0, 0, 0).subst(0, &v.1).alpha_eq(&x.1)
+      && x.1.shift(0, 0, 1, 0).subst_ty(0, &t).alpha_eq(&x.1)
+  }
 }
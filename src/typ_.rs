@@ -1,13 +1,32 @@
 use crate::{
+  anon::{
+    bind,
+    ctor,
+    uses,
+    Anon,
+  },
+  embed_error::EmbedError,
+  hash::ipld_cid,
+  ipld_error::IpldError,
   kind::BaseKind,
+  meta::Meta,
   name::Name,
   position::Pos,
+  print::{
+    typ_,
+    Config,
+  },
   pure::Pure,
   term::Tm,
   uses::Uses,
 };
+use sp_cid::Cid;
+use sp_ipld::Ipld;
+
 use sp_std::{
+  borrow::ToOwned,
   boxed::Box,
+  convert::TryInto,
   fmt,
 };
 
@@ -30,6 +49,8 @@ pub enum BaseType<const T: Tm> {
   Eql(Pos, Box<Pure>, Box<Pure>),
   /// f x
   AppTy(Pos, Box<BaseType<T>>, Box<BaseType<T>>),
+  /// reference to a global type definition
+  Ref(Pos, Name, Cid),
 }
 
 /// The Type of Pure terms
@@ -37,35 +58,441 @@ pub type PureType = BaseType<true>;
 /// The Type of annotated Terms
 pub type Type = BaseType<false>;
 
-impl<const T: Tm> fmt::Debug for BaseType<T> {
-  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl<const T: Tm> BaseType<T> {
+  pub fn pos(&self) -> Pos {
     match self {
-      Self::Var(_, n, i) => fmt.debug_tuple("Var").field(&n).field(i).finish(),
-      Self::Lam(_, n, t, b) => {
-        fmt.debug_tuple("Lam").field(&n).field(&t).field(&b).finish()
+      Self::Var(pos, ..) => *pos,
+      Self::Lam(pos, ..) => *pos,
+      Self::LamTy(pos, ..) => *pos,
+      Self::Pi(pos, ..) => *pos,
+      Self::PiTy(pos, ..) => *pos,
+      Self::Iota(pos, ..) => *pos,
+      Self::Eql(pos, ..) => *pos,
+      Self::AppTy(pos, ..) => *pos,
+      Self::Ref(pos, ..) => *pos,
+    }
+  }
+
+  pub fn to_ipld(&self) -> Ipld {
+    match self {
+      Self::Var(pos, n, i) => Ipld::List(vec![
+        Ipld::Integer(0),
+        pos.to_ipld(),
+        n.to_ipld(),
+        Ipld::Integer(*i as i128),
+      ]),
+      Self::Lam(pos, n, a, b) => Ipld::List(vec![
+        Ipld::Integer(1),
+        pos.to_ipld(),
+        n.to_ipld(),
+        a.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::LamTy(pos, n, k, b) => Ipld::List(vec![
+        Ipld::Integer(2),
+        pos.to_ipld(),
+        n.to_ipld(),
+        k.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::Pi(pos, u, n, a, b) => Ipld::List(vec![
+        Ipld::Integer(3),
+        pos.to_ipld(),
+        u.to_ipld(),
+        n.to_ipld(),
+        a.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::PiTy(pos, n, k, b) => Ipld::List(vec![
+        Ipld::Integer(4),
+        pos.to_ipld(),
+        n.to_ipld(),
+        k.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::Iota(pos, n, a, b) => Ipld::List(vec![
+        Ipld::Integer(5),
+        pos.to_ipld(),
+        n.to_ipld(),
+        a.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::Eql(pos, a, b) => Ipld::List(vec![
+        Ipld::Integer(6),
+        pos.to_ipld(),
+        a.to_ipld(),
+        b.to_ipld(),
+      ]),
+      Self::AppTy(pos, f, a) => Ipld::List(vec![
+        Ipld::Integer(7),
+        pos.to_ipld(),
+        f.to_ipld(),
+        a.to_ipld(),
+      ]),
+      Self::Ref(pos, n, cid) => Ipld::List(vec![
+        Ipld::Integer(8),
+        pos.to_ipld(),
+        n.to_ipld(),
+        Ipld::Link(*cid),
+      ]),
+    }
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    let typ = |x: &Ipld| Self::from_ipld(x).map(Box::new);
+    let kind = |x: &Ipld| BaseKind::from_ipld(x).map(Box::new);
+    match ipld {
+      Ipld::List(xs) => match xs.as_slice() {
+        [Ipld::Integer(tag), pos, args @ ..] => {
+          let pos = Pos::from_ipld(pos)?;
+          match (*tag, args) {
+            (0, [n, Ipld::Integer(i)]) => {
+              let i: u64 = (*i).try_into().map_err(IpldError::U64)?;
+              Ok(Self::Var(pos, Name::from_ipld(n)?, i))
+            }
+            (1, [n, a, b]) => {
+              Ok(Self::Lam(pos, Name::from_ipld(n)?, typ(a)?, typ(b)?))
+            }
+            (2, [n, k, b]) => {
+              Ok(Self::LamTy(pos, Name::from_ipld(n)?, kind(k)?, typ(b)?))
+            }
+            (3, [u, n, a, b]) => Ok(Self::Pi(
+              pos,
+              Uses::from_ipld(u)?,
+              Name::from_ipld(n)?,
+              typ(a)?,
+              typ(b)?,
+            )),
+            (4, [n, k, b]) => {
+              Ok(Self::PiTy(pos, Name::from_ipld(n)?, kind(k)?, typ(b)?))
+            }
+            (5, [n, a, b]) => {
+              Ok(Self::Iota(pos, Name::from_ipld(n)?, typ(a)?, typ(b)?))
+            }
+            (6, [a, b]) => Ok(Self::Eql(
+              pos,
+              Box::new(Pure::from_ipld(a)?),
+              Box::new(Pure::from_ipld(b)?),
+            )),
+            (8, [n, Ipld::Link(cid)]) => {
+              Ok(Self::Ref(pos, Name::from_ipld(n)?, *cid))
+            }
+            (7, [f, a]) => Ok(Self::AppTy(pos, typ(f)?, typ(a)?)),
+            _ => Err(IpldError::Type(ipld.to_owned())),
+          }
+        }
+        xs => Err(IpldError::Type(Ipld::List(xs.to_owned()))),
+      },
+      xs => Err(IpldError::Type(xs.to_owned())),
+    }
+  }
+
+  /// Content address of the DAG-CBOR encoding of this type
+  pub fn cid(&self) -> Cid { ipld_cid(&self.to_ipld()) }
+
+  /// Splits into an anonymous tree and its metadata
+  pub fn embed(&self) -> (Anon, Meta) {
+    match self {
+      Self::Var(pos, nam, idx) => {
+        (Anon::Vari(*idx), Meta::Vari(*pos, nam.clone()))
       }
-      Self::LamTy(_, n, k, b) => {
-        fmt.debug_tuple("LamTy").field(&n).field(&k).field(&b).finish()
+      Self::Lam(pos, n, a, b) => {
+        ctor("lam", *pos, vec![a.embed(), bind(n, b.embed())])
       }
-      Self::Pi(_, u, n, t, b) => {
-        fmt.debug_tuple("Pi").field(&u).field(&n).field(&t).field(&b).finish()
+      Self::LamTy(pos, n, a, b) => {
+        ctor("lam_ty", *pos, vec![a.embed(), bind(n, b.embed())])
       }
-      Self::PiTy(_, n, k, b) => {
-        fmt.debug_tuple("PiTy").field(&n).field(&k).field(&b).finish()
+      Self::Pi(pos, u, n, a, b) => {
+        ctor("pi", *pos, vec![uses(*u), a.embed(), bind(n, b.embed())])
       }
-      Self::Iota(_, n, a, b) => {
-        fmt.debug_tuple("Iota").field(&n).field(&a).field(&b).finish()
+      Self::PiTy(pos, n, a, b) => {
+        ctor("pi_ty", *pos, vec![a.embed(), bind(n, b.embed())])
       }
-      Self::Eql(_, a, b) => fmt.debug_tuple("Eql").field(&a).field(&b).finish(),
-      Self::AppTy(_, f, a) => {
-        fmt.debug_tuple("AppTy").field(&f).field(&a).finish()
+      Self::Iota(pos, n, a, b) => {
+        ctor("iota", *pos, vec![a.embed(), bind(n, b.embed())])
+      }
+      Self::Eql(pos, a, b) => ctor("eql", *pos, vec![a.embed(), b.embed()]),
+      Self::AppTy(pos, a, b) => {
+        ctor("app_ty", *pos, vec![a.embed(), b.embed()])
+      }
+      Self::Ref(pos, nam, cid) => {
+        (Anon::Link(*cid), Meta::Link(*pos, nam.clone()))
       }
     }
   }
-}
 
-impl<const T: Tm> PartialEq for BaseType<T> {
-  fn eq(&self, other: &Self) -> bool {
+  /// Recombines an anonymous tree with its metadata
+  pub fn unembed(anon: &Anon, meta: &Meta) -> Result<Self, EmbedError> {
+    let err =
+      || EmbedError::Type(Box::new(anon.clone()), Box::new(meta.clone()));
+    let typ = |a: &Anon, m: &Meta| Self::unembed(a, m).map(Box::new);
+    let kind = |a: &Anon, m: &Meta| BaseKind::unembed(a, m).map(Box::new);
+    let pure = |a: &Anon, m: &Meta| Pure::unembed(a, m).map(Box::new);
+    match (anon, meta) {
+      (Anon::Vari(idx), Meta::Vari(pos, nam)) => {
+        Ok(Self::Var(*pos, nam.clone(), *idx))
+      }
+      (Anon::Link(cid), Meta::Link(pos, nam)) => {
+        Ok(Self::Ref(*pos, nam.clone(), *cid))
+      }
+      (Anon::Ctor(tag, xs), Meta::Ctor(pos, ms)) => {
+        match (tag.as_str(), xs.as_slice(), ms.as_slice()) {
+          ("lam", [aa, Anon::Bind(ba)], [am, Meta::Bind(n, bm)]) => {
+            Ok(Self::Lam(*pos, n.clone(), typ(aa, am)?, typ(ba, bm)?))
+          }
+          ("lam_ty", [aa, Anon::Bind(ba)], [am, Meta::Bind(n, bm)]) => {
+            Ok(Self::LamTy(*pos, n.clone(), kind(aa, am)?, typ(ba, bm)?))
+          }
+          (
+            "pi",
+            [Anon::Uses(u), aa, Anon::Bind(ba)],
+            [Meta::Leaf, am, Meta::Bind(n, bm)],
+          ) => Ok(Self::Pi(*pos, *u, n.clone(), typ(aa, am)?, typ(ba, bm)?)),
+          ("pi_ty", [aa, Anon::Bind(ba)], [am, Meta::Bind(n, bm)]) => {
+            Ok(Self::PiTy(*pos, n.clone(), kind(aa, am)?, typ(ba, bm)?))
+          }
+          ("iota", [aa, Anon::Bind(ba)], [am, Meta::Bind(n, bm)]) => {
+            Ok(Self::Iota(*pos, n.clone(), typ(aa, am)?, typ(ba, bm)?))
+          }
+          ("eql", [aa, ba], [am, bm]) => {
+            Ok(Self::Eql(*pos, pure(aa, am)?, pure(ba, bm)?))
+          }
+          ("app_ty", [aa, ba], [am, bm]) => {
+            Ok(Self::AppTy(*pos, typ(aa, am)?, typ(ba, bm)?))
+          }
+          _ => Err(err()),
+        }
+      }
+      _ => Err(err()),
+    }
+  }
+
+  /// Shifts the free term variables at or above `ct` by `dt` and the free
+  /// type variables at or above `cy` by `dy`
+  pub fn shift(&self, dt: i64, ct: u64, dy: i64, cy: u64) -> Self {
+    match self {
+      Self::Var(pos, n, i) => {
+        let i = if *i >= cy { (*i as i64 + dy) as u64 } else { *i };
+        Self::Var(*pos, n.clone(), i)
+      }
+      Self::Ref(..) => self.clone(),
+      Self::Lam(pos, n, a, b) => Self::Lam(
+        *pos,
+        n.clone(),
+        Box::new(a.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct + 1, dy, cy)),
+      ),
+      Self::LamTy(pos, n, k, b) => Self::LamTy(
+        *pos,
+        n.clone(),
+        Box::new(k.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct, dy, cy + 1)),
+      ),
+      Self::Pi(pos, u, n, a, b) => Self::Pi(
+        *pos,
+        *u,
+        n.clone(),
+        Box::new(a.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct + 1, dy, cy)),
+      ),
+      Self::PiTy(pos, n, k, b) => Self::PiTy(
+        *pos,
+        n.clone(),
+        Box::new(k.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct, dy, cy + 1)),
+      ),
+      Self::Iota(pos, n, a, b) => Self::Iota(
+        *pos,
+        n.clone(),
+        Box::new(a.shift(dt, ct, dy, cy)),
+        Box::new(b.shift(dt, ct + 1, dy, cy)),
+      ),
+      Self::Eql(pos, a, b) => {
+        Self::Eql(*pos, Box::new(a.shift(dt, ct)), Box::new(b.shift(dt, ct)))
+      }
+      Self::AppTy(pos, f, a) => Self::AppTy(
+        *pos,
+        Box::new(f.shift(dt, ct, dy, cy)),
+        Box::new(a.shift(dt, ct, dy, cy)),
+      ),
+    }
+  }
+
+  /// Substitutes the type `v` for the free type variable `j`, removing its
+  /// binder
+  pub fn subst_ty(&self, j: u64, v: &Self) -> Self {
+    match self {
+      Self::Var(pos, n, i) => {
+        if *i == j {
+          v.clone()
+        }
+        else if *i > j {
+          Self::Var(*pos, n.clone(), i - 1)
+        }
+        else {
+          self.clone()
+        }
+      }
+      Self::Ref(..) => self.clone(),
+      Self::Lam(pos, n, a, b) => Self::Lam(
+        *pos,
+        n.clone(),
+        Box::new(a.subst_ty(j, v)),
+        Box::new(b.subst_ty(j, &v.shift(1, 0, 0, 0))),
+      ),
+      Self::LamTy(pos, n, k, b) => Self::LamTy(
+        *pos,
+        n.clone(),
+        Box::new(k.subst_ty(j, v)),
+        Box::new(b.subst_ty(j + 1, &v.shift(0, 0, 1, 0))),
+      ),
+      Self::Pi(pos, u, n, a, b) => Self::Pi(
+        *pos,
+        *u,
+        n.clone(),
+        Box::new(a.subst_ty(j, v)),
+        Box::new(b.subst_ty(j, &v.shift(1, 0, 0, 0))),
+      ),
+      Self::PiTy(pos, n, k, b) => Self::PiTy(
+        *pos,
+        n.clone(),
+        Box::new(k.subst_ty(j, v)),
+        Box::new(b.subst_ty(j + 1, &v.shift(0, 0, 1, 0))),
+      ),
+      Self::Iota(pos, n, a, b) => Self::Iota(
+        *pos,
+        n.clone(),
+        Box::new(a.subst_ty(j, v)),
+        Box::new(b.subst_ty(j, &v.shift(1, 0, 0, 0))),
+      ),
+      Self::Eql(..) => self.clone(),
+      Self::AppTy(pos, f, a) => Self::AppTy(
+        *pos,
+        Box::new(f.subst_ty(j, v)),
+        Box::new(a.subst_ty(j, v)),
+      ),
+    }
+  }
+
+  /// Substitutes the pure term `v` for the free term variable `j` in the
+  /// equalities of this type, removing its binder
+  pub fn subst_tm(&self, j: u64, v: &Pure) -> Self {
+    match self {
+      Self::Var(..) | Self::Ref(..) => self.clone(),
+      Self::Lam(pos, n, a, b) => Self::Lam(
+        *pos,
+        n.clone(),
+        Box::new(a.subst_tm(j, v)),
+        Box::new(b.subst_tm(j + 1, &v.shift(1, 0))),
+      ),
+      Self::LamTy(pos, n, k, b) => Self::LamTy(
+        *pos,
+        n.clone(),
+        Box::new(k.subst_tm(j, v)),
+        Box::new(b.subst_tm(j, v)),
+      ),
+      Self::Pi(pos, u, n, a, b) => Self::Pi(
+        *pos,
+        *u,
+        n.clone(),
+        Box::new(a.subst_tm(j, v)),
+        Box::new(b.subst_tm(j + 1, &v.shift(1, 0))),
+      ),
+      Self::PiTy(pos, n, k, b) => Self::PiTy(
+        *pos,
+        n.clone(),
+        Box::new(k.subst_tm(j, v)),
+        Box::new(b.subst_tm(j, v)),
+      ),
+      Self::Iota(pos, n, a, b) => Self::Iota(
+        *pos,
+        n.clone(),
+        Box::new(a.subst_tm(j, v)),
+        Box::new(b.subst_tm(j + 1, &v.shift(1, 0))),
+      ),
+      Self::Eql(pos, a, b) => {
+        Self::Eql(*pos, Box::new(a.subst(j, v)), Box::new(b.subst(j, v)))
+      }
+      Self::AppTy(pos, f, a) => Self::AppTy(
+        *pos,
+        Box::new(f.subst_tm(j, v)),
+        Box::new(a.subst_tm(j, v)),
+      ),
+    }
+  }
+
+  /// Reinterprets this type at another purity
+  pub fn retag<const U: Tm>(&self) -> BaseType<U> {
+    match self {
+      Self::Var(pos, n, i) => BaseType::Var(*pos, n.clone(), *i),
+      Self::Ref(pos, n, cid) => BaseType::Ref(*pos, n.clone(), *cid),
+      Self::Lam(pos, n, a, b) => {
+        BaseType::Lam(*pos, n.clone(), Box::new(a.retag()), Box::new(b.retag()))
+      }
+      Self::LamTy(pos, n, k, b) => BaseType::LamTy(
+        *pos,
+        n.clone(),
+        Box::new(k.retag()),
+        Box::new(b.retag()),
+      ),
+      Self::Pi(pos, u, n, a, b) => BaseType::Pi(
+        *pos,
+        *u,
+        n.clone(),
+        Box::new(a.retag()),
+        Box::new(b.retag()),
+      ),
+      Self::PiTy(pos, n, k, b) => BaseType::PiTy(
+        *pos,
+        n.clone(),
+        Box::new(k.retag()),
+        Box::new(b.retag()),
+      ),
+      Self::Iota(pos, n, a, b) => BaseType::Iota(
+        *pos,
+        n.clone(),
+        Box::new(a.retag()),
+        Box::new(b.retag()),
+      ),
+      Self::Eql(pos, a, b) => BaseType::Eql(*pos, a.clone(), b.clone()),
+      Self::AppTy(pos, f, a) => {
+        BaseType::AppTy(*pos, Box::new(f.retag()), Box::new(a.retag()))
+      }
+    }
+  }
+
+  /// Structural equality up to the names of binders and variables
+  pub fn alpha_eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Var(_, _, ia), Self::Var(_, _, ib)) => ia == ib,
+      (Self::Lam(_, _, ta, ba), Self::Lam(_, _, tb, bb)) => {
+        ta.alpha_eq(tb) && ba.alpha_eq(bb)
+      }
+      (Self::LamTy(_, _, ka, ba), Self::LamTy(_, _, kb, bb)) => {
+        ka.alpha_eq(kb) && ba.alpha_eq(bb)
+      }
+      (Self::Pi(_, ua, _, ta, ba), Self::Pi(_, ub, _, tb, bb)) => {
+        ua == ub && ta.alpha_eq(tb) && ba.alpha_eq(bb)
+      }
+      (Self::PiTy(_, _, ka, ba), Self::PiTy(_, _, kb, bb)) => {
+        ka.alpha_eq(kb) && ba.alpha_eq(bb)
+      }
+      (Self::Iota(_, _, ka, ba), Self::Iota(_, _, kb, bb)) => {
+        ka.alpha_eq(kb) && ba.alpha_eq(bb)
+      }
+      (Self::Eql(_, aa, ba), Self::Eql(_, ab, bb)) => {
+        aa.alpha_eq(ab) && ba.alpha_eq(bb)
+      }
+      (Self::AppTy(_, fa, aa), Self::AppTy(_, fb, ab)) => {
+        fa.alpha_eq(fb) && aa.alpha_eq(ab)
+      }
+      (Self::Ref(_, _, ca), Self::Ref(_, _, cb)) => ca == cb,
+      _ => false,
+    }
+  }
+
+  /// Structural equality including names, ignoring only positions
+  pub fn syntactic_eq(&self, other: &Self) -> bool {
     match (self, other) {
       (Self::Var(_, na, ia), Self::Var(_, nb, ib)) => na == nb && ia == ib,
       (Self::Lam(_, na, ta, ba), Self::Lam(_, nb, tb, bb)) => {
@@ -85,7 +512,181 @@ impl<const T: Tm> PartialEq for BaseType<T> {
       }
       (Self::Eql(_, aa, ba), Self::Eql(_, ab, bb)) => aa == ab && ba == bb,
       (Self::AppTy(_, fa, aa), Self::AppTy(_, fb, ab)) => fa == fb && aa == ab,
+      (Self::Ref(_, na, ca), Self::Ref(_, nb, cb)) => na == nb && ca == cb,
+      _ => false,
+    }
+  }
+}
+
+impl<const T: Tm> fmt::Debug for BaseType<T> {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Var(_, n, i) => fmt.debug_tuple("Var").field(&n).field(i).finish(),
+      Self::Lam(_, n, t, b) => {
+        fmt.debug_tuple("Lam").field(&n).field(&t).field(&b).finish()
+      }
+      Self::LamTy(_, n, k, b) => {
+        fmt.debug_tuple("LamTy").field(&n).field(&k).field(&b).finish()
+      }
+      Self::Pi(_, u, n, t, b) => {
+        fmt.debug_tuple("Pi").field(&u).field(&n).field(&t).field(&b).finish()
+      }
+      Self::PiTy(_, n, k, b) => {
+        fmt.debug_tuple("PiTy").field(&n).field(&k).field(&b).finish()
+      }
+      Self::Iota(_, n, a, b) => {
+        fmt.debug_tuple("Iota").field(&n).field(&a).field(&b).finish()
+      }
+      Self::Eql(_, a, b) => fmt.debug_tuple("Eql").field(&a).field(&b).finish(),
+      Self::AppTy(_, f, a) => {
+        fmt.debug_tuple("AppTy").field(&f).field(&a).finish()
+      }
+      Self::Ref(_, n, c) => fmt.debug_tuple("Ref").field(&n).field(c).finish(),
+    }
+  }
+}
+
+impl<const T: Tm> fmt::Display for BaseType<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", typ_::print(&Config::from_formatter(f), self))
+  }
+}
+
+impl<const T: Tm> PartialEq for BaseType<T> {
+  fn eq(&self, other: &Self) -> bool { self.syntactic_eq(other) }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+  use crate::{
+    gen::{
+      pure::{
+        tests::arbitrary_pure,
+        Pure as GenPure,
+      },
+      tests::{
+        arbitrary_name,
+        gen_range,
+      },
+    },
+    kind::tests::arbitrary_kind,
+  };
+  use quickcheck::{
+    Arbitrary,
+    Gen,
+  };
+  use sp_im::Vector;
+  use sp_std::mem;
+
+  pub fn arbitrary_uses(g: &mut Gen) -> Uses {
+    match gen_range(g, 0..4) {
+      0 => Uses::None,
+      1 => Uses::Once,
+      2 => Uses::Affi,
+      _ => Uses::Many,
+    }
+  }
+
+  /// A pure term with up to two free variables
+  pub fn arbitrary_open_pure(g: &mut Gen) -> Pure {
+    let ctx = Vector::from(vec![arbitrary_name(g), arbitrary_name(g)]);
+    let gen_pure = arbitrary_pure(g, ctx);
+    unsafe { mem::transmute::<GenPure, Pure>(gen_pure) }
+  }
+
+  /// An arbitrary, not necessarily well-scoped, type of at most `depth`
+  /// nested constructors
+  pub fn arbitrary_type<const T: Tm>(g: &mut Gen, depth: usize) -> BaseType<T> {
+    let case = if depth == 0 { gen_range(g, 0..2) } else { gen_range(g, 0..8) };
+    let typ = |g: &mut Gen| Box::new(arbitrary_type(g, depth - 1));
+    let kind = |g: &mut Gen| Box::new(arbitrary_kind(g, depth - 1));
+    let pos = Pos::None;
+    match case {
+      0 => BaseType::Var(pos, arbitrary_name(g), gen_range(g, 0..4) as u64),
+      1 => BaseType::Eql(
+        pos,
+        Box::new(arbitrary_open_pure(g)),
+        Box::new(arbitrary_open_pure(g)),
+      ),
+      2 => BaseType::Lam(pos, arbitrary_name(g), typ(g), typ(g)),
+      3 => BaseType::LamTy(pos, arbitrary_name(g), kind(g), typ(g)),
+      4 => {
+        BaseType::Pi(pos, arbitrary_uses(g), arbitrary_name(g), typ(g), typ(g))
+      }
+      5 => BaseType::PiTy(pos, arbitrary_name(g), kind(g), typ(g)),
+      6 => BaseType::Iota(pos, arbitrary_name(g), typ(g), typ(g)),
+      _ => BaseType::AppTy(pos, typ(g), typ(g)),
+    }
+  }
+
+  impl<const T: Tm> Arbitrary for BaseType<T> {
+    fn arbitrary(g: &mut Gen) -> Self { arbitrary_type(g, 4) }
+  }
+
+  #[quickcheck]
+  fn type_embed(x: Type) -> bool {
+    let (anon, meta) = x.embed();
+    let anon = Anon::from_ipld(&anon.to_ipld());
+    let meta = Meta::from_ipld(&meta.to_ipld());
+    match (anon, meta) {
+      (Ok(anon), Ok(meta)) => match Type::unembed(&anon, &meta) {
+        Ok(y) => x.syntactic_eq(&y),
+        _ => false,
+      },
       _ => false,
     }
   }
+
+  #[quickcheck]
+  fn type_ipld(x: Type) -> bool {
+    match Type::from_ipld(&x.to_ipld()) {
+      Ok(y) => x.syntactic_eq(&y) && x.cid() == y.cid(),
+      _ => false,
+    }
+  }
+
+  #[quickcheck]
+  fn type_shift_zero(x: Type) -> bool { x.shift(0, 0, 0, 0).alpha_eq(&x) }
+
+  #[quickcheck]
+  fn type_shift_inverse(x: Type, dt: u8, dy: u8, ct: u8, cy: u8) -> bool {
+    let (dt, dy, ct, cy) = (dt as i64, dy as i64, ct as u64, cy as u64);
+    x.shift(dt, ct, dy, cy).shift(-dt, ct, -dy, cy).alpha_eq(&x)
+  }
+
+  #[quickcheck]
+  fn type_subst_ty_fresh(x: Type, v: Type) -> bool {
+    x.shift(0, 0, 1, 0).subst_ty(0, &v).alpha_eq(&x)
+  }
+
+  #[quickcheck]
+  fn type_subst_tm_fresh(x: Type, v: Pure) -> bool {
+    x.shift(1, 0, 0, 0).subst_tm(0, &v).alpha_eq(&x)
+  }
+
+  #[quickcheck]
+  fn type_subst_tm_eql(a: Pure, b: Pure, v: Pure) -> bool {
+    // substitution goes under a term binder into the equality
+    let eql = |a: &Pure, b: &Pure| -> Type {
+      BaseType::Eql(Pos::None, Box::new(a.clone()), Box::new(b.clone()))
+    };
+    let iota = |x: Type| -> Type {
+      BaseType::Iota(
+        Pos::None,
+        Name::from("x"),
+        Box::new(eql(&a, &b)),
+        Box::new(x),
+      )
+    };
+    let lhs = iota(eql(&a.shift(1, 0), &b.shift(1, 0))).subst_tm(0, &v);
+    let (sa, sb) = (a.subst(0, &v), b.subst(0, &v));
+    let rhs = BaseType::Iota(
+      Pos::None,
+      Name::from("x"),
+      Box::new(eql(&sa, &sb)),
+      Box::new(eql(&sa.shift(1, 0), &sb.shift(1, 0))),
+    );
+    lhs.alpha_eq(&rhs)
+  }
 }
@@ -1,3 +1,9 @@
+use crate::ipld_error::IpldError;
+
+use sp_ipld::Ipld;
+
+use sp_std::borrow::ToOwned;
+
 /// Quantitative Type Theory usage multiplicity
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Uses {
@@ -10,3 +16,24 @@ pub enum Uses {
   /// unrestricted multiplictiy ⁺x
   Many,
 }
+
+impl Uses {
+  pub fn to_ipld(self) -> Ipld {
+    match self {
+      Self::None => Ipld::Integer(0),
+      Self::Once => Ipld::Integer(1),
+      Self::Affi => Ipld::Integer(2),
+      Self::Many => Ipld::Integer(3),
+    }
+  }
+
+  pub fn from_ipld(ipld: &Ipld) -> Result<Self, IpldError> {
+    match ipld {
+      Ipld::Integer(0) => Ok(Self::None),
+      Ipld::Integer(1) => Ok(Self::Once),
+      Ipld::Integer(2) => Ok(Self::Affi),
+      Ipld::Integer(3) => Ok(Self::Many),
+      xs => Err(IpldError::Uses(xs.to_owned())),
+    }
+  }
+}